@@ -0,0 +1,3 @@
+pub mod options;
+
+pub use self::options::{ParseOptions, VisibilityFilter};
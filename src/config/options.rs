@@ -0,0 +1,110 @@
+use crate::error::{Result, SynParserError};
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ANCHOR: ParseOptions
+/// Options controlling which items the parser records and how much of them it keeps.
+///
+/// By default every item is recorded whatever its visibility, along with bodies,
+/// comments, tests and private traits, while only `#[macro_export]` macros are kept and
+/// parse errors fail the analysis. Fields missing from a config file take their default
+/// value, so a file only needs the options it changes:
+///
+/// ```toml
+/// visibility = "Public"
+/// include_bodies = false
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseOptions {
+    // Which functions, inherent methods, structs, enums, type aliases, unions, constants,
+    // statics and items of `extern` blocks are stored. Methods of traits and trait impls
+    // are stored whenever their trait is.
+    pub visibility: VisibilityFilter,
+    // Only store `macro_rules!` macros marked `#[macro_export]`
+    pub exported_macros_only: bool,
+    // Store function bodies, macro bodies and the initializers of constants and statics
+    pub include_bodies: bool,
+    // Truncate stored bodies to at most this many characters
+    pub max_body_length: Option<usize>,
+    // Also store bodies in token-string form (`fn foo () { let x = 1 ; }`), which drops
+    // comments and formatting
+    pub normalized_bodies: bool,
+    // Record regular comments and attach them to nodes
+    pub include_comments: bool,
+    // Analyze `#[cfg(test)]` items and `#[test]` functions
+    pub include_tests: bool,
+    // Store traits that are not `pub`
+    pub include_private_traits: bool,
+    // Record unparsable items and missing module files as diagnostics instead of failing
+    pub error_tolerant: bool,
+}
+//ANCHOR_END: ParseOptions
+
+// Visibility an item needs in order to be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VisibilityFilter {
+    // Only `pub` items
+    Public,
+    // `pub` items and items restricted to a path, like `pub(crate)` or `pub(super)`
+    Restricted,
+    // Every item, including private ones
+    All,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            visibility: VisibilityFilter::All,
+            exported_macros_only: true,
+            include_bodies: true,
+            max_body_length: None,
+            normalized_bodies: false,
+            include_comments: true,
+            include_tests: true,
+            include_private_traits: true,
+            error_tolerant: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Load options from a `.ron` or `.toml` file, chosen by extension.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|err| SynParserError::io(path, err))?;
+        let config_error = |message: String| SynParserError::Config {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => ron::from_str(&contents).map_err(|err| config_error(err.to_string())),
+            Some("toml") => toml::from_str(&contents).map_err(|err| config_error(err.to_string())),
+            _ => Err(config_error(
+                "expected a `.ron` or `.toml` file".to_string(),
+            )),
+        }
+    }
+
+    // Whether an item with the given visibility passes the visibility filter
+    pub fn allows_visibility(&self, vis: &syn::Visibility) -> bool {
+        match self.visibility {
+            VisibilityFilter::Public => matches!(vis, syn::Visibility::Public(_)),
+            VisibilityFilter::Restricted => !matches!(vis, syn::Visibility::Inherited),
+            VisibilityFilter::All => true,
+        }
+    }
+
+    // Apply `include_bodies` and `max_body_length` to a body
+    pub fn body(&self, body: String) -> Option<String> {
+        if !self.include_bodies {
+            return None;
+        }
+        match self.max_body_length {
+            Some(max) if body.chars().count() > max => Some(body.chars().take(max).collect()),
+            _ => Some(body),
+        }
+    }
+}
@@ -1,2 +1,150 @@
 // Custom Error Type and Handling
-// todo
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors returned by the public API of this crate.
+///
+/// Every variant that comes from a file carries its path, so callers processing many
+/// files can report which one broke and carry on with the rest.
+#[derive(Debug)]
+pub enum SynParserError {
+    // Reading a source file or manifest failed
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    // Writing a serialized graph or cache failed
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    // A source file is not valid Rust. `line` and `column` are 1-based.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    // A Cargo.toml could not be parsed
+    Manifest {
+        path: PathBuf,
+        message: String,
+    },
+    // A `ParseOptions` file could not be parsed
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    // Serializing the graph failed
+    Serialization(String),
+    // A crate root or module file could not be found. `path` is the file that
+    // needed it, or the directory searched for a crate root.
+    Resolution {
+        path: PathBuf,
+        message: String,
+    },
+    // Watching a crate directory for changes failed
+    Watch {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, SynParserError>;
+
+impl SynParserError {
+    pub fn io(path: &Path, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn write(path: &Path, source: std::io::Error) -> Self {
+        Self::Write {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    // Wrap a `syn` parse error, keeping the position of its first token
+    pub fn parse(path: &Path, err: &syn::Error) -> Self {
+        let start = err.span().start();
+        Self::Parse {
+            path: path.to_path_buf(),
+            line: start.line,
+            column: start.column + 1,
+            message: err.to_string(),
+        }
+    }
+
+    pub fn resolution(path: &Path, message: impl Into<String>) -> Self {
+        Self::Resolution {
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+
+    // The file (or directory) the error relates to, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Io { path, .. }
+            | Self::Write { path, .. }
+            | Self::Parse { path, .. }
+            | Self::Manifest { path, .. }
+            | Self::Config { path, .. }
+            | Self::Resolution { path, .. }
+            | Self::Watch { path, .. } => Some(path),
+            Self::Serialization(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for SynParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            Self::Write { path, source } => {
+                write!(f, "failed to write {}: {}", path.display(), source)
+            }
+            Self::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            Self::Manifest { path, message } => {
+                write!(f, "invalid manifest {}: {}", path.display(), message)
+            }
+            Self::Config { path, message } => {
+                write!(f, "invalid options file {}: {}", path.display(), message)
+            }
+            Self::Serialization(message) => write!(f, "serialization failed: {}", message),
+            Self::Resolution { path, message } => write!(f, "{}: {}", path.display(), message),
+            Self::Watch { path, message } => {
+                write!(f, "cannot watch {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SynParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } | Self::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ron::Error> for SynParserError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialization(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for SynParserError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Serialization(err.to_string())
+    }
+}
@@ -1,7 +1,17 @@
+pub mod config;
+pub mod error;
 pub mod parser;
 pub mod serialization;
 // pub mod analysis; // For future code analysis features
 
 // Re-export key items for easier access
-pub use parser::{analyze_code, CodeGraph};
+pub use config::ParseOptions;
+pub use parser::{
+    analyze_code, analyze_code_tolerant, analyze_code_with_options, analyze_crate,
+    analyze_crate_incremental, analyze_crate_incremental_with_options, analyze_crate_tolerant,
+    analyze_crate_with_options, analyze_workspace, analyze_workspace_with_options, api_diff, diff,
+    public_api, AnalysisCache, CodeGraph, CrateWatcher, GraphDiff, GraphEvent, WorkspaceGraph,
+};
+pub use error::SynParserError;
+pub use serialization::json::save_to_json;
 pub use serialization::ron::save_to_ron;
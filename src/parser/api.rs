@@ -0,0 +1,317 @@
+use crate::parser::graph::{CodeGraph, NodeRef};
+use crate::parser::nodes::{MacroKind, ModuleNode, NodeId, TraitRef};
+use crate::parser::relations::RelationKind;
+use crate::parser::resolve::Resolver;
+use crate::parser::types::VisibilityKind;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// ANCHOR: EffectiveVisibility
+// How far an item can be seen, once the modules it is declared in and the re-exports
+// that name it are taken into account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EffectiveVisibility {
+    // Reachable from other crates through at least one public path
+    Public,
+    // Visible outside the module it is declared in, but not to other crates
+    Crate,
+    // Only visible in the module it is declared in and its submodules
+    Private,
+}
+//ANCHOR_END: EffectiveVisibility
+
+// An item of the graph with its effective visibility
+#[derive(Debug, Clone)]
+pub struct ApiItem<'a> {
+    pub node: NodeRef<'a>,
+    pub visibility: EffectiveVisibility,
+    // Paths other crates can name the item by, starting with the name of the root
+    // module. Empty unless the item is `Public`. A `pub` item in a private module is
+    // only reachable through re-exports, so its canonical path is not among them.
+    pub public_paths: Vec<String>,
+}
+
+// Effective visibility of every item of a graph, see `public_api`
+#[derive(Debug)]
+pub struct PublicApi<'a> {
+    // Every node with a canonical path, in the order of `CodeGraph::path_nodes`
+    pub items: Vec<ApiItem<'a>>,
+}
+
+impl<'a> PublicApi<'a> {
+    /// Items other crates can use, with the paths they can use them by.
+    pub fn public_items(&self) -> impl Iterator<Item = &ApiItem<'a>> {
+        self.items
+            .iter()
+            .filter(|item| item.visibility == EffectiveVisibility::Public)
+    }
+
+    /// Find an item by ID.
+    pub fn get(&self, id: NodeId) -> Option<&ApiItem<'a>> {
+        self.items.iter().find(|item| item.node.id() == id)
+    }
+}
+
+/// Compute the effective visibility of every item in `graph`, and the public paths
+/// through which other crates can reach it.
+///
+/// An item is reachable through its own path when it and every module on the way to it
+/// are `pub`, and through every `pub use` that re-exports it (or one of the modules it
+/// is in) from a reachable module. Methods are reachable through their trait, or
+/// through the type of their impl.
+pub fn public_api(graph: &CodeGraph) -> PublicApi<'_> {
+    let Some(root) = graph.modules.first() else {
+        return PublicApi { items: Vec::new() };
+    };
+
+    let mut reachability = Reachability {
+        resolver: Resolver::new(graph, root.id),
+        modules: graph.modules.iter().map(|m| (m.id, m)).collect(),
+        nodes: graph.path_nodes().map(|node| (node.id(), node)).collect(),
+        paths: HashMap::new(),
+    };
+    reachability.add_path(root.id, root.path.clone());
+    reachability.expand(root.id, &root.path, &mut vec![root.id]);
+
+    // Exported macros are used through the crate root, whatever module defines them
+    for mac in &graph.macros {
+        if mac.kind == MacroKind::DeclarativeMacro && mac.visibility == VisibilityKind::Public {
+            reachability.add_path(mac.id, mac.path.clone());
+        }
+    }
+
+    let Reachability {
+        mut paths, nodes, ..
+    } = reachability;
+    let mut containers = HashMap::new();
+    for trait_node in graph.traits.iter().chain(&graph.private_traits) {
+        let trait_paths = paths.get(&trait_node.id).cloned().unwrap_or_default();
+        for method in &trait_node.methods {
+            let method_paths = trait_paths
+                .iter()
+                .map(|path| format!("{}::{}", path, method.name))
+                .collect();
+            paths.insert(method.id, method_paths);
+            containers.insert(method.id, trait_node.id);
+        }
+    }
+    for impl_node in &graph.impls {
+        let Some(self_item) = graph.relations.iter().find_map(|r| {
+            (r.source == impl_node.self_type && r.kind == RelationKind::ResolvesTo)
+                .then_some(r.target)
+        }) else {
+            continue;
+        };
+        let self_paths = paths.get(&self_item).cloned().unwrap_or_default();
+        let trait_path = match &impl_node.trait_ref {
+            Some(TraitRef::Local(id)) => paths.get(id).and_then(|p| p.first()).cloned(),
+            Some(TraitRef::External { path }) => Some(path.join("::")),
+            _ => None,
+        };
+
+        for method in &impl_node.methods {
+            let method_paths = match (&impl_node.trait_type, &trait_path) {
+                // Methods of trait impls are public whenever the type and trait are
+                (Some(_), Some(trait_path)) => self_paths
+                    .first()
+                    .map(|self_path| format!("<{} as {}>::{}", self_path, trait_path, method.name))
+                    .into_iter()
+                    .collect(),
+                (Some(_), None) => Vec::new(),
+                (None, _) if method.visibility == VisibilityKind::Public => self_paths
+                    .iter()
+                    .map(|path| format!("{}::{}", path, method.name))
+                    .collect(),
+                (None, _) => Vec::new(),
+            };
+            paths.insert(method.id, method_paths);
+            containers.insert(method.id, self_item);
+        }
+    }
+
+    // Module each item is declared in, by its path from the crate root, to compare with
+    // the modules `pub(in path)` restrictions reach
+    let declared_in: HashMap<NodeId, Vec<String>> = graph
+        .modules
+        .iter()
+        .flat_map(|module| {
+            let path: Vec<String> = std::iter::once("crate")
+                .chain(module.path.split("::").skip(1))
+                .map(str::to_string)
+                .collect();
+            module
+                .items
+                .iter()
+                .chain(&module.submodules)
+                .map(move |id| (*id, path.clone()))
+        })
+        .collect();
+
+    // Items without `pub` are recorded as `pub(super)`, so that counts as private too.
+    // Explicit restrictions are private when they only reach the item's own module.
+    let own_visibility = |node: &NodeRef| match node.visibility() {
+        VisibilityKind::Inherited => EffectiveVisibility::Private,
+        VisibilityKind::Restricted(path) if path == &["super"] => EffectiveVisibility::Private,
+        VisibilityKind::Restricted(path) if path != &["crate"] => {
+            let item = containers.get(&node.id()).copied().unwrap_or(node.id());
+            if declared_in.get(&item) == Some(path) {
+                EffectiveVisibility::Private
+            } else {
+                EffectiveVisibility::Crate
+            }
+        }
+        _ => EffectiveVisibility::Crate,
+    };
+    let items = graph
+        .path_nodes()
+        .map(|node| {
+            let public_paths = paths.remove(&node.id()).unwrap_or_default();
+            let visibility = if !public_paths.is_empty() {
+                EffectiveVisibility::Public
+            } else {
+                // Methods are at most as visible as their trait or type
+                let container = containers.get(&node.id()).and_then(|id| nodes.get(id));
+                match (own_visibility(&node), container) {
+                    (EffectiveVisibility::Crate, Some(container)) => own_visibility(container),
+                    (visibility, _) => visibility,
+                }
+            };
+            ApiItem {
+                node,
+                visibility,
+                public_paths,
+            }
+        })
+        .collect();
+
+    PublicApi { items }
+}
+
+// Walks the modules reachable from other crates, collecting the public paths of items
+struct Reachability<'a> {
+    resolver: Resolver<'a>,
+    modules: HashMap<NodeId, &'a ModuleNode>,
+    nodes: HashMap<NodeId, NodeRef<'a>>,
+    paths: HashMap<NodeId, Vec<String>>,
+}
+
+impl<'a> Reachability<'a> {
+    // Record the public names of `module`, which is reachable as `prefix`. `chain` holds
+    // the modules being expanded, so re-exports that form cycles stop.
+    fn expand(&mut self, module: NodeId, prefix: &str, chain: &mut Vec<NodeId>) {
+        let Some(module_node) = self.modules.get(&module).copied() else {
+            return;
+        };
+
+        for id in module_node.items.iter().chain(&module_node.submodules) {
+            let Some(node) = self.nodes.get(id).copied() else {
+                continue;
+            };
+            // `macro_rules!` macros are not named through paths, see `public_api`
+            if let NodeRef::Macro(mac) = node {
+                if mac.kind == MacroKind::DeclarativeMacro {
+                    continue;
+                }
+            }
+            if *node.visibility() == VisibilityKind::Public {
+                self.add_reachable(*id, format!("{}::{}", prefix, node.name()), chain);
+            }
+        }
+
+        for import in &module_node.imports {
+            if import.visibility != VisibilityKind::Public {
+                continue;
+            }
+            if import.is_glob {
+                if let Some(glob_module) = self.resolver.resolve_module(module, &import.path) {
+                    if !chain.contains(&glob_module) {
+                        chain.push(glob_module);
+                        self.expand(glob_module, prefix, chain);
+                        chain.pop();
+                    }
+                }
+                continue;
+            }
+
+            let Some(name) = import.name() else {
+                continue;
+            };
+            for id in self.imported(module, &import.path, &mut HashSet::new()) {
+                self.add_reachable(id, format!("{}::{}", prefix, name), chain);
+            }
+        }
+    }
+
+    fn add_reachable(&mut self, id: NodeId, path: String, chain: &mut Vec<NodeId>) {
+        self.add_path(id, path.clone());
+        if self.modules.contains_key(&id) && !chain.contains(&id) {
+            chain.push(id);
+            self.expand(id, &path, chain);
+            chain.pop();
+        }
+    }
+
+    fn add_path(&mut self, id: NodeId, path: String) {
+        let paths = self.paths.entry(id).or_default();
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    // Items an import in `module` names. A name can stand for items of several
+    // namespaces at once, like a function and a module.
+    fn imported(
+        &self,
+        module: NodeId,
+        path: &'a [String],
+        seen: &mut HashSet<(NodeId, &'a str)>,
+    ) -> Vec<NodeId> {
+        let Some((name, parent_path)) = path.split_last() else {
+            return Vec::new();
+        };
+        let parent = if parent_path.is_empty() {
+            Some(module)
+        } else {
+            self.resolver.resolve_module(module, parent_path)
+        };
+        match parent {
+            Some(parent) => self.named(parent, name, seen),
+            None => Vec::new(),
+        }
+    }
+
+    // Items named `name` in `module`: its own items, and those it imports
+    fn named(
+        &self,
+        module: NodeId,
+        name: &'a str,
+        seen: &mut HashSet<(NodeId, &'a str)>,
+    ) -> Vec<NodeId> {
+        let Some(module_node) = self.modules.get(&module).copied() else {
+            return Vec::new();
+        };
+        // Imports can refer to each other in cycles through globs
+        if !seen.insert((module, name)) {
+            return Vec::new();
+        }
+
+        let mut ids: Vec<NodeId> = module_node
+            .items
+            .iter()
+            .chain(&module_node.submodules)
+            .filter(|id| self.nodes.get(id).is_some_and(|node| node.name() == name))
+            .copied()
+            .collect();
+        for import in &module_node.imports {
+            if import.is_glob {
+                if let Some(glob_module) = self.resolver.resolve_module(module, &import.path) {
+                    ids.extend(self.named(glob_module, name, seen));
+                }
+            } else if import.name() == Some(name) {
+                ids.extend(self.imported(module, &import.path, seen));
+            }
+        }
+        ids
+    }
+}
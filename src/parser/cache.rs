@@ -0,0 +1,165 @@
+use crate::config::ParseOptions;
+use crate::error::{Result, SynParserError};
+use crate::parser::graph::CodeGraph;
+use crate::parser::nodes::stable_hash;
+use crate::parser::utils::ModuleDirs;
+use crate::parser::visitor::{
+    analyze_files, crate_root_file, visit_file, FileFragment, FileModule,
+};
+use crate::serialization::ron::save_to_ron;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+// ANCHOR: AnalysisCache
+/// What earlier analyses of a crate produced for each of its files, so that
+/// `analyze_crate_incremental` only visits the files that changed since.
+///
+/// Entries are keyed by file path and remember a hash of the contents they were built
+/// from. A cache can be kept in memory between analyses, or saved to disk with `save`
+/// and read back with `load`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnalysisCache {
+    // Options the cached files were visited with. Other options give other nodes, so
+    // changing them empties the cache.
+    options: Option<ParseOptions>,
+    files: HashMap<PathBuf, CachedFile>,
+    #[serde(skip)]
+    last_run: CacheRun,
+}
+//ANCHOR_END: AnalysisCache
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFile {
+    content_hash: u64,
+    // Where the file was in the crate. A file moved to another module has other IDs.
+    file_module: FileModule,
+    fragment: FileFragment,
+}
+
+// Files the last analysis took from the cache, and files it had to visit, sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheRun {
+    pub reused: Vec<PathBuf>,
+    pub visited: Vec<PathBuf>,
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a cache saved with `save`. A missing file gives an empty cache.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let contents =
+            std::fs::read_to_string(path).map_err(|err| SynParserError::io(path, err))?;
+        ron::from_str(&contents).map_err(|err| SynParserError::Serialization(err.to_string()))
+    }
+
+    /// Write the cache to `path`, to be read back with `load`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_to_ron(self, path)
+    }
+
+    // Number of cached files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Which files the last analysis reused and which it visited.
+    pub fn last_run(&self) -> &CacheRun {
+        &self.last_run
+    }
+}
+
+// The fragment of a file with the given contents, from the cache if it is up to date.
+// Files are visited on several threads, so the cache is only locked to look them up and
+// to store them.
+fn cached_fragment(
+    cache: &Mutex<&mut AnalysisCache>,
+    file_module: &FileModule,
+    source: String,
+    options: &ParseOptions,
+) -> Result<FileFragment> {
+    let content_hash = stable_hash(&[&source]);
+    let path = &file_module.file;
+    {
+        let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let cached = cache.files.get(path).filter(|cached| {
+            cached.content_hash == content_hash && cached.file_module == *file_module
+        });
+        if let Some(fragment) = cached.map(|cached| cached.fragment.clone()) {
+            cache.last_run.reused.push(path.clone());
+            return Ok(fragment);
+        }
+    }
+
+    let fragment = visit_file(file_module, source, options)?;
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+    cache.last_run.visited.push(path.clone());
+    cache.files.insert(
+        path.clone(),
+        CachedFile {
+            content_hash,
+            file_module: file_module.clone(),
+            fragment: fragment.clone(),
+        },
+    );
+    Ok(fragment)
+}
+
+/// Like `analyze_crate`, but only visits the files that changed since they were cached.
+pub fn analyze_crate_incremental(root: &Path, cache: &mut AnalysisCache) -> Result<CodeGraph> {
+    analyze_crate_incremental_with_options(root, &ParseOptions::default(), cache)
+}
+
+/// Analyze a whole crate with the given options, reusing what `cache` holds for files
+/// whose contents are unchanged and updating it with the files that had to be visited.
+///
+/// Only visiting files is cached. Module files are located again, and names resolved
+/// over the whole crate, so the graph is the same as the one `analyze_crate_with_options`
+/// gives. Files that are no longer part of the crate are dropped from the cache.
+pub fn analyze_crate_incremental_with_options(
+    root: &Path,
+    options: &ParseOptions,
+    cache: &mut AnalysisCache,
+) -> Result<CodeGraph> {
+    let root_file = crate_root_file(root)?;
+    if cache.options.as_ref() != Some(options) {
+        cache.clear();
+        cache.options = Some(options.clone());
+    }
+    cache.last_run = CacheRun::default();
+
+    let module_dirs = ModuleDirs::for_file(&root_file, true);
+    let shared = Mutex::new(&mut *cache);
+    let graph = analyze_files(
+        &root_file,
+        "crate",
+        None,
+        Some(module_dirs),
+        options,
+        &|file_module, source| cached_fragment(&shared, file_module, source, options),
+    )?;
+
+    // Files finish in no particular order
+    let run = &mut cache.last_run;
+    run.reused.sort();
+    run.visited.sort();
+    let used: HashSet<&PathBuf> = run.reused.iter().chain(&run.visited).collect();
+    cache.files.retain(|path, _| used.contains(path));
+    Ok(graph)
+}
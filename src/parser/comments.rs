@@ -0,0 +1,404 @@
+use crate::parser::graph::CodeGraph;
+use crate::parser::nodes::{CommentKind, CommentPlacement, NodeId, Span, TypeDefNode};
+
+use std::ops::Range;
+use std::path::Path;
+
+// A regular (non-doc) comment found in source text
+#[derive(Debug)]
+pub(crate) struct ScannedComment {
+    pub(crate) range: Range<usize>,
+    // Code precedes the comment on its first line
+    pub(crate) trailing: bool,
+}
+
+// Find the line and block comments in `source`, skipping doc comments (which `syn`
+// turns into `#[doc]` attributes) and anything inside string and character literals
+pub(crate) fn scan_comments(source: &str) -> Vec<ScannedComment> {
+    let bytes = source.as_bytes();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    // Whether the current line has code before `i`
+    let mut code_on_line = false;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => {
+                code_on_line = false;
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map_or(source.len(), |n| i + n);
+                if !is_doc_comment(&source[i..end]) {
+                    comments.push(ScannedComment {
+                        range: i..end,
+                        trailing: code_on_line,
+                    });
+                }
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = block_comment_end(bytes, i);
+                if !is_doc_comment(&source[i..end]) {
+                    comments.push(ScannedComment {
+                        range: i..end,
+                        trailing: code_on_line,
+                    });
+                }
+                i = end;
+            }
+            b'"' => {
+                i = string_end(bytes, i + 1);
+                code_on_line = true;
+            }
+            b'r' | b'b' | b'c' if raw_string_start(bytes, i).is_some() => {
+                let (hashes, quote) = raw_string_start(bytes, i).unwrap_or_default();
+                i = raw_string_end(bytes, quote + 1, hashes);
+                code_on_line = true;
+            }
+            b'\'' => {
+                i = char_literal_end(source, i);
+                code_on_line = true;
+            }
+            byte if byte.is_ascii_alphanumeric() || byte == b'_' => {
+                // Step over whole identifiers, so the `r` ending `for"` is not read as a
+                // raw string prefix
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                code_on_line = true;
+            }
+            byte => {
+                if !byte.is_ascii_whitespace() {
+                    code_on_line = true;
+                }
+                i += 1;
+            }
+        }
+    }
+
+    comments
+}
+
+// Whether the byte before `i` continues an identifier
+fn is_ident_byte(bytes: &[u8], i: usize) -> bool {
+    i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_')
+}
+
+// `///`, `//!`, `/** */` and `/*! */` are doc comments, but `////`, `/**/` and `/***` are not
+fn is_doc_comment(comment: &str) -> bool {
+    if comment.starts_with("//!") || comment.starts_with("/*!") {
+        return true;
+    }
+    (comment.starts_with("///") && !comment.starts_with("////"))
+        || (comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/")
+}
+
+// End of the (possibly nested) block comment starting at `start`
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// End of a string literal whose contents start at `i`
+fn string_end(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// For a raw string prefix (`r"`, `r#"`, `br##"`, `cr"`) at `i`, the number of `#`s and
+// the position of the opening quote
+fn raw_string_start(bytes: &[u8], i: usize) -> Option<(usize, usize)> {
+    if is_ident_byte(bytes, i) {
+        return None;
+    }
+    let mut j = i;
+    if matches!(bytes.get(j), Some(b'b' | b'c')) {
+        j += 1;
+    }
+    if bytes.get(j) != Some(&b'r') {
+        return None;
+    }
+    j += 1;
+    let hashes_start = j;
+    while bytes.get(j) == Some(&b'#') {
+        j += 1;
+    }
+    (bytes.get(j) == Some(&b'"')).then_some((j - hashes_start, j))
+}
+
+// End of a raw string whose contents start at `i`, closed by `"` and `hashes` `#`s
+fn raw_string_end(bytes: &[u8], mut i: usize, hashes: usize) -> usize {
+    while i < bytes.len() {
+        let closes = bytes
+            .get(i + 1..i + 1 + hashes)
+            .is_some_and(|tail| tail.iter().all(|b| *b == b'#'));
+        if bytes[i] == b'"' && closes {
+            return i + 1 + hashes;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+// End of the character literal or lifetime starting with the quote at `i`
+fn char_literal_end(source: &str, i: usize) -> usize {
+    let bytes = source.as_bytes();
+    if bytes.get(i + 1) == Some(&b'\\') {
+        // Escaped character: skip to the closing quote
+        let mut j = i + 2;
+        while j < bytes.len() && bytes[j] != b'\'' && bytes[j] != b'\n' {
+            j += 1;
+        }
+        return (j + 1).min(bytes.len());
+    }
+
+    // A single character followed by a quote is a literal, anything else is a lifetime
+    // or label
+    let char_len = source[i + 1..].chars().next().map_or(1, char::len_utf8);
+    if bytes.get(i + 1 + char_len) == Some(&b'\'') {
+        i + 2 + char_len
+    } else {
+        i + 1
+    }
+}
+
+// Classify a comment by the marker it starts with
+pub(crate) fn classify(comment: &str) -> CommentKind {
+    let text = comment
+        .trim_start_matches('/')
+        .trim_start_matches('*')
+        .trim_start();
+    if text.starts_with("SAFETY:") {
+        return CommentKind::Safety;
+    }
+
+    let marker_end = text
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    match &text[..marker_end] {
+        "TODO" => CommentKind::Todo,
+        "FIXME" => CommentKind::Fixme,
+        "HACK" => CommentKind::Hack,
+        _ => CommentKind::Regular,
+    }
+}
+
+// Span of `range` within `source`, the contents of `file`
+pub(crate) fn span_of(file: &Path, source: &str, range: Range<usize>) -> Span {
+    let position = |offset: usize| {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+        (
+            before.matches('\n').count() + 1,
+            source[line_start..offset].chars().count(),
+        )
+    };
+    let (start_line, start_col) = position(range.start);
+    let (end_line, end_col) = position(range.end);
+
+    Span {
+        file: file.to_path_buf(),
+        start_line,
+        start_col,
+        end_line,
+        end_col,
+        byte_range: range,
+    }
+}
+
+// The spans of every node in `graph` that comes from `file`, with whether the node is
+// a module
+pub(crate) fn file_node_spans<'g>(
+    graph: &'g CodeGraph,
+    file: &Path,
+) -> Vec<(NodeId, &'g Span, bool)> {
+    let mut spans = Vec::new();
+    let mut add = |id: NodeId, span: &'g Span, is_module: bool| {
+        if span.file == file {
+            spans.push((id, span, is_module));
+        }
+    };
+
+    for module in &graph.modules {
+        add(module.id, &module.span, true);
+    }
+    let functions = graph
+        .functions
+        .iter()
+        .chain(graph.impls.iter().flat_map(|imp| &imp.methods))
+        .chain(graph.traits.iter().flat_map(|t| &t.methods));
+    for function in functions {
+        add(function.id, &function.span, false);
+        for param in &function.parameters {
+            add(param.id, &param.span, false);
+        }
+    }
+    for def in &graph.defined_types {
+        match def {
+            TypeDefNode::Struct(s) => {
+                add(s.id, &s.span, false);
+                for field in &s.fields {
+                    add(field.id, &field.span, false);
+                }
+            }
+            TypeDefNode::Enum(e) => {
+                add(e.id, &e.span, false);
+                for variant in &e.variants {
+                    add(variant.id, &variant.span, false);
+                    for field in &variant.fields {
+                        add(field.id, &field.span, false);
+                    }
+                }
+            }
+            TypeDefNode::Union(u) => {
+                add(u.id, &u.span, false);
+                for field in &u.fields {
+                    add(field.id, &field.span, false);
+                }
+            }
+            TypeDefNode::TypeAlias(ta) => add(ta.id, &ta.span, false),
+            TypeDefNode::ForeignType(ft) => add(ft.id, &ft.span, false),
+        }
+    }
+    for imp in &graph.impls {
+        add(imp.id, &imp.span, false);
+        for assoc in &imp.assoc_types {
+            add(assoc.id, &assoc.span, false);
+        }
+        for assoc in &imp.assoc_consts {
+            add(assoc.id, &assoc.span, false);
+        }
+    }
+    for t in &graph.traits {
+        add(t.id, &t.span, false);
+        for assoc in &t.assoc_types {
+            add(assoc.id, &assoc.span, false);
+        }
+        for assoc in &t.assoc_consts {
+            add(assoc.id, &assoc.span, false);
+        }
+    }
+    for alias in &graph.trait_aliases {
+        add(alias.id, &alias.span, false);
+    }
+    for value in &graph.values {
+        add(value.id, &value.span, false);
+    }
+    for mac in &graph.macros {
+        add(mac.id, &mac.span, false);
+    }
+    for foreign_mod in &graph.foreign_mods {
+        add(foreign_mod.id, &foreign_mod.span, false);
+    }
+
+    spans
+}
+
+// Choose the node a comment belongs to and how it is placed relative to it.
+// `file_module` is the module whose file the comment is in.
+//
+// A comment on its own line belongs to the item that directly follows it, a comment
+// after code belongs to the innermost node ending on the same line, and any other
+// comment (e.g. one between statements of a function body) belongs to the innermost
+// node containing it.
+pub(crate) fn attach(
+    comment: &ScannedComment,
+    comments: &[ScannedComment],
+    spans: &[(NodeId, &Span, bool)],
+    source: &str,
+    file_module: NodeId,
+) -> (NodeId, CommentPlacement, NodeId) {
+    let range = &comment.range;
+    let contains =
+        |span: &Span| span.byte_range.start <= range.start && range.end <= span.byte_range.end;
+    let len = |span: &Span| span.byte_range.len();
+
+    // Innermost node and module around the comment
+    let container = spans
+        .iter()
+        .filter(|(_, span, _)| contains(span))
+        .min_by_key(|(_, span, _)| len(span));
+    let module = spans
+        .iter()
+        .filter(|(_, span, is_module)| *is_module && contains(span))
+        .min_by_key(|(_, span, _)| len(span))
+        .map_or(file_module, |(id, _, _)| *id);
+    let container_id = container.map_or(module, |(id, _, _)| *id);
+
+    if comment.trailing {
+        let line = span_of(Path::new(""), source, range.clone()).start_line;
+        let ended = spans
+            .iter()
+            .filter(|(_, span, _)| span.end_line == line && span.byte_range.end <= range.start)
+            .max_by_key(|(_, span, _)| (span.byte_range.end, usize::MAX - len(span)));
+        return match ended {
+            Some((id, _, _)) => (*id, CommentPlacement::Trailing, module),
+            None => (container_id, CommentPlacement::Inner, module),
+        };
+    }
+
+    // Outermost node starting after the comment, with nothing but whitespace and other
+    // comments in between
+    let next = spans
+        .iter()
+        .filter(|(_, span, _)| span.byte_range.start >= range.end)
+        .filter(|(_, span, _)| container.is_none_or(|(_, outer, _)| contains_span(outer, span)))
+        .min_by_key(|(_, span, _)| (span.byte_range.start, usize::MAX - len(span)));
+    if let Some((id, span, _)) = next {
+        if only_comments_between(source, comments, range.end, span.byte_range.start) {
+            return (*id, CommentPlacement::Leading, module);
+        }
+    }
+
+    (container_id, CommentPlacement::Inner, module)
+}
+
+fn contains_span(outer: &Span, inner: &Span) -> bool {
+    outer.byte_range.start <= inner.byte_range.start && inner.byte_range.end <= outer.byte_range.end
+}
+
+// Whether `source[start..end]` holds nothing but whitespace and comments
+fn only_comments_between(
+    source: &str,
+    comments: &[ScannedComment],
+    start: usize,
+    end: usize,
+) -> bool {
+    let mut i = start;
+    while i < end {
+        if let Some(comment) = comments.iter().find(|c| c.range.start == i) {
+            i = comment.range.end;
+            continue;
+        }
+        let c = source[i..].chars().next().unwrap_or(' ');
+        if !c.is_whitespace() {
+            return false;
+        }
+        i += c.len_utf8();
+    }
+    true
+}
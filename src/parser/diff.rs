@@ -0,0 +1,390 @@
+use crate::parser::graph::{CodeGraph, NodeRef};
+use crate::parser::nodes::{FieldNode, NodeId, TypeDefNode};
+use crate::parser::relations::{Relation, RelationKind};
+use crate::parser::types::TypeId;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// Lowest similarity for an item with the same name at another path to count as moved
+const MOVE_THRESHOLD: f64 = 0.5;
+// Lowest similarity for an item with another name to count as renamed
+const RENAME_THRESHOLD: f64 = 0.8;
+// Tokens a renamed item must share with the original, since tiny items like empty
+// functions look alike whatever they are
+const MIN_SHARED_TOKENS: usize = 4;
+
+// ANCHOR: GraphDiff
+// Structural changes between two graphs of the same code. Nodes are matched by kind
+// and canonical path; nodes left over on both sides that look alike are reported as
+// moved or renamed instead of as a removal and an addition.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub added: Vec<NodeSummary>,
+    pub removed: Vec<NodeSummary>,
+    // Same path in both graphs, with a different signature or body
+    pub modified: Vec<NodeModification>,
+    // Same name at a different path
+    pub moved: Vec<NodeMatch>,
+    // Different name with a similar signature and body, possibly at another path too
+    pub renamed: Vec<NodeMatch>,
+    pub added_types: Vec<TypeId>,
+    pub removed_types: Vec<TypeId>,
+    // Relations are compared by source, target and kind. Node IDs are derived from
+    // paths, so the relations of moved and renamed nodes show up here as well.
+    pub added_relations: Vec<Relation>,
+    pub removed_relations: Vec<Relation>,
+}
+//ANCHOR_END: GraphDiff
+
+// Kinds of nodes with a canonical path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum NodeKind {
+    Function,
+    Struct,
+    Enum,
+    TypeAlias,
+    Union,
+    ForeignType,
+    Trait,
+    TraitAlias,
+    Value,
+    Macro,
+    Module,
+}
+
+impl NodeKind {
+    pub fn of(node: &NodeRef) -> Self {
+        match node {
+            NodeRef::Function(_) => NodeKind::Function,
+            NodeRef::TypeDef(TypeDefNode::Struct(_)) => NodeKind::Struct,
+            NodeRef::TypeDef(TypeDefNode::Enum(_)) => NodeKind::Enum,
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(_)) => NodeKind::TypeAlias,
+            NodeRef::TypeDef(TypeDefNode::Union(_)) => NodeKind::Union,
+            NodeRef::TypeDef(TypeDefNode::ForeignType(_)) => NodeKind::ForeignType,
+            NodeRef::Trait(_) => NodeKind::Trait,
+            NodeRef::TraitAlias(_) => NodeKind::TraitAlias,
+            NodeRef::Value(_) => NodeKind::Value,
+            NodeRef::Macro(_) => NodeKind::Macro,
+            NodeRef::Module(_) => NodeKind::Module,
+        }
+    }
+}
+
+// A node of one of the compared graphs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSummary {
+    pub id: NodeId,
+    pub kind: NodeKind,
+    pub path: String,
+}
+
+impl NodeSummary {
+    fn of(node: &NodeRef) -> Self {
+        Self {
+            id: node.id(),
+            kind: NodeKind::of(node),
+            path: node.path().to_string(),
+        }
+    }
+}
+
+// A node whose signature or body changed in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeModification {
+    pub old: NodeSummary,
+    pub new: NodeSummary,
+    pub signature_changed: bool,
+    pub body_changed: bool,
+}
+
+// A node of the old graph and the node it became in the new one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeMatch {
+    pub old: NodeSummary,
+    pub new: NodeSummary,
+    // Share of signature and body tokens the two nodes have in common, from 0 to 1
+    pub similarity: f64,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.moved.is_empty()
+            && self.renamed.is_empty()
+            && self.added_types.is_empty()
+            && self.removed_types.is_empty()
+            && self.added_relations.is_empty()
+            && self.removed_relations.is_empty()
+    }
+}
+
+/// Compare two graphs of the same code, e.g. before and after a change, listing the
+/// nodes and relations that were added, removed, modified, moved or renamed.
+///
+/// Nodes with the same kind and canonical path are the same node. Of the rest, a node
+/// with the same name at another path is taken to be moved, and a node with another
+/// name but a nearly identical signature and body to be renamed. The result can be
+/// saved with `save_to_ron` or `save_to_json`.
+pub fn diff(old: &CodeGraph, new: &CodeGraph) -> GraphDiff {
+    let old_side = Side::new(old);
+    let new_side = Side::new(new);
+    let mut result = GraphDiff::default();
+
+    // Match nodes by kind and path
+    let mut new_by_path: HashMap<(NodeKind, &str), usize> = HashMap::new();
+    for (i, node) in new_side.nodes.iter().enumerate() {
+        new_by_path
+            .entry((NodeKind::of(node), node.path()))
+            .or_insert(i);
+    }
+    let mut unmatched_old = Vec::new();
+    let mut matched_new = HashSet::new();
+    for (i, old_node) in old_side.nodes.iter().enumerate() {
+        let key = (NodeKind::of(old_node), old_node.path());
+        let Some(&j) = new_by_path.get(&key).filter(|j| !matched_new.contains(*j)) else {
+            unmatched_old.push(i);
+            continue;
+        };
+        matched_new.insert(j);
+
+        let (old_print, new_print) = (&old_side.prints[i], &new_side.prints[j]);
+        let signature_changed = old_print.signature != new_print.signature;
+        let body_changed = old_print.body != new_print.body;
+        if signature_changed || body_changed {
+            result.modified.push(NodeModification {
+                old: NodeSummary::of(old_node),
+                new: NodeSummary::of(&new_side.nodes[j]),
+                signature_changed,
+                body_changed,
+            });
+        }
+    }
+    let unmatched_new: Vec<usize> = (0..new_side.nodes.len())
+        .filter(|j| !matched_new.contains(j))
+        .collect();
+
+    // Pair up what is left, most similar pairs first
+    let mut candidates = Vec::new();
+    for &i in &unmatched_old {
+        for &j in &unmatched_new {
+            let (old_node, new_node) = (&old_side.nodes[i], &new_side.nodes[j]);
+            if NodeKind::of(old_node) != NodeKind::of(new_node) {
+                continue;
+            }
+            let similarity = old_side.prints[i].similarity(&new_side.prints[j]);
+            let same_name = old_node.name() == new_node.name();
+            let threshold = if same_name {
+                MOVE_THRESHOLD
+            } else {
+                RENAME_THRESHOLD
+            };
+            let shared = old_side.prints[i].shared_tokens(&new_side.prints[j]);
+            if similarity >= threshold && (same_name || shared >= MIN_SHARED_TOKENS) {
+                candidates.push((similarity, same_name, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    let mut paired_old = HashSet::new();
+    let mut paired_new = HashSet::new();
+    for (similarity, same_name, i, j) in candidates {
+        if paired_old.contains(&i) || paired_new.contains(&j) {
+            continue;
+        }
+        paired_old.insert(i);
+        paired_new.insert(j);
+        let pair = NodeMatch {
+            old: NodeSummary::of(&old_side.nodes[i]),
+            new: NodeSummary::of(&new_side.nodes[j]),
+            similarity,
+        };
+        if same_name {
+            result.moved.push(pair);
+        } else {
+            result.renamed.push(pair);
+        }
+    }
+
+    result.removed = unmatched_old
+        .iter()
+        .filter(|i| !paired_old.contains(*i))
+        .map(|&i| NodeSummary::of(&old_side.nodes[i]))
+        .collect();
+    result.added = unmatched_new
+        .iter()
+        .filter(|j| !paired_new.contains(*j))
+        .map(|&j| NodeSummary::of(&new_side.nodes[j]))
+        .collect();
+    for summaries in [&mut result.added, &mut result.removed] {
+        summaries.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    result.moved.sort_by(|a, b| a.old.path.cmp(&b.old.path));
+    result.renamed.sort_by(|a, b| a.old.path.cmp(&b.old.path));
+
+    let old_types: HashSet<TypeId> = old.type_graph.iter().map(|t| t.id).collect();
+    let new_types: HashSet<TypeId> = new.type_graph.iter().map(|t| t.id).collect();
+    result.added_types = new
+        .type_graph
+        .iter()
+        .map(|t| t.id)
+        .filter(|id| !old_types.contains(id))
+        .collect();
+    result.removed_types = old
+        .type_graph
+        .iter()
+        .map(|t| t.id)
+        .filter(|id| !new_types.contains(id))
+        .collect();
+
+    let relation_key = |r: &Relation| (r.source, r.target, r.kind);
+    let old_relations: HashSet<(NodeId, NodeId, RelationKind)> =
+        old.relations.iter().map(relation_key).collect();
+    let new_relations: HashSet<(NodeId, NodeId, RelationKind)> =
+        new.relations.iter().map(relation_key).collect();
+    result.added_relations = new
+        .relations
+        .iter()
+        .filter(|r| !old_relations.contains(&relation_key(r)))
+        .cloned()
+        .collect();
+    result.removed_relations = old
+        .relations
+        .iter()
+        .filter(|r| !new_relations.contains(&relation_key(r)))
+        .cloned()
+        .collect();
+
+    result
+}
+
+// The nodes of one graph, with their fingerprints
+struct Side<'a> {
+    nodes: Vec<NodeRef<'a>>,
+    prints: Vec<Fingerprint>,
+}
+
+impl<'a> Side<'a> {
+    fn new(graph: &'a CodeGraph) -> Self {
+        let nodes: Vec<NodeRef> = graph.path_nodes().collect();
+        let names: HashMap<NodeId, &str> = nodes.iter().map(|n| (n.id(), n.name())).collect();
+        let prints = nodes
+            .iter()
+            .map(|node| Fingerprint::of(node, &names))
+            .collect();
+        Self { nodes, prints }
+    }
+}
+
+// What a node declares, for comparing nodes of different graphs. Types are written
+// as their IDs, which are the same in both graphs for the same type.
+struct Fingerprint {
+    signature: Vec<String>,
+    body: Option<String>,
+    // Signature entries and the words of the body, for similarity
+    tokens: BTreeSet<String>,
+}
+
+impl Fingerprint {
+    fn of(node: &NodeRef, names: &HashMap<NodeId, &str>) -> Self {
+        let ty = |id: &TypeId| format!("{:x}", id.to_compact());
+        let fields = |fields: &[FieldNode]| -> Vec<String> {
+            fields
+                .iter()
+                .map(|f| format!("{:?} {:?}: {}", f.visibility, f.name, ty(&f.type_id)))
+                .collect()
+        };
+
+        let mut signature = Vec::new();
+        let mut body = None;
+        match node {
+            NodeRef::Function(f) => {
+                for param in &f.parameters {
+                    signature.push(format!("param {}", ty(&param.type_id)));
+                }
+                signature.extend(f.return_type.iter().map(|id| format!("-> {}", ty(id))));
+                signature.extend(f.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+                signature.push(format!("{:?}", f.visibility));
+                body = f.body.clone();
+            }
+            NodeRef::TypeDef(TypeDefNode::Struct(s)) => {
+                signature.extend(fields(&s.fields));
+                signature.extend(s.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+            }
+            NodeRef::TypeDef(TypeDefNode::Union(u)) => {
+                signature.extend(fields(&u.fields));
+                signature.extend(u.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+            }
+            NodeRef::TypeDef(TypeDefNode::Enum(e)) => {
+                for variant in &e.variants {
+                    signature.push(format!("variant {}", variant.name));
+                    signature.extend(fields(&variant.fields));
+                }
+                signature.extend(e.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+            }
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(ta)) => {
+                signature.push(ty(&ta.type_id));
+                signature.extend(ta.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+            }
+            NodeRef::TypeDef(TypeDefNode::ForeignType(_)) => {}
+            NodeRef::Trait(t) => {
+                signature.extend(t.methods.iter().map(|m| format!("fn {}", m.name)));
+                signature.extend(t.assoc_types.iter().map(|a| format!("type {}", a.name)));
+                signature.extend(t.super_traits.iter().map(|id| format!("super {}", ty(id))));
+                signature.extend(t.generic_params.iter().map(|p| format!("{:?}", p.kind)));
+            }
+            NodeRef::TraitAlias(t) => {
+                signature.extend(t.bounds.iter().map(ty));
+            }
+            NodeRef::Value(v) => {
+                signature.push(ty(&v.type_id));
+                body = v.value.clone();
+            }
+            NodeRef::Macro(m) => {
+                body = m.body.clone();
+            }
+            NodeRef::Module(m) => {
+                // Item IDs change with the module's path, their names do not
+                let mut children: Vec<String> = m
+                    .items
+                    .iter()
+                    .chain(&m.submodules)
+                    .filter_map(|id| names.get(id))
+                    .map(|name| format!("item {}", name))
+                    .collect();
+                children.sort();
+                signature = children;
+            }
+        }
+
+        let mut tokens: BTreeSet<String> = signature.iter().cloned().collect();
+        if let Some(body) = &body {
+            tokens.extend(
+                body.split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .filter(|word| !word.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        Self {
+            signature,
+            body,
+            tokens,
+        }
+    }
+
+    fn shared_tokens(&self, other: &Fingerprint) -> usize {
+        self.tokens.intersection(&other.tokens).count()
+    }
+
+    // Jaccard similarity of the tokens of two fingerprints
+    fn similarity(&self, other: &Fingerprint) -> f64 {
+        let union = self.tokens.union(&other.tokens).count();
+        if union == 0 {
+            return 1.0;
+        }
+        self.shared_tokens(other) as f64 / union as f64
+    }
+}
@@ -1,13 +1,18 @@
 use crate::parser::{
-    nodes::{FunctionNode, ImplNode, MacroNode, ModuleNode, TraitNode, TypeDefNode, ValueNode},
+    nodes::{
+        AssocConstNode, AssocTypeNode, CallNode, CommentNode, FieldNode, ForeignModNode,
+        FunctionNode, ImplNode, ImportNode, MacroInvocationNode, MacroNode, ModuleNode, NodeId,
+        TraitAliasNode, TraitNode, TraitRef, TypeDefNode, ValueNode,
+    },
     relations::Relation,
-    types::TypeNode,
+    types::{GenericParamKind, GenericParamNode, TypeId, TypeNode, VisibilityKind},
 };
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // Main structure representing the entire code graph
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeGraph {
     // Functions defined in the code
     pub functions: Vec<FunctionNode>,
@@ -21,6 +26,8 @@ pub struct CodeGraph {
     pub traits: Vec<TraitNode>,
     // Private traits defined in the code
     pub private_traits: Vec<TraitNode>,
+    // Trait aliases defined in the code
+    pub trait_aliases: Vec<TraitAliasNode>,
     // Relations between nodes
     pub relations: Vec<Relation>,
     // Modules defined in the code
@@ -29,4 +36,382 @@ pub struct CodeGraph {
     pub values: Vec<ValueNode>,
     // Macros defined in the code
     pub macros: Vec<MacroNode>,
+    // Macro invocations, in item position and inside bodies
+    pub macro_invocations: Vec<MacroInvocationNode>,
+    // Call sites inside function bodies
+    pub calls: Vec<CallNode>,
+    // `use` and `extern crate` items inside function bodies and other blocks, linked
+    // from their function (or module, outside of functions) by a `Uses` relation.
+    // Names are not resolved through them.
+    pub block_imports: Vec<ImportNode>,
+    // `extern` blocks
+    pub foreign_mods: Vec<ForeignModNode>,
+    // Regular comments, attached to the nodes they describe
+    pub comments: Vec<CommentNode>,
+}
+
+// A node of any kind that has a canonical path
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'a> {
+    // Free functions, foreign functions and methods
+    Function(&'a FunctionNode),
+    TypeDef(&'a TypeDefNode),
+    Trait(&'a TraitNode),
+    TraitAlias(&'a TraitAliasNode),
+    Value(&'a ValueNode),
+    Macro(&'a MacroNode),
+    Module(&'a ModuleNode),
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn id(&self) -> NodeId {
+        match self {
+            NodeRef::Function(f) => f.id,
+            NodeRef::TypeDef(TypeDefNode::Struct(s)) => s.id,
+            NodeRef::TypeDef(TypeDefNode::Enum(e)) => e.id,
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(ta)) => ta.id,
+            NodeRef::TypeDef(TypeDefNode::Union(u)) => u.id,
+            NodeRef::TypeDef(TypeDefNode::ForeignType(ft)) => ft.id,
+            NodeRef::Trait(t) => t.id,
+            NodeRef::TraitAlias(t) => t.id,
+            NodeRef::Value(v) => v.id,
+            NodeRef::Macro(m) => m.id,
+            NodeRef::Module(m) => m.id,
+        }
+    }
+
+    pub fn name(&self) -> &'a str {
+        match self {
+            NodeRef::Function(f) => &f.name,
+            NodeRef::TypeDef(TypeDefNode::Struct(s)) => &s.name,
+            NodeRef::TypeDef(TypeDefNode::Enum(e)) => &e.name,
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(ta)) => &ta.name,
+            NodeRef::TypeDef(TypeDefNode::Union(u)) => &u.name,
+            NodeRef::TypeDef(TypeDefNode::ForeignType(ft)) => &ft.name,
+            NodeRef::Trait(t) => &t.name,
+            NodeRef::TraitAlias(t) => &t.name,
+            NodeRef::Value(v) => &v.name,
+            NodeRef::Macro(m) => &m.name,
+            NodeRef::Module(m) => &m.name,
+        }
+    }
+
+    // Visibility as declared on the node itself
+    pub fn visibility(&self) -> &'a VisibilityKind {
+        match self {
+            NodeRef::Function(f) => &f.visibility,
+            NodeRef::TypeDef(TypeDefNode::Struct(s)) => &s.visibility,
+            NodeRef::TypeDef(TypeDefNode::Enum(e)) => &e.visibility,
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(ta)) => &ta.visibility,
+            NodeRef::TypeDef(TypeDefNode::Union(u)) => &u.visibility,
+            NodeRef::TypeDef(TypeDefNode::ForeignType(ft)) => &ft.visibility,
+            NodeRef::Trait(t) => &t.visibility,
+            NodeRef::TraitAlias(t) => &t.visibility,
+            NodeRef::Value(v) => &v.visibility,
+            NodeRef::Macro(m) => &m.visibility,
+            NodeRef::Module(m) => &m.visibility,
+        }
+    }
+
+    pub fn path(&self) -> &'a str {
+        match self {
+            NodeRef::Function(f) => &f.path,
+            NodeRef::TypeDef(TypeDefNode::Struct(s)) => &s.path,
+            NodeRef::TypeDef(TypeDefNode::Enum(e)) => &e.path,
+            NodeRef::TypeDef(TypeDefNode::TypeAlias(ta)) => &ta.path,
+            NodeRef::TypeDef(TypeDefNode::Union(u)) => &u.path,
+            NodeRef::TypeDef(TypeDefNode::ForeignType(ft)) => &ft.path,
+            NodeRef::Trait(t) => &t.path,
+            NodeRef::TraitAlias(t) => &t.path,
+            NodeRef::Value(v) => &v.path,
+            NodeRef::Macro(m) => &m.path,
+            NodeRef::Module(m) => &m.path,
+        }
+    }
+}
+
+impl CodeGraph {
+    // Move the nodes and relations of `other` to the end of this graph
+    pub(crate) fn append(&mut self, mut other: CodeGraph) {
+        self.functions.append(&mut other.functions);
+        self.defined_types.append(&mut other.defined_types);
+        self.type_graph.append(&mut other.type_graph);
+        self.impls.append(&mut other.impls);
+        self.traits.append(&mut other.traits);
+        self.private_traits.append(&mut other.private_traits);
+        self.trait_aliases.append(&mut other.trait_aliases);
+        self.relations.append(&mut other.relations);
+        self.modules.append(&mut other.modules);
+        self.values.append(&mut other.values);
+        self.macros.append(&mut other.macros);
+        self.macro_invocations.append(&mut other.macro_invocations);
+        self.calls.append(&mut other.calls);
+        self.block_imports.append(&mut other.block_imports);
+        self.foreign_mods.append(&mut other.foreign_mods);
+        self.comments.append(&mut other.comments);
+    }
+
+    /// Every node with a canonical path, including the methods of impls and traits.
+    pub fn path_nodes(&self) -> impl Iterator<Item = NodeRef<'_>> {
+        let methods = self
+            .impls
+            .iter()
+            .flat_map(|i| &i.methods)
+            .chain(self.traits.iter().flat_map(|t| &t.methods))
+            .chain(self.private_traits.iter().flat_map(|t| &t.methods));
+
+        self.functions
+            .iter()
+            .chain(methods)
+            .map(NodeRef::Function)
+            .chain(self.defined_types.iter().map(NodeRef::TypeDef))
+            .chain(self.traits.iter().map(NodeRef::Trait))
+            .chain(self.private_traits.iter().map(NodeRef::Trait))
+            .chain(self.trait_aliases.iter().map(NodeRef::TraitAlias))
+            .chain(self.values.iter().map(NodeRef::Value))
+            .chain(self.macros.iter().map(NodeRef::Macro))
+            .chain(self.modules.iter().map(NodeRef::Module))
+    }
+
+    /// Find the node with the given canonical path, e.g. `crate::net::Client::new` or
+    /// `<crate::net::Client as std::ops::Drop>::drop`.
+    ///
+    /// This walks the whole graph; use [`CodeGraph::path_index`] for repeated lookups.
+    pub fn find_by_path(&self, path: &str) -> Option<NodeRef<'_>> {
+        self.path_nodes().find(|node| node.path() == path)
+    }
+
+    /// Map from canonical path to node, for every node in [`CodeGraph::path_nodes`].
+    ///
+    /// Items of different namespaces can share a path (`mod parse` and `fn parse`); only
+    /// one of them is kept.
+    pub fn path_index(&self) -> HashMap<&str, NodeRef<'_>> {
+        self.path_nodes().map(|node| (node.path(), node)).collect()
+    }
+
+    // Replace every reference to a type in `map` with the type it maps to, in types,
+    // relations and every node that refers to types
+    pub(crate) fn remap_type_ids(&mut self, map: &HashMap<TypeId, TypeId>) {
+        let remap = |id: &mut TypeId| {
+            if let Some(new_id) = map.get(id) {
+                *id = *new_id;
+            }
+        };
+        let remap_generics = |params: &mut Vec<GenericParamNode>| {
+            for param in params {
+                match &mut param.kind {
+                    GenericParamKind::Type {
+                        bounds, default, ..
+                    } => {
+                        bounds.iter_mut().for_each(remap);
+                        default.iter_mut().for_each(remap);
+                    }
+                    GenericParamKind::Const { type_id, .. } => remap(type_id),
+                    GenericParamKind::Lifetime { .. } => {}
+                }
+            }
+        };
+        let remap_function = |function: &mut FunctionNode| {
+            for param in &mut function.parameters {
+                remap(&mut param.type_id);
+            }
+            function.return_type.iter_mut().for_each(remap);
+            remap_generics(&mut function.generic_params);
+        };
+
+        for ty in &mut self.type_graph {
+            remap(&mut ty.id);
+            ty.related_types.iter_mut().for_each(remap);
+        }
+        for relation in &mut self.relations {
+            remap(&mut relation.source);
+            remap(&mut relation.target);
+        }
+        self.functions.iter_mut().for_each(remap_function);
+        for def in &mut self.defined_types {
+            match def {
+                TypeDefNode::Struct(s) => {
+                    s.fields.iter_mut().for_each(|f| remap(&mut f.type_id));
+                    remap_generics(&mut s.generic_params);
+                }
+                TypeDefNode::Enum(e) => {
+                    for variant in &mut e.variants {
+                        variant
+                            .fields
+                            .iter_mut()
+                            .for_each(|f| remap(&mut f.type_id));
+                    }
+                    remap_generics(&mut e.generic_params);
+                }
+                TypeDefNode::TypeAlias(ta) => {
+                    remap(&mut ta.type_id);
+                    remap_generics(&mut ta.generic_params);
+                }
+                TypeDefNode::Union(u) => {
+                    u.fields.iter_mut().for_each(|f| remap(&mut f.type_id));
+                    remap_generics(&mut u.generic_params);
+                }
+                TypeDefNode::ForeignType(_) => {}
+            }
+        }
+        for impl_node in &mut self.impls {
+            remap(&mut impl_node.self_type);
+            impl_node.trait_type.iter_mut().for_each(remap);
+            impl_node.methods.iter_mut().for_each(remap_function);
+            remap_generics(&mut impl_node.generic_params);
+            for assoc_type in &mut impl_node.assoc_types {
+                assoc_type.bounds.iter_mut().for_each(remap);
+                assoc_type.type_id.iter_mut().for_each(remap);
+                remap_generics(&mut assoc_type.generic_params);
+            }
+            for assoc_const in &mut impl_node.assoc_consts {
+                remap(&mut assoc_const.type_id);
+            }
+        }
+        for trait_node in self.traits.iter_mut().chain(&mut self.private_traits) {
+            trait_node.super_traits.iter_mut().for_each(remap);
+            trait_node.methods.iter_mut().for_each(remap_function);
+            remap_generics(&mut trait_node.generic_params);
+            for assoc_type in &mut trait_node.assoc_types {
+                assoc_type.bounds.iter_mut().for_each(remap);
+                assoc_type.type_id.iter_mut().for_each(remap);
+                remap_generics(&mut assoc_type.generic_params);
+            }
+            for assoc_const in &mut trait_node.assoc_consts {
+                remap(&mut assoc_const.type_id);
+            }
+        }
+        for alias in &mut self.trait_aliases {
+            alias.bounds.iter_mut().for_each(remap);
+            remap_generics(&mut alias.generic_params);
+        }
+        for value in &mut self.values {
+            remap(&mut value.type_id);
+        }
+    }
+
+    // Replace node IDs (not type IDs) by the IDs they map to, wherever they appear
+    pub(crate) fn remap_node_ids(&mut self, map: &HashMap<NodeId, NodeId>) {
+        if map.is_empty() {
+            return;
+        }
+        let remap = |id: &mut NodeId| {
+            if let Some(new_id) = map.get(id) {
+                *id = *new_id;
+            }
+        };
+        let remap_generics = |params: &mut Vec<GenericParamNode>| {
+            params.iter_mut().for_each(|param| remap(&mut param.id));
+        };
+        let remap_function = |function: &mut FunctionNode| {
+            remap(&mut function.id);
+            function
+                .parameters
+                .iter_mut()
+                .for_each(|p| remap(&mut p.id));
+            remap_generics(&mut function.generic_params);
+        };
+        let remap_fields = |fields: &mut Vec<FieldNode>| {
+            fields.iter_mut().for_each(|field| remap(&mut field.id));
+        };
+        let remap_assoc = |types: &mut Vec<AssocTypeNode>, consts: &mut Vec<AssocConstNode>| {
+            for assoc_type in types {
+                remap(&mut assoc_type.id);
+                remap_generics(&mut assoc_type.generic_params);
+            }
+            consts.iter_mut().for_each(|c| remap(&mut c.id));
+        };
+
+        self.functions.iter_mut().for_each(remap_function);
+        for def in &mut self.defined_types {
+            match def {
+                TypeDefNode::Struct(s) => {
+                    remap(&mut s.id);
+                    remap_fields(&mut s.fields);
+                    remap_generics(&mut s.generic_params);
+                }
+                TypeDefNode::Enum(e) => {
+                    remap(&mut e.id);
+                    for variant in &mut e.variants {
+                        remap(&mut variant.id);
+                        remap_fields(&mut variant.fields);
+                    }
+                    remap_generics(&mut e.generic_params);
+                }
+                TypeDefNode::TypeAlias(ta) => {
+                    remap(&mut ta.id);
+                    remap_generics(&mut ta.generic_params);
+                }
+                TypeDefNode::Union(u) => {
+                    remap(&mut u.id);
+                    remap_fields(&mut u.fields);
+                    remap_generics(&mut u.generic_params);
+                }
+                TypeDefNode::ForeignType(ft) => remap(&mut ft.id),
+            }
+        }
+        for impl_node in &mut self.impls {
+            remap(&mut impl_node.id);
+            impl_node.methods.iter_mut().for_each(remap_function);
+            remap_generics(&mut impl_node.generic_params);
+            remap_assoc(&mut impl_node.assoc_types, &mut impl_node.assoc_consts);
+            if let Some(TraitRef::Local(id)) = &mut impl_node.trait_ref {
+                remap(id);
+            }
+        }
+        for trait_node in self.traits.iter_mut().chain(&mut self.private_traits) {
+            remap(&mut trait_node.id);
+            trait_node.methods.iter_mut().for_each(remap_function);
+            remap_generics(&mut trait_node.generic_params);
+            remap_assoc(&mut trait_node.assoc_types, &mut trait_node.assoc_consts);
+        }
+        for alias in &mut self.trait_aliases {
+            remap(&mut alias.id);
+            remap_generics(&mut alias.generic_params);
+        }
+        for relation in &mut self.relations {
+            remap(&mut relation.source);
+            remap(&mut relation.target);
+        }
+        for module in &mut self.modules {
+            remap(&mut module.id);
+            module.submodules.iter_mut().for_each(remap);
+            module.items.iter_mut().for_each(remap);
+            module
+                .imports
+                .iter_mut()
+                .for_each(|import| remap(&mut import.id));
+            module.exports.iter_mut().for_each(remap);
+        }
+        self.values
+            .iter_mut()
+            .for_each(|value| remap(&mut value.id));
+        for mac in &mut self.macros {
+            remap(&mut mac.id);
+            mac.rules.iter_mut().for_each(|rule| remap(&mut rule.id));
+        }
+        for invocation in &mut self.macro_invocations {
+            remap(&mut invocation.id);
+            invocation.macro_id.iter_mut().for_each(remap);
+        }
+        for call in &mut self.calls {
+            remap(&mut call.id);
+            remap(&mut call.caller);
+            remap(&mut call.module);
+            call.callee.iter_mut().for_each(remap);
+            call.candidates.iter_mut().for_each(remap);
+        }
+        self.block_imports
+            .iter_mut()
+            .for_each(|import| remap(&mut import.id));
+        for foreign_mod in &mut self.foreign_mods {
+            remap(&mut foreign_mod.id);
+            foreign_mod.items.iter_mut().for_each(remap);
+        }
+        for comment in &mut self.comments {
+            remap(&mut comment.id);
+            remap(&mut comment.target);
+            remap(&mut comment.module);
+        }
+    }
 }
@@ -1,10 +1,31 @@
+pub mod api;
+pub mod cache;
+pub mod diff;
 pub mod graph;  // Make these public
 pub mod nodes;
 pub mod relations;
+pub mod semver;
 pub mod types;
 pub mod visitor;
+pub mod watch;
+pub mod workspace;
+
+mod comments;
+mod resolve;
+mod utils;
 
 // Re-export key items
+pub use self::api::public_api;
+pub use self::cache::{
+    analyze_crate_incremental, analyze_crate_incremental_with_options, AnalysisCache,
+};
+pub use self::semver::api_diff;
+pub use self::diff::{diff, GraphDiff};
 pub use self::graph::CodeGraph;
 pub use self::types::TypeId;
-pub use self::visitor::analyze_code;
+pub use self::visitor::{
+    analyze_code, analyze_code_tolerant, analyze_code_with_options, analyze_crate,
+    analyze_crate_tolerant, analyze_crate_with_options,
+};
+pub use self::watch::{CrateWatcher, GraphEvent};
+pub use self::workspace::{analyze_workspace, analyze_workspace_with_options, WorkspaceGraph};
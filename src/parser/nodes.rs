@@ -1,148 +1,349 @@
-use crate::parser::types::{GenericParamNode, TypeId, VisibilityKind};
+use crate::parser::relations::CallConfidence;
+use crate::parser::types::{GenericParamNode, Resolution, TypeId, VisibilityKind};
 
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::PathBuf;
 
-// Unique ID for a node in the graph
-pub type NodeId = usize;
+// ANCHOR: NodeId
+// Unique ID for a node in the graph, derived from where the node is in the code rather
+// than from the order it was visited in, so it stays the same across runs and when
+// unrelated items are added or removed.
+// - `namespace` is a hash of the canonical path of the module containing the node, e.g.
+//   `crate::net::tcp`, and of the package name for the member crates of a workspace
+// - `element` is a hash of the node's kind and name within that module (or within its
+//   parent item, for fields, parameters and the like), plus a disambiguator when several
+//   elements would otherwise share an ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeId {
+    namespace: u64,
+    element: u64,
+}
+//ANCHOR_END: NodeId
+
+// `NodeId` packed into a single integer, for storage
+pub type CompactNodeId = u128;
+
+impl NodeId {
+    pub fn new(namespace: u64, element: u64) -> Self {
+        Self { namespace, element }
+    }
+
+    pub fn namespace(&self) -> u64 {
+        self.namespace
+    }
+
+    pub fn element(&self) -> u64 {
+        self.element
+    }
+
+    pub fn to_compact(self) -> CompactNodeId {
+        (self.namespace as u128) << 64 | self.element as u128
+    }
+
+    pub fn from_compact(compact: CompactNodeId) -> Self {
+        Self {
+            namespace: (compact >> 64) as u64,
+            element: compact as u64,
+        }
+    }
+}
+
+// FNV-1a hash of `parts`. Unlike the hashers in `std`, its output is fixed, so IDs
+// computed from it can be stored and compared across builds.
+pub fn stable_hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (i, part) in parts.iter().enumerate() {
+        // Separate parts, so `["ab", "c"]` and `["a", "bc"]` differ
+        let separator = if i == 0 {
+            [].as_slice()
+        } else {
+            [0xff].as_slice()
+        };
+        for byte in separator.iter().chain(part.as_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+// ANCHOR: Span
+// Location of a node in its source file. Lines are 1-based, columns are 0-based and
+// count characters, and `byte_range` indexes into the file's contents. Spans of items
+// include their attributes and doc comments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub file: PathBuf,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub byte_range: Range<usize>,
+}
+//ANCHOR_END: Span
+
+impl Span {
+    // Span covering the whole of `source`, the contents of `file`
+    pub fn whole_file(file: PathBuf, source: &str) -> Self {
+        let last_line = source.rsplit('\n').next().unwrap_or_default();
+        Self {
+            file,
+            start_line: 1,
+            start_col: 0,
+            end_line: source.matches('\n').count() + 1,
+            end_col: last_line.chars().count(),
+            byte_range: 0..source.len(),
+        }
+    }
+
+    // The text the span covers, given the contents of its file
+    pub fn slice<'s>(&self, source: &'s str) -> Option<&'s str> {
+        source.get(self.byte_range.clone())
+    }
+}
 
 // ANCHOR: ItemFn
 // Represents a function definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionNode {
     pub id: NodeId,
     pub name: String,
+    // Canonical path, like `crate::net::Client::new`. Methods of trait impls are
+    // qualified with the impl, like `<crate::net::Client as Drop>::drop`.
+    pub path: String,
     pub visibility: VisibilityKind,
     pub parameters: Vec<ParameterNode>,
     pub return_type: Option<TypeId>,
     pub generic_params: Vec<GenericParamNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    // Source text of the body as written, including comments and formatting
     pub body: Option<String>,
+    // Token-string form of the body, only stored with `normalized_bodies`
+    pub body_normalized: Option<String>,
+    // Declared in an `extern` block, implemented outside the crate
+    pub is_foreign: bool,
+    pub span: Span,
 }
 //ANCHOR_END: ItemFn
 
 // Represents a parameter in a function
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParameterNode {
     pub id: NodeId,
     pub name: Option<String>,
     pub type_id: TypeId,
     pub is_mutable: bool,
     pub is_self: bool,
+    pub span: Span,
 }
 
 // Represents a type definition (struct, enum, type alias, or union)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TypeDefNode {
     Struct(StructNode),
     Enum(EnumNode),
     TypeAlias(TypeAliasNode),
     Union(UnionNode),
+    ForeignType(ForeignTypeNode),
 }
 
 // ANCHOR: StructNode
 // Represents a struct definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StructNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub fields: Vec<FieldNode>,
     pub generic_params: Vec<GenericParamNode>,
     pub attributes: Vec<Attribute>, // Replace Vec<String>
     pub docstring: Option<String>,
+    pub span: Span,
 }
 //ANCHOR_END: StructNode
 
 // Represents an enum definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub variants: Vec<VariantNode>,
     pub generic_params: Vec<GenericParamNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    pub span: Span,
 }
 
 // ANCHOR: field_node
 // Represents a field in a struct
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldNode {
     pub id: NodeId,
     pub name: Option<String>,
     pub type_id: TypeId,
     pub visibility: VisibilityKind,
     pub attributes: Vec<Attribute>,
+    pub span: Span,
 }
 //ANCHOR_END: field_node
 
 // Represents a variant in an enum
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariantNode {
     pub id: NodeId,
     pub name: String,
     pub fields: Vec<FieldNode>,
     pub discriminant: Option<String>,
     pub attributes: Vec<Attribute>,
+    pub span: Span,
 }
 
 // Represents a type alias (type NewType = OldType)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeAliasNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub type_id: TypeId,
     pub generic_params: Vec<GenericParamNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    pub span: Span,
 }
 
 // Represents a union definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnionNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub fields: Vec<FieldNode>,
     pub generic_params: Vec<GenericParamNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    pub span: Span,
+}
+
+// Represents an opaque type declared in an `extern` block (`type Handle;`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignTypeNode {
+    pub id: NodeId,
+    pub name: String,
+    pub path: String,
+    pub visibility: VisibilityKind,
+    pub attributes: Vec<Attribute>,
+    pub docstring: Option<String>,
+    pub span: Span,
 }
 
 // ANCHOR: ImplNode
 // Represents an implementation block
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImplNode {
     pub id: NodeId,
     pub self_type: TypeId,
     pub trait_type: Option<TypeId>,
     pub methods: Vec<FunctionNode>,
     pub generic_params: Vec<GenericParamNode>,
+    pub assoc_types: Vec<AssocTypeNode>,
+    pub assoc_consts: Vec<AssocConstNode>,
+    // What `trait_type` refers to, `None` for inherent impls
+    pub trait_ref: Option<TraitRef>,
+    pub span: Span,
 }
 //ANCHOR_END: ItemImpl
 
+// The trait implemented by a trait impl
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum TraitRef {
+    // A trait defined in the analyzed code
+    Local(NodeId),
+    // A trait from another crate, by its full path, e.g. `["std", "fmt", "Display"]`
+    External { path: Vec<String> },
+    // A trait that could not be found
+    Unresolved,
+}
+
 // ANCHOR: TraitNode
 // Represents a trait definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraitNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub methods: Vec<FunctionNode>,
     pub generic_params: Vec<GenericParamNode>,
     pub super_traits: Vec<TypeId>,
+    pub assoc_types: Vec<AssocTypeNode>,
+    pub assoc_consts: Vec<AssocConstNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    pub span: Span,
 }
 //ANCHOR_END: TraitNode
 
-#[derive(Debug, Serialize, Deserialize)]
+// ANCHOR: AssocTypeNode
+// Represents an associated type, declared in a trait (`type Item: Clone;`) or given a
+// value in an impl (`type Item = u8;`). Generic associated types keep their own
+// parameters in `generic_params`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssocTypeNode {
+    pub id: NodeId,
+    pub name: String,
+    pub generic_params: Vec<GenericParamNode>,
+    // Bounds declared in the trait
+    pub bounds: Vec<TypeId>,
+    // The type assigned in an impl, or the default given in the trait
+    pub type_id: Option<TypeId>,
+    pub attributes: Vec<Attribute>,
+    pub docstring: Option<String>,
+    pub span: Span,
+}
+//ANCHOR_END: AssocTypeNode
+
+// Represents an associated constant in a trait or impl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssocConstNode {
+    pub id: NodeId,
+    pub name: String,
+    pub type_id: TypeId,
+    // The value assigned in an impl, or the default given in the trait
+    pub value: Option<String>,
+    // Token-string form of the value, only stored with `normalized_bodies`
+    pub value_normalized: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub docstring: Option<String>,
+    pub span: Span,
+}
+
+// Represents a trait alias (`trait Alias = Bound + Other;`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitAliasNode {
+    pub id: NodeId,
+    pub name: String,
+    pub path: String,
+    pub visibility: VisibilityKind,
+    pub generic_params: Vec<GenericParamNode>,
+    pub bounds: Vec<TypeId>,
+    pub attributes: Vec<Attribute>,
+    pub docstring: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleNode {
     pub id: NodeId,
     pub name: String,
+    // Canonical path, starting with the name of the root module, like `crate::net`
+    pub path: String,
     pub visibility: VisibilityKind,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
@@ -150,36 +351,180 @@ pub struct ModuleNode {
     pub items: Vec<NodeId>,
     pub imports: Vec<ImportNode>,
     pub exports: Vec<NodeId>,
+    // Problems hit while parsing the module's file in error-tolerant mode
+    pub diagnostics: Vec<Diagnostic>,
+    pub span: Span,
+}
+
+// Represents a part of the source that could not be analyzed, e.g. an item that does
+// not parse or a module file that could not be read. `line` and `column` are 1-based.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn from_syn_error(file: PathBuf, err: &syn::Error) -> Self {
+        let start = err.span().start();
+        Self {
+            file,
+            line: start.line,
+            column: start.column + 1,
+            message: err.to_string(),
+        }
+    }
+}
+
+// ANCHOR: CommentNode
+// Represents a regular (non-doc) comment and the node it is attached to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentNode {
+    pub id: NodeId,
+    // The comment as written, including `//` or `/* */`
+    pub text: String,
+    pub kind: CommentKind,
+    pub placement: CommentPlacement,
+    // The node the comment is attached to
+    pub target: NodeId,
+    // The module the comment is in
+    pub module: NodeId,
+    pub span: Span,
+}
+//ANCHOR_END: CommentNode
+
+// Markers a comment can start with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CommentKind {
+    Regular,
+    Todo,
+    Fixme,
+    Hack,
+    // A `// SAFETY:` justification for unsafe code
+    Safety,
+}
+
+// Where a comment sits relative to the node it is attached to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CommentPlacement {
+    // On the lines before the node
+    Leading,
+    // After the node on its last line
+    Trailing,
+    // Inside the node, e.g. between the statements of a function body
+    Inner,
 }
 
 // Represents a constant or static variable
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValueNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub type_id: TypeId,
     pub kind: ValueKind,
+    // Source text of the value as written
     pub value: Option<String>,
+    // Token-string form of the value, only stored with `normalized_bodies`
+    pub value_normalized: Option<String>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    pub span: Span,
 }
 
 // Represents a macro definition
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MacroNode {
     pub id: NodeId,
     pub name: String,
+    pub path: String,
     pub visibility: VisibilityKind,
     pub kind: MacroKind,
     pub rules: Vec<MacroRuleNode>,
     pub attributes: Vec<Attribute>,
     pub docstring: Option<String>,
+    // Source text of the body as written, including comments and formatting
     pub body: Option<String>,
+    // Token-string form of the body, only stored with `normalized_bodies`
+    pub body_normalized: Option<String>,
+    pub span: Span,
+}
+
+// ANCHOR: CallNode
+// A call site in the body of a function, linked to the function by a `Calls` relation.
+// Inside macro invocations, only the arguments of standard macros like `assert_eq!`,
+// `format!` and `vec!` are searched for calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallNode {
+    pub id: NodeId,
+    // Function, method or default trait method whose body contains the call
+    pub caller: NodeId,
+    // Module the caller is declared in, which the path is resolved from
+    pub module: NodeId,
+    pub kind: CallKind,
+    // Path of the called function as written, without generic arguments, e.g.
+    // `["Vec", "new"]`. Just the method name for method calls.
+    pub path: Vec<String>,
+    // What a method is called on, `None` for other calls
+    pub receiver: Option<Receiver>,
+    // The called function, when it is defined in the graph
+    pub callee: Option<NodeId>,
+    // Canonical path of the called function, or its full path in another crate
+    pub callee_path: Option<String>,
+    // What the path refers to, set once the whole crate has been visited
+    pub resolution: Option<Resolution>,
+    // How sure `callee` or `candidates` are, `None` when the call leads to no function
+    // of the graph
+    pub confidence: Option<CallConfidence>,
+    // Methods an ambiguous method call may be of, e.g. the methods of two traits
+    // implemented for the receiver type. `callee` is not set then.
+    pub candidates: Vec<NodeId>,
+    pub span: Span,
+}
+//ANCHOR_END: CallNode
+
+// The receiver of a method call, as far as its type can be told from the body of the
+// caller. Resolved to a type once the whole crate has been visited.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Receiver {
+    // `self`, of the type of the impl or trait the caller is in
+    SelfValue,
+    // A parameter, binding or struct literal of the type with this path, written like
+    // `x: Config`, `x: &dyn Describe` or `Config { .. }`
+    Typed(Vec<String>),
+    // A named field of another receiver, like `self.config`
+    Field(Box<Receiver>, String),
+    // Anything else, like the result of another call
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CallKind {
+    // `path(args)`
+    Function,
+    // `receiver.method(args)`
+    Method,
+    // A function named without being called, like `parse` in `iter.map(parse)`. Only
+    // kept when the path resolves to a function of the graph.
+    Reference,
+}
+
+// Represents a macro invocation (`name!(..)`), in item position or inside a body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroInvocationNode {
+    pub id: NodeId,
+    // Path of the invoked macro as written, e.g. `println` or `std :: vec`
+    pub path: String,
+    // The macro definition in this graph, if the invoked name matches one
+    pub macro_id: Option<NodeId>,
+    pub span: Span,
 }
 
 // Represents a macro rule
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MacroRuleNode {
     pub id: NodeId,
     pub pattern: String,
@@ -187,42 +532,89 @@ pub struct MacroRuleNode {
 }
 
 // Different kinds of macros
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MacroKind {
     DeclarativeMacro,
     ProcedureMacro { kind: ProcMacroKind },
 }
 
 // Different kinds of procedural macros
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ProcMacroKind {
     Derive,
     Attribute,
     Function,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ValueKind {
     Constant,
     Static { is_mutable: bool },
+    // A static declared in an `extern` block
+    ForeignStatic { is_mutable: bool },
+}
+
+// ANCHOR: ForeignModNode
+// Represents an `extern "ABI" { .. }` block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignModNode {
+    pub id: NodeId,
+    // ABI string as written, `None` for a bare `extern { .. }` (which means "C")
+    pub abi: Option<String>,
+    pub is_unsafe: bool,
+    // Foreign functions, statics and types declared in the block
+    pub items: Vec<NodeId>,
+    pub attributes: Vec<Attribute>,
+    pub docstring: Option<String>,
+    pub span: Span,
 }
+//ANCHOR_END: ForeignModNode
 
-// Represents a module
-#[derive(Debug, Serialize, Deserialize)]
+impl ForeignModNode {
+    // The `#[link(..)]` attributes naming the native libraries the block binds to
+    pub fn links(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.iter().filter(|attr| attr.name == "link")
+    }
+}
+
+// ANCHOR: ImportNode
+// Represents one imported name: a leaf of a `use` tree or an `extern crate` item.
+// `use a::{b, c as d, e::*}` becomes three imports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportNode {
     pub id: NodeId,
+    // Full path of the imported item, or of the module for glob imports
     pub path: Vec<String>,
+    // Name given with `as`
+    pub alias: Option<String>,
+    pub is_glob: bool,
+    // `Public` for `pub use` re-exports
+    pub visibility: VisibilityKind,
     pub kind: ImportKind,
+    pub span: Span,
+}
+//ANCHOR_END: ImportNode
+
+impl ImportNode {
+    // The name the import is visible as in its module, `None` for glob imports
+    pub fn name(&self) -> Option<&str> {
+        if self.is_glob {
+            return None;
+        }
+        self.alias
+            .as_deref()
+            .or(self.path.last().map(String::as_str))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ImportKind {
     UseStatement,
     ExternCrate,
 }
 
 // Represent an attribute
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Attribute {
     pub name: String,          // e.g., "derive", "cfg", "serde"
     pub args: Vec<String>,     // Arguments or parameters of the attribute
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 // ANCHOR: Relation
 // Represents a relation between nodes
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Relation {
     pub source: NodeId,
     pub target: NodeId,
@@ -12,7 +12,7 @@ pub struct Relation {
 
 // ANCHOR: Uses
 // Different kinds of relations
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum RelationKind {
     FunctionParameter,
     FunctionReturn,
@@ -26,9 +26,35 @@ pub enum RelationKind {
     Uses,
     ValueType,
     MacroUse,
+    // An associated type or const in an impl to its declaration in the implemented trait
+    ImplementsItem,
+    // A comment to the node it is attached to
+    Annotates,
+    // A named type to the item that defines it
+    ResolvesTo,
+    // A function to the function it calls, or to the `CallNode` of a call that does not
+    // lead to a function of the graph
+    Calls { confidence: CallConfidence },
     // MacroExpansion,
     // This is outside the scope of this project right now, but if it were to be implemented, it
     // would probably go here.
 }
 //ANCHOR_END: Uses
 //ANCHOR_END: Relation
+
+// How sure a `Calls` relation is, ordered from least to most certain. Calls that lead
+// to no function of the graph link the caller to their `CallNode`, with `Unresolved`
+// or `External` to tell why.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CallConfidence {
+    // No function was found for the call
+    Unresolved,
+    // A function of another crate, or a method of a built-in type
+    External,
+    // One of several methods the call may be of, all of which are linked
+    Ambiguous,
+    // The only method of the graph with the name, found without the receiver type
+    Heuristic,
+    // Named by the path of the call, or found through the type of the receiver
+    Exact,
+}
@@ -0,0 +1,1036 @@
+use crate::parser::graph::CodeGraph;
+use crate::parser::nodes::{
+    stable_hash, CallKind, CallNode, FunctionNode, ImplNode, ModuleNode, NodeId, Receiver,
+    TraitNode, TraitRef, TypeDefNode,
+};
+use crate::parser::relations::{CallConfidence, Relation, RelationKind};
+use crate::parser::types::{
+    GenericParamKind, GenericParamNode, Resolution, TypeId, TypeKind, TypeNode,
+};
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+// Built-in types, which are written like single-segment paths
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+
+// Types and traits in scope everywhere through the standard prelude, with their paths
+const PRELUDE: &[(&str, &str)] = &[
+    ("Box", "std::boxed::Box"),
+    ("String", "std::string::String"),
+    ("Vec", "std::vec::Vec"),
+    ("Option", "std::option::Option"),
+    ("Result", "std::result::Result"),
+    ("Copy", "std::marker::Copy"),
+    ("Send", "std::marker::Send"),
+    ("Sync", "std::marker::Sync"),
+    ("Sized", "std::marker::Sized"),
+    ("Unpin", "std::marker::Unpin"),
+    ("Drop", "std::ops::Drop"),
+    ("Fn", "std::ops::Fn"),
+    ("FnMut", "std::ops::FnMut"),
+    ("FnOnce", "std::ops::FnOnce"),
+    ("Clone", "std::clone::Clone"),
+    ("ToOwned", "std::borrow::ToOwned"),
+    ("ToString", "std::string::ToString"),
+    ("PartialEq", "std::cmp::PartialEq"),
+    ("PartialOrd", "std::cmp::PartialOrd"),
+    ("Eq", "std::cmp::Eq"),
+    ("Ord", "std::cmp::Ord"),
+    ("AsRef", "std::convert::AsRef"),
+    ("AsMut", "std::convert::AsMut"),
+    ("Into", "std::convert::Into"),
+    ("From", "std::convert::From"),
+    ("TryFrom", "std::convert::TryFrom"),
+    ("TryInto", "std::convert::TryInto"),
+    ("Default", "std::default::Default"),
+    ("Iterator", "std::iter::Iterator"),
+    ("IntoIterator", "std::iter::IntoIterator"),
+    ("DoubleEndedIterator", "std::iter::DoubleEndedIterator"),
+    ("ExactSizeIterator", "std::iter::ExactSizeIterator"),
+    ("Extend", "std::iter::Extend"),
+    ("FromIterator", "std::iter::FromIterator"),
+];
+
+// Functions and variant constructors in scope everywhere through the standard prelude
+const PRELUDE_FUNCTIONS: &[(&str, &str)] = &[
+    ("Some", "std::option::Option::Some"),
+    ("Ok", "std::result::Result::Ok"),
+    ("Err", "std::result::Result::Err"),
+    ("drop", "std::mem::drop"),
+];
+
+// Item a path leads to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Target {
+    Local(NodeId),
+    // Full path of an item in another crate, starting with the crate name
+    External(Vec<String>),
+}
+
+// Second pass over the graph, once every module has been visited:
+// - resolve the path of every `Named` type in the module it is written in (`scopes`),
+//   setting its `resolution` and linking it to the item it names with a `ResolvesTo`
+//   relation. Types with no recorded scope are resolved from the crate root.
+// - resolve the trait of every trait impl and link the impl's associated items to their
+//   declarations in local traits
+// - set the paths of impl methods, which are qualified with the resolved impl types
+// - resolve the path of every call site and link callers to callees, see `resolve_calls`
+// - merge the types that turned out to be the same, see `intern_types`
+pub(crate) fn resolve_names(graph: &mut CodeGraph, root: NodeId, scopes: &HashMap<TypeId, NodeId>) {
+    let mut resolutions = HashMap::new();
+    let mut targets: HashMap<TypeId, Target> = HashMap::new();
+    {
+        let resolver = Resolver::new(graph, root);
+        for ty in &graph.type_graph {
+            let TypeKind::Named { path, .. } = &ty.kind else {
+                continue;
+            };
+            let module = scopes.get(&ty.id).copied().unwrap_or(root);
+            let (resolution, target) = resolver.resolve_type(module, path);
+            resolutions.insert(ty.id, resolution);
+            if let Some(target) = target {
+                targets.insert(ty.id, target);
+            }
+        }
+    }
+
+    for ty in &mut graph.type_graph {
+        ty.resolution = resolutions.remove(&ty.id);
+        if let Some(Target::Local(target)) = targets.get(&ty.id) {
+            graph.relations.push(Relation {
+                source: ty.id,
+                target: *target,
+                kind: RelationKind::ResolvesTo,
+            });
+        }
+    }
+
+    resolve_impl_traits(graph, &targets);
+    set_impl_method_paths(graph, &targets);
+    resolve_calls(graph, root, &targets);
+    link_macro_invocations(graph);
+    intern_types(graph, root, scopes, &targets);
+}
+
+// Set the `trait_ref` of every trait impl from what its trait type resolved to
+fn resolve_impl_traits(graph: &mut CodeGraph, targets: &HashMap<TypeId, Target>) {
+    let mut links = Vec::new();
+    for impl_node in &mut graph.impls {
+        let Some(trait_type) = impl_node.trait_type else {
+            continue;
+        };
+        let target = targets.get(&trait_type);
+
+        let local_trait = match target {
+            Some(Target::Local(id)) => graph
+                .traits
+                .iter()
+                .chain(&graph.private_traits)
+                .find(|t| t.id == *id),
+            _ => None,
+        };
+        impl_node.trait_ref = Some(match (local_trait, target) {
+            (Some(trait_def), _) => {
+                links.extend(assoc_item_links(impl_node, trait_def));
+                TraitRef::Local(trait_def.id)
+            }
+            (None, Some(Target::External(path))) => TraitRef::External { path: path.clone() },
+            _ => TraitRef::Unresolved,
+        });
+    }
+
+    graph
+        .relations
+        .extend(links.into_iter().map(|(source, target)| Relation {
+            source,
+            target,
+            kind: RelationKind::ImplementsItem,
+        }));
+}
+
+// Link macro invocations to the macro of the same name defined in the crate, if any,
+// with a `MacroUse` relation
+fn link_macro_invocations(graph: &mut CodeGraph) {
+    let mut macros: HashMap<&str, NodeId> = HashMap::new();
+    for mac in &graph.macros {
+        macros.entry(mac.name.as_str()).or_insert(mac.id);
+    }
+
+    for invocation in &mut graph.macro_invocations {
+        let name = invocation
+            .path
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if let Some(&macro_id) = macros.get(name) {
+            invocation.macro_id = Some(macro_id);
+            graph.relations.push(Relation {
+                source: invocation.id,
+                target: macro_id,
+                kind: RelationKind::MacroUse,
+            });
+        }
+    }
+}
+
+// Methods of inherent impls get `Type::method` paths, methods of trait impls
+// `<Type as Trait>::method`
+fn set_impl_method_paths(graph: &mut CodeGraph, targets: &HashMap<TypeId, Target>) {
+    let item_paths: HashMap<NodeId, &str> = graph
+        .path_nodes()
+        .map(|node| (node.id(), node.path()))
+        .collect();
+    let types: HashMap<TypeId, &TypeNode> = graph.type_graph.iter().map(|t| (t.id, t)).collect();
+    let paths = TypePaths {
+        types,
+        targets,
+        item_paths,
+    };
+
+    let prefixes: Vec<String> = graph
+        .impls
+        .iter()
+        .map(|impl_node| {
+            let self_path = paths.render(impl_node.self_type);
+            match impl_node.trait_type {
+                Some(trait_type) => format!("<{} as {}>", self_path, paths.render(trait_type)),
+                None => self_path,
+            }
+        })
+        .collect();
+
+    for (impl_node, prefix) in graph.impls.iter_mut().zip(prefixes) {
+        for method in &mut impl_node.methods {
+            method.path = format!("{}::{}", prefix, method.name);
+        }
+    }
+}
+
+// Resolve every call site from the module of its caller, and link the caller to the
+// function it calls with a `Calls` relation:
+// - paths are resolved like the paths of types, with their last segment looked up among
+//   the functions of a module or the associated functions of a type or trait
+// - methods are looked up in the type of their receiver, when the parameters, bindings
+//   and fields it goes through tell it. Otherwise the only method of the graph with the
+//   name is taken as a heuristic, or all of them as ambiguous candidates.
+// Calls that do not lead to a function of the graph are linked to their `CallNode`
+// instead, and references to other things than functions (local variables, constants)
+// are dropped.
+fn resolve_calls(graph: &mut CodeGraph, root: NodeId, targets: &HashMap<TypeId, Target>) {
+    let callees: Vec<Callee> = {
+        let resolver = Resolver::new(graph, root);
+        let functions = Functions::new(graph, targets);
+        graph
+            .calls
+            .iter()
+            .map(|call| functions.resolve(&resolver, call))
+            .collect()
+    };
+    let function_paths: HashMap<NodeId, String> = graph
+        .path_nodes()
+        .map(|node| (node.id(), node.path().to_string()))
+        .collect();
+
+    // A caller calling a function several times is linked to it once, as surely as the
+    // surest of the calls
+    let mut relations: Vec<Relation> = Vec::new();
+    let mut links: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+    let mut link = |source: NodeId, target: NodeId, confidence: CallConfidence| match links
+        .entry((source, target))
+    {
+        Entry::Occupied(entry) => {
+            let kind = &mut relations[*entry.get()].kind;
+            if let RelationKind::Calls { confidence: linked } = kind {
+                *linked = confidence.max(*linked);
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(relations.len());
+            relations.push(Relation {
+                source,
+                target,
+                kind: RelationKind::Calls { confidence },
+            });
+        }
+    };
+
+    let calls = std::mem::take(&mut graph.calls);
+    for (mut call, callee) in calls.into_iter().zip(callees) {
+        call.resolution = Some(match callee {
+            Callee::Local(ids, confidence) => {
+                call.confidence = Some(confidence);
+                match ids[..] {
+                    [id] => {
+                        call.callee = Some(id);
+                        call.callee_path = function_paths.get(&id).cloned();
+                    }
+                    _ => call.candidates = ids,
+                }
+                Resolution::Local
+            }
+            Callee::External(path) => {
+                call.callee_path = Some(path.join("::"));
+                Resolution::External {
+                    crate_name: path[0].clone(),
+                }
+            }
+            Callee::Primitive => Resolution::Primitive,
+            Callee::Unresolved => Resolution::Unresolved,
+        });
+
+        match call.confidence {
+            Some(confidence) => {
+                for id in call.callee.iter().chain(&call.candidates) {
+                    link(call.caller, *id, confidence);
+                }
+            }
+            None if call.kind == CallKind::Reference => continue,
+            None => {
+                let confidence = match call.resolution {
+                    Some(Resolution::Unresolved) => CallConfidence::Unresolved,
+                    _ => CallConfidence::External,
+                };
+                link(call.caller, call.id, confidence);
+            }
+        }
+        graph.calls.push(call);
+    }
+    graph.relations.extend(relations);
+}
+
+// What a call was resolved to
+enum Callee {
+    // Functions of the graph, more than one when the call is ambiguous
+    Local(Vec<NodeId>, CallConfidence),
+    // Full path of a function of another crate
+    External(Vec<String>),
+    // A method of a built-in type, like `u8::from`
+    Primitive,
+    Unresolved,
+}
+
+// Functions that calls can lead to besides the free functions of modules: the methods of
+// types and traits, and what `Self` and the receivers of method calls stand for
+struct Functions<'a> {
+    targets: &'a HashMap<TypeId, Target>,
+    types: HashMap<TypeId, &'a TypeNode>,
+    // Methods of inherent impls by the item of their self type and name
+    inherent: HashMap<(NodeId, &'a str), NodeId>,
+    // Methods of trait impls by their self type, local or external, and name
+    trait_impls: HashMap<(Target, &'a str), Vec<NodeId>>,
+    // Local traits implemented by each local type
+    impl_traits: HashMap<NodeId, Vec<NodeId>>,
+    // Methods declared by traits, with or without a default body
+    trait_methods: HashMap<(NodeId, &'a str), NodeId>,
+    // Item the `Self` of every method stands for
+    owners: HashMap<NodeId, NodeId>,
+    // Types of the named fields of structs
+    fields: HashMap<(NodeId, &'a str), TypeId>,
+    // Every method of the graph by name, for receivers of unknown type
+    by_name: HashMap<&'a str, Vec<NodeId>>,
+}
+
+impl<'a> Functions<'a> {
+    fn new(graph: &'a CodeGraph, targets: &'a HashMap<TypeId, Target>) -> Self {
+        let mut functions = Self {
+            targets,
+            types: graph.type_graph.iter().map(|t| (t.id, t)).collect(),
+            inherent: HashMap::new(),
+            trait_impls: HashMap::new(),
+            impl_traits: HashMap::new(),
+            trait_methods: HashMap::new(),
+            owners: HashMap::new(),
+            fields: HashMap::new(),
+            by_name: HashMap::new(),
+        };
+
+        for trait_node in graph.traits.iter().chain(&graph.private_traits) {
+            for method in &trait_node.methods {
+                let name = method.name.as_str();
+                functions
+                    .trait_methods
+                    .insert((trait_node.id, name), method.id);
+                functions.owners.insert(method.id, trait_node.id);
+                functions.by_name.entry(name).or_default().push(method.id);
+            }
+        }
+        for impl_node in &graph.impls {
+            let Some(self_type) = targets.get(&impl_node.self_type) else {
+                continue;
+            };
+            if let (Target::Local(self_item), Some(TraitRef::Local(trait_id))) =
+                (self_type, &impl_node.trait_ref)
+            {
+                let traits = functions.impl_traits.entry(*self_item).or_default();
+                traits.push(*trait_id);
+            }
+            for method in &impl_node.methods {
+                let name = method.name.as_str();
+                match (self_type, impl_node.trait_type) {
+                    (Target::Local(self_item), None) => {
+                        functions
+                            .inherent
+                            .entry((*self_item, name))
+                            .or_insert(method.id);
+                    }
+                    (_, None) => {}
+                    (_, Some(_)) => {
+                        let key = (self_type.clone(), name);
+                        functions
+                            .trait_impls
+                            .entry(key)
+                            .or_default()
+                            .push(method.id);
+                    }
+                }
+                if let Target::Local(self_item) = self_type {
+                    functions.owners.insert(method.id, *self_item);
+                }
+                functions.by_name.entry(name).or_default().push(method.id);
+            }
+        }
+        for def in &graph.defined_types {
+            if let TypeDefNode::Struct(s) = def {
+                for field in &s.fields {
+                    if let Some(name) = &field.name {
+                        functions.fields.insert((s.id, name), field.type_id);
+                    }
+                }
+            }
+        }
+        functions
+    }
+
+    fn resolve(&self, resolver: &Resolver<'a>, call: &'a CallNode) -> Callee {
+        let Some((name, prefix)) = call.path.split_last() else {
+            return Callee::Unresolved;
+        };
+        if call.kind == CallKind::Method {
+            return self.resolve_method(resolver, call, name);
+        }
+
+        let target = match prefix {
+            [] => resolver.resolve_value(call.module, &call.path).or_else(|| {
+                PRELUDE_FUNCTIONS
+                    .iter()
+                    .find(|(prelude_name, _)| prelude_name == name)
+                    .map(|(_, std_path)| {
+                        Target::External(std_path.split("::").map(str::to_string).collect())
+                    })
+            }),
+            [first] if first == "Self" => match self.owners.get(&call.caller) {
+                Some(owner) => return self.associated(&Target::Local(*owner), name),
+                None => None,
+            },
+            _ => match resolver.resolve_value(call.module, &call.path) {
+                Some(target) => Some(target),
+                // `Vec::new`, `u8::from`
+                None => match resolver.resolve_type(call.module, prefix) {
+                    (_, Some(owner)) => return self.associated(&owner, name),
+                    (Resolution::Primitive, None) => return Callee::Primitive,
+                    // Like other paths, ones that start with a name not in scope are taken
+                    // to lead to another crate, e.g. `serde_json::to_string`
+                    (Resolution::Unresolved, None) => Some(Target::External(call.path.clone())),
+                    _ => None,
+                },
+            },
+        };
+        match target {
+            Some(Target::Local(id)) => Callee::Local(vec![id], CallConfidence::Exact),
+            Some(Target::External(path)) => Callee::External(path),
+            None => Callee::Unresolved,
+        }
+    }
+
+    // A method is looked up in the type of its receiver when that is known, and among
+    // every method of the graph otherwise
+    fn resolve_method(&self, resolver: &Resolver<'a>, call: &'a CallNode, name: &str) -> Callee {
+        let receiver_type = call
+            .receiver
+            .as_ref()
+            .and_then(|receiver| self.receiver_type(resolver, call, receiver));
+        if let Some(receiver_type) = receiver_type {
+            return self.associated(&receiver_type, name);
+        }
+
+        match self.by_name.get(name).map(Vec::as_slice) {
+            Some([id]) => Callee::Local(vec![*id], CallConfidence::Heuristic),
+            Some(ids) => Callee::Local(ids.to_vec(), CallConfidence::Ambiguous),
+            None => Callee::Unresolved,
+        }
+    }
+
+    // Function `name` of a type or trait: an inherent method, the methods of its trait
+    // impls, or the method of a local trait it implements
+    fn associated(&self, owner: &Target, name: &str) -> Callee {
+        let mut ids = Vec::new();
+        if let Target::Local(item) = owner {
+            ids.extend(self.inherent.get(&(*item, name)));
+            ids.extend(self.trait_methods.get(&(*item, name)));
+        }
+        if ids.is_empty() {
+            ids.extend(
+                self.trait_impls
+                    .get(&(owner.clone(), name))
+                    .into_iter()
+                    .flatten(),
+            );
+        }
+        if let (true, Target::Local(item)) = (ids.is_empty(), owner) {
+            // Default methods not overridden by the impl
+            for trait_id in self.impl_traits.get(item).into_iter().flatten() {
+                ids.extend(self.trait_methods.get(&(*trait_id, name)));
+            }
+        }
+
+        match (ids.len(), owner) {
+            (0, Target::External(path)) => {
+                Callee::External([path.as_slice(), &[name.to_string()]].concat())
+            }
+            (0, Target::Local(_)) => Callee::Unresolved,
+            (1, _) => Callee::Local(ids, CallConfidence::Exact),
+            _ => Callee::Local(ids, CallConfidence::Ambiguous),
+        }
+    }
+
+    // Type of the receiver of a method call, if it is a type of the graph or of another
+    // crate. Generic parameters and receivers of unknown type give `None`.
+    fn receiver_type(
+        &self,
+        resolver: &Resolver<'a>,
+        call: &'a CallNode,
+        receiver: &'a Receiver,
+    ) -> Option<Target> {
+        match receiver {
+            Receiver::SelfValue => self.owners.get(&call.caller).map(|id| Target::Local(*id)),
+            Receiver::Typed(path) if path == &["Self"] => {
+                self.owners.get(&call.caller).map(|id| Target::Local(*id))
+            }
+            Receiver::Typed(path) => resolver.resolve_type(call.module, path).1,
+            Receiver::Field(base, name) => match self.receiver_type(resolver, call, base)? {
+                Target::Local(item) => self.named_type(*self.fields.get(&(item, name.as_str()))?),
+                Target::External(_) => None,
+            },
+            Receiver::Unknown => None,
+        }
+    }
+
+    // What the type of a field names, through references and trait objects
+    fn named_type(&self, id: TypeId) -> Option<Target> {
+        let ty = self.types.get(&id)?;
+        match ty.kind {
+            TypeKind::Named { .. } => self.targets.get(&id).cloned(),
+            TypeKind::Reference { .. }
+            | TypeKind::Paren { .. }
+            | TypeKind::TraitObject { .. }
+            | TypeKind::ImplTrait { .. } => self.named_type(*ty.related_types.first()?),
+            _ => None,
+        }
+    }
+}
+
+// Renders types with the canonical paths of the items they name
+struct TypePaths<'a> {
+    types: HashMap<TypeId, &'a TypeNode>,
+    targets: &'a HashMap<TypeId, Target>,
+    item_paths: HashMap<NodeId, &'a str>,
+}
+
+impl TypePaths<'_> {
+    // Generic arguments are left out, as in `Vec::new`
+    fn render(&self, id: TypeId) -> String {
+        let Some(ty) = self.types.get(&id) else {
+            return "_".to_string();
+        };
+        let related = |i: usize| {
+            ty.related_types
+                .get(i)
+                .map_or_else(|| "_".to_string(), |id| self.render(*id))
+        };
+        let all_related = |separator: &str| {
+            ty.related_types
+                .iter()
+                .map(|id| self.render(*id))
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+
+        match &ty.kind {
+            TypeKind::Named { path, .. } => match self.targets.get(&id) {
+                Some(Target::Local(item)) => self
+                    .item_paths
+                    .get(item)
+                    .map_or_else(|| path.join("::"), |p| p.to_string()),
+                Some(Target::External(full_path)) => full_path.join("::"),
+                None => path.join("::"),
+            },
+            TypeKind::Reference { is_mutable, .. } => {
+                format!("&{}{}", if *is_mutable { "mut " } else { "" }, related(0))
+            }
+            TypeKind::Slice {} => format!("[{}]", related(0)),
+            TypeKind::Array { size } => {
+                format!("[{}; {}]", related(0), size.as_deref().unwrap_or("_"))
+            }
+            TypeKind::Tuple {} => format!("({})", all_related(", ")),
+            TypeKind::Paren {} => related(0),
+            TypeKind::TraitObject { .. } => format!("dyn {}", all_related(" + ")),
+            _ => "_".to_string(),
+        }
+    }
+}
+
+// Merge the types that are the same once their names are resolved, so `Vec<u8>` and
+// `std::vec::Vec<u8>` become one type while `Error` written in two modules, naming two
+// different enums, stays two. The merged type keeps every spelling.
+fn intern_types(
+    graph: &mut CodeGraph,
+    root: NodeId,
+    scopes: &HashMap<TypeId, NodeId>,
+    targets: &HashMap<TypeId, Target>,
+) {
+    let new_ids: HashMap<TypeId, TypeId> = {
+        let keys = TypeKeys {
+            types: graph.type_graph.iter().map(|t| (t.id, t)).collect(),
+            targets,
+            item_paths: graph
+                .path_nodes()
+                .map(|node| (node.id(), node.path()))
+                .collect(),
+            scopes,
+            module_paths: graph
+                .modules
+                .iter()
+                .map(|m| (m.id, m.path.as_str()))
+                .collect(),
+            root,
+        };
+        let mut memo = HashMap::new();
+        graph
+            .type_graph
+            .iter()
+            .map(|ty| {
+                let key = keys.key(ty.id, &mut memo);
+                let new_id = NodeId::new(root.namespace(), stable_hash(&["type", &key]));
+                (ty.id, new_id)
+            })
+            .collect()
+    };
+    graph.remap_type_ids(&new_ids);
+
+    // Keep the first node of each merged type, with the spellings of the others
+    let mut index_of: HashMap<TypeId, usize> = HashMap::new();
+    let mut merged: Vec<TypeNode> = Vec::new();
+    for ty in std::mem::take(&mut graph.type_graph) {
+        match index_of.get(&ty.id) {
+            Some(&i) => {
+                for spelling in ty.spellings {
+                    if !merged[i].spellings.contains(&spelling) {
+                        merged[i].spellings.push(spelling);
+                    }
+                }
+            }
+            None => {
+                index_of.insert(ty.id, merged.len());
+                merged.push(ty);
+            }
+        }
+    }
+    graph.type_graph = merged;
+
+    // Merged types have one `ResolvesTo` relation each. Relations of other kinds are
+    // kept as they are, even when they now point to the same type.
+    let mut seen = HashSet::new();
+    graph
+        .relations
+        .retain(|r| r.kind != RelationKind::ResolvesTo || seen.insert((r.source, r.target)));
+}
+
+// Identity of types once their names are resolved
+struct TypeKeys<'a> {
+    types: HashMap<TypeId, &'a TypeNode>,
+    targets: &'a HashMap<TypeId, Target>,
+    item_paths: HashMap<NodeId, &'a str>,
+    scopes: &'a HashMap<TypeId, NodeId>,
+    module_paths: HashMap<NodeId, &'a str>,
+    root: NodeId,
+}
+
+impl TypeKeys<'_> {
+    // Paths that resolve to an item are keyed by the item's path, with the keys of their
+    // generic arguments. Generics and paths that do not resolve mean different things in
+    // different modules, so they are keyed by their spelling and module.
+    fn key(&self, id: TypeId, memo: &mut HashMap<TypeId, String>) -> String {
+        if let Some(key) = memo.get(&id) {
+            return key.clone();
+        }
+        let Some(ty) = self.types.get(&id) else {
+            return format!("{:?}", id);
+        };
+
+        // A type that is among its own related types is keyed by its ID where it
+        // refers to itself, instead of recursing forever
+        memo.insert(id, format!("{:?}", id));
+        let mut related = Vec::new();
+        for related_id in &ty.related_types {
+            related.push(self.key(*related_id, memo));
+        }
+        let head = match (&ty.kind, self.targets.get(&id), &ty.resolution) {
+            (TypeKind::Named { .. }, Some(Target::Local(item)), _) => {
+                self.item_paths.get(item).map(|p| p.to_string())
+            }
+            (TypeKind::Named { .. }, Some(Target::External(path)), _) => Some(path.join("::")),
+            (TypeKind::Named { path, .. }, None, Some(Resolution::Primitive)) => {
+                Some(path.join("::"))
+            }
+            (TypeKind::Named { .. }, _, _) => None,
+            (kind, _, _) => Some(format!("{:?}", kind)),
+        };
+        let key = match head {
+            Some(head) => {
+                let qualified = match ty.kind {
+                    TypeKind::Named {
+                        is_fully_qualified: true,
+                        ..
+                    } => "qualified ",
+                    _ => "",
+                };
+                format!("{}{}<{}>", qualified, head, related.join(", "))
+            }
+            None => {
+                let module = self.scopes.get(&id).unwrap_or(&self.root);
+                format!(
+                    "{} in {}",
+                    ty.spellings.first().map_or("", String::as_str),
+                    self.module_paths.get(module).copied().unwrap_or_default()
+                )
+            }
+        };
+        memo.insert(id, key.clone());
+        key
+    }
+}
+
+// Pairs of (associated item in the impl, its declaration in the implemented trait)
+fn assoc_item_links(impl_node: &ImplNode, trait_def: &TraitNode) -> Vec<(NodeId, NodeId)> {
+    let mut links = Vec::new();
+    for assoc_type in &impl_node.assoc_types {
+        if let Some(decl) = trait_def
+            .assoc_types
+            .iter()
+            .find(|t| t.name == assoc_type.name)
+        {
+            links.push((assoc_type.id, decl.id));
+        }
+    }
+    for assoc_const in &impl_node.assoc_consts {
+        if let Some(decl) = trait_def
+            .assoc_consts
+            .iter()
+            .find(|c| c.name == assoc_const.name)
+        {
+            links.push((assoc_const.id, decl.id));
+        }
+    }
+    links
+}
+
+// Name lookup over the modules of a graph
+pub(crate) struct Resolver<'a> {
+    root: NodeId,
+    modules: HashMap<NodeId, &'a ModuleNode>,
+    parents: HashMap<NodeId, NodeId>,
+    // Items in the type namespace (types, traits and modules) by ID
+    type_names: HashMap<NodeId, &'a str>,
+    // Functions of modules by ID, the part of the value namespace calls are resolved in
+    function_names: HashMap<NodeId, &'a str>,
+    // Names of all type parameters declared anywhere in the graph
+    generic_names: HashSet<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(graph: &'a CodeGraph, root: NodeId) -> Self {
+        let modules: HashMap<NodeId, &ModuleNode> =
+            graph.modules.iter().map(|m| (m.id, m)).collect();
+        let parents = graph
+            .modules
+            .iter()
+            .flat_map(|m| m.submodules.iter().map(move |sub| (*sub, m.id)))
+            .collect();
+
+        let mut type_names: HashMap<NodeId, &str> = HashMap::new();
+        for def in &graph.defined_types {
+            let (id, name) = match def {
+                TypeDefNode::Struct(s) => (s.id, &s.name),
+                TypeDefNode::Enum(e) => (e.id, &e.name),
+                TypeDefNode::TypeAlias(ta) => (ta.id, &ta.name),
+                TypeDefNode::Union(u) => (u.id, &u.name),
+                TypeDefNode::ForeignType(ft) => (ft.id, &ft.name),
+            };
+            type_names.insert(id, name);
+        }
+        for t in graph.traits.iter().chain(&graph.private_traits) {
+            type_names.insert(t.id, &t.name);
+        }
+        for t in &graph.trait_aliases {
+            type_names.insert(t.id, &t.name);
+        }
+        for m in &graph.modules {
+            type_names.insert(m.id, &m.name);
+        }
+
+        Self {
+            root,
+            modules,
+            parents,
+            type_names,
+            function_names: graph
+                .functions
+                .iter()
+                .map(|f| (f.id, f.name.as_str()))
+                .collect(),
+            generic_names: generic_names(graph),
+        }
+    }
+
+    // Resolve the path of a type written in `module`, returning the defining item for
+    // local types and the full path for external ones
+    fn resolve_type(&self, module: NodeId, path: &'a [String]) -> (Resolution, Option<Target>) {
+        let Some(first) = path.first() else {
+            return (Resolution::Unresolved, None);
+        };
+        if first == "Self" {
+            return (Resolution::Generic, None);
+        }
+
+        match self.resolve_path(module, path, &mut HashSet::new()) {
+            Some(Target::External(path)) => (
+                Resolution::External {
+                    crate_name: path[0].clone(),
+                },
+                Some(Target::External(path)),
+            ),
+            Some(local) => (Resolution::Local, Some(local)),
+            // Generic parameters, and associated types of them like `T::Item`
+            None if self.generic_names.contains(first.as_str()) => (Resolution::Generic, None),
+            None if path.len() > 1 => (Resolution::Unresolved, None),
+            None if PRIMITIVES.contains(&first.as_str()) => (Resolution::Primitive, None),
+            None => match PRELUDE.iter().find(|(name, _)| name == first) {
+                Some((_, std_path)) => (
+                    Resolution::External {
+                        crate_name: "std".to_string(),
+                    },
+                    Some(Target::External(
+                        std_path.split("::").map(str::to_string).collect(),
+                    )),
+                ),
+                None => (Resolution::Unresolved, None),
+            },
+        }
+    }
+
+    // Resolve the path of a function written in `module`. Its last segment is looked up
+    // in the value namespace, the others like the path of a module.
+    fn resolve_value(&self, module: NodeId, path: &'a [String]) -> Option<Target> {
+        let (name, prefix) = path.split_last()?;
+        if prefix.is_empty() {
+            return self.lookup_value(module, name, &mut HashSet::new());
+        }
+        match self.resolve_path(module, prefix, &mut HashSet::new())? {
+            Target::Local(id) if self.modules.contains_key(&id) => {
+                self.lookup_value(id, name, &mut HashSet::new())
+            }
+            Target::Local(_) => None,
+            Target::External(path) => Some(external_path(path, std::slice::from_ref(name))),
+        }
+    }
+
+    // Like `lookup`, for the functions of `module` and the functions it imports
+    fn lookup_value(
+        &self,
+        module: NodeId,
+        name: &'a str,
+        seen: &mut HashSet<(NodeId, &'a str)>,
+    ) -> Option<Target> {
+        if !seen.insert((module, name)) {
+            return None;
+        }
+        let module_node = self.modules.get(&module)?;
+
+        if let Some(id) = module_node
+            .items
+            .iter()
+            .find(|id| self.function_names.get(id) == Some(&name))
+        {
+            return Some(Target::Local(*id));
+        }
+
+        for import in &module_node.imports {
+            if import.is_glob || import.name() != Some(name) {
+                continue;
+            }
+            match self.resolve_value(module, &import.path) {
+                Some(target) => return Some(target),
+                // The import may name a type or module instead
+                None => continue,
+            }
+        }
+
+        for import in module_node.imports.iter().filter(|i| i.is_glob) {
+            if let Some(glob_module) = self.resolve_module(module, &import.path) {
+                if let Some(target) = self.lookup_value(glob_module, name, seen) {
+                    return Some(target);
+                }
+            }
+        }
+        None
+    }
+
+    // Follow `path` from `module` to a local module
+    pub(crate) fn resolve_module(&self, module: NodeId, path: &'a [String]) -> Option<NodeId> {
+        match self.resolve_path(module, path, &mut HashSet::new())? {
+            Target::Local(id) if self.modules.contains_key(&id) => Some(id),
+            _ => None,
+        }
+    }
+
+    // Follow `path` from `module`. Paths whose first segment is not in scope are taken
+    // to start with the name of another crate, unless it is `Self` or a generic parameter.
+    fn resolve_path(
+        &self,
+        module: NodeId,
+        path: &'a [String],
+        seen: &mut HashSet<(NodeId, &'a str)>,
+    ) -> Option<Target> {
+        let (first, rest) = path.split_first()?;
+        let mut current = match first.as_str() {
+            "crate" => self.root,
+            "self" => module,
+            "super" => *self.parents.get(&module)?,
+            name => match self.lookup(module, name, seen) {
+                Some(Target::Local(id)) => id,
+                Some(Target::External(path)) => return Some(external_path(path, rest)),
+                None if rest.is_empty() || name == "Self" || self.generic_names.contains(name) => {
+                    return None
+                }
+                None => return Some(Target::External(path.to_vec())),
+            },
+        };
+
+        for (i, segment) in rest.iter().enumerate() {
+            current = match segment.as_str() {
+                "super" => *self.parents.get(&current)?,
+                "self" => current,
+                name => {
+                    // Only modules have items that can be named through a path
+                    self.modules.get(&current)?;
+                    match self.lookup(current, name, seen)? {
+                        Target::Local(id) => id,
+                        Target::External(path) => return Some(external_path(path, &rest[i + 1..])),
+                    }
+                }
+            };
+        }
+        Some(Target::Local(current))
+    }
+
+    // Find what `name` refers to inside `module`: an item or submodule defined there,
+    // a named import, or an item brought in by a glob import
+    fn lookup(
+        &self,
+        module: NodeId,
+        name: &'a str,
+        seen: &mut HashSet<(NodeId, &'a str)>,
+    ) -> Option<Target> {
+        // Imports can refer to each other in cycles through globs
+        if !seen.insert((module, name)) {
+            return None;
+        }
+        let module_node = self.modules.get(&module)?;
+
+        if let Some(id) = module_node
+            .items
+            .iter()
+            .chain(&module_node.submodules)
+            .find(|id| self.type_names.get(id) == Some(&name))
+        {
+            return Some(Target::Local(*id));
+        }
+
+        for import in &module_node.imports {
+            if import.name() != Some(name) {
+                continue;
+            }
+            // `use other_crate;` and `extern crate other_crate;` name a crate
+            return self.resolve_path(module, &import.path, seen).or_else(|| {
+                match import.path.first().map(String::as_str) {
+                    Some("crate" | "self" | "super") | None => None,
+                    Some(_) => Some(Target::External(import.path.clone())),
+                }
+            });
+        }
+
+        for import in module_node.imports.iter().filter(|i| i.is_glob) {
+            if let Some(Target::Local(glob_module)) = self.resolve_path(module, &import.path, seen)
+            {
+                if let Some(target) = self.lookup(glob_module, name, seen) {
+                    return Some(target);
+                }
+            }
+        }
+        None
+    }
+}
+
+// Path of an item named through an external `prefix`, e.g. `fmt::Display` after
+// `use std::fmt;`
+fn external_path(mut prefix: Vec<String>, rest: &[String]) -> Target {
+    prefix.extend(rest.iter().cloned());
+    Target::External(prefix)
+}
+
+// Names of the type parameters of every item, impl and method in the graph
+fn generic_names(graph: &CodeGraph) -> HashSet<&str> {
+    fn type_params(params: &[GenericParamNode]) -> impl Iterator<Item = &str> {
+        params.iter().filter_map(|param| match &param.kind {
+            GenericParamKind::Type { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+    }
+    fn function_params(functions: &[FunctionNode]) -> impl Iterator<Item = &str> {
+        functions
+            .iter()
+            .flat_map(|f| type_params(&f.generic_params))
+    }
+
+    let mut names = HashSet::new();
+    names.extend(function_params(&graph.functions));
+    for def in &graph.defined_types {
+        names.extend(type_params(match def {
+            TypeDefNode::Struct(s) => &s.generic_params,
+            TypeDefNode::Enum(e) => &e.generic_params,
+            TypeDefNode::TypeAlias(ta) => &ta.generic_params,
+            TypeDefNode::Union(u) => &u.generic_params,
+            TypeDefNode::ForeignType(_) => continue,
+        }));
+    }
+    for imp in &graph.impls {
+        names.extend(type_params(&imp.generic_params));
+        names.extend(function_params(&imp.methods));
+    }
+    for t in graph.traits.iter().chain(&graph.private_traits) {
+        names.extend(type_params(&t.generic_params));
+        names.extend(function_params(&t.methods));
+        for assoc in &t.assoc_types {
+            names.extend(type_params(&assoc.generic_params));
+        }
+    }
+    for t in &graph.trait_aliases {
+        names.extend(type_params(&t.generic_params));
+    }
+    names
+}
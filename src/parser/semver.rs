@@ -0,0 +1,563 @@
+use crate::parser::api::{public_api, ApiItem};
+use crate::parser::graph::{CodeGraph, NodeRef};
+use crate::parser::nodes::{
+    Attribute, FieldNode, FunctionNode, NodeId, TraitNode, TypeDefNode, ValueKind,
+};
+use crate::parser::types::{GenericParamKind, GenericParamNode, TypeId, TypeNode, VisibilityKind};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// ANCHOR: SemverLevel
+// Version bump a change requires under Cargo's semver rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SemverLevel {
+    Patch,
+    Minor,
+    Major,
+}
+//ANCHOR_END: SemverLevel
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+// A change to one public item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiChange {
+    // Public path of the item, in the new graph for added items and the old one otherwise
+    pub path: String,
+    pub kind: ChangeKind,
+    pub level: SemverLevel,
+    // What changed, e.g. "parameter `addr` changed type from `String` to `& str`"
+    pub description: String,
+}
+
+// Changes between the public APIs of two versions of a crate, ordered by path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiDiff {
+    pub changes: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    /// The version bump the whole diff requires: the highest level among its changes,
+    /// or `Patch` when nothing changed.
+    pub fn level(&self) -> SemverLevel {
+        self.changes
+            .iter()
+            .map(|change| change.level)
+            .max()
+            .unwrap_or(SemverLevel::Patch)
+    }
+
+    /// Changes that require at least the given version bump.
+    pub fn at_least(&self, level: SemverLevel) -> impl Iterator<Item = &ApiChange> {
+        self.changes
+            .iter()
+            .filter(move |change| change.level >= level)
+    }
+}
+
+/// Compare the public APIs of two versions of the same crate, classifying every
+/// added, removed or changed public item as a major, minor or patch change.
+///
+/// Items are matched by their public paths (see [`public_api`]), so moving an item
+/// while re-exporting it at its old path is not a change. Types are compared by
+/// their IDs, which are derived from what they resolve to and are the same in both
+/// graphs for the same type.
+///
+/// Whether a trait method has a default is read from its body, so both graphs need
+/// to be parsed with `include_bodies` for new trait methods to be classified.
+pub fn api_diff(old: &CodeGraph, new: &CodeGraph) -> ApiDiff {
+    let old_api = public_api(old);
+    let new_api = public_api(new);
+    let old_items = by_public_path(&old_api.items);
+    let new_items = by_public_path(&new_api.items);
+
+    let mut comparison = Comparison {
+        old_types: old.type_graph.iter().map(|t| (t.id, t)).collect(),
+        new_types: new.type_graph.iter().map(|t| (t.id, t)).collect(),
+        changes: Vec::new(),
+        path: String::new(),
+    };
+
+    for (path, old_item) in &old_items {
+        comparison.path = path.to_string();
+        match new_items.get(path) {
+            None => comparison.push(
+                ChangeKind::Removed,
+                SemverLevel::Major,
+                format!("removed {}", kind_name(&old_item.node)),
+            ),
+            // An item reachable through several paths is compared once
+            Some(new_item) if old_item.public_paths.first().map(String::as_str) == Some(path) => {
+                comparison.compare(old_item.node, new_item.node)
+            }
+            Some(_) => {}
+        }
+    }
+
+    let required_methods = required_trait_methods(new);
+    for (path, new_item) in &new_items {
+        if old_items.contains_key(path) {
+            continue;
+        }
+        comparison.path = path.to_string();
+        // New required methods break implementors of traits that already existed
+        let existing_trait = required_methods.get(&new_item.node.id()).and_then(|t| {
+            new_api.get(t.id).map(|trait_item| {
+                trait_item
+                    .public_paths
+                    .iter()
+                    .any(|p| old_items.contains_key(p.as_str()))
+            })
+        });
+        if existing_trait == Some(true) {
+            comparison.push(
+                ChangeKind::Added,
+                SemverLevel::Major,
+                "added required trait method".to_string(),
+            );
+        } else {
+            comparison.push(
+                ChangeKind::Added,
+                SemverLevel::Minor,
+                format!("added {}", kind_name(&new_item.node)),
+            );
+        }
+    }
+
+    let mut changes = comparison.changes;
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    ApiDiff { changes }
+}
+
+fn by_public_path<'a, 'g>(items: &'a [ApiItem<'g>]) -> BTreeMap<&'a str, &'a ApiItem<'g>> {
+    items
+        .iter()
+        .flat_map(|item| {
+            item.public_paths
+                .iter()
+                .map(move |path| (path.as_str(), item))
+        })
+        .collect()
+}
+
+// Trait methods without a default body, by ID
+fn required_trait_methods(graph: &CodeGraph) -> HashMap<NodeId, &TraitNode> {
+    graph
+        .traits
+        .iter()
+        .chain(&graph.private_traits)
+        .flat_map(|t| {
+            t.methods
+                .iter()
+                .filter(|m| m.body.is_none())
+                .map(move |m| (m.id, t))
+        })
+        .collect()
+}
+
+fn kind_name(node: &NodeRef) -> &'static str {
+    match node {
+        NodeRef::Function(_) => "function",
+        NodeRef::TypeDef(TypeDefNode::Struct(_)) => "struct",
+        NodeRef::TypeDef(TypeDefNode::Enum(_)) => "enum",
+        NodeRef::TypeDef(TypeDefNode::TypeAlias(_)) => "type alias",
+        NodeRef::TypeDef(TypeDefNode::Union(_)) => "union",
+        NodeRef::TypeDef(TypeDefNode::ForeignType(_)) => "foreign type",
+        NodeRef::Trait(_) => "trait",
+        NodeRef::TraitAlias(_) => "trait alias",
+        NodeRef::Value(v) if v.kind == ValueKind::Constant => "constant",
+        NodeRef::Value(_) => "static",
+        NodeRef::Macro(_) => "macro",
+        NodeRef::Module(_) => "module",
+    }
+}
+
+fn is_non_exhaustive(attributes: &[Attribute]) -> bool {
+    attributes.iter().any(|attr| attr.name == "non_exhaustive")
+}
+
+// Name of a field for descriptions, its index for tuple fields
+fn field_name(field: &FieldNode, index: usize) -> String {
+    field.name.clone().unwrap_or_else(|| index.to_string())
+}
+
+// Compares matching items of the two graphs, collecting the changes of the item at `path`
+struct Comparison<'a> {
+    old_types: HashMap<TypeId, &'a TypeNode>,
+    new_types: HashMap<TypeId, &'a TypeNode>,
+    changes: Vec<ApiChange>,
+    path: String,
+}
+
+impl Comparison<'_> {
+    fn push(&mut self, kind: ChangeKind, level: SemverLevel, description: String) {
+        self.changes.push(ApiChange {
+            path: self.path.clone(),
+            kind,
+            level,
+            description,
+        });
+    }
+
+    fn changed(&mut self, level: SemverLevel, description: String) {
+        self.push(ChangeKind::Changed, level, description);
+    }
+
+    // How a type is written, in the graph it comes from
+    fn spelling(types: &HashMap<TypeId, &TypeNode>, id: TypeId) -> String {
+        types
+            .get(&id)
+            .and_then(|t| t.spellings.first())
+            .cloned()
+            .unwrap_or_else(|| "_".to_string())
+    }
+
+    fn type_change(&mut self, what: String, old: TypeId, new: TypeId) {
+        if old != new {
+            let description = format!(
+                "{} changed type from `{}` to `{}`",
+                what,
+                Self::spelling(&self.old_types, old),
+                Self::spelling(&self.new_types, new)
+            );
+            self.changed(SemverLevel::Major, description);
+        }
+    }
+
+    fn compare(&mut self, old: NodeRef, new: NodeRef) {
+        if kind_name(&old) != kind_name(&new) {
+            let description = format!("changed from {} to {}", kind_name(&old), kind_name(&new));
+            self.changed(SemverLevel::Major, description);
+            return;
+        }
+
+        match (old, new) {
+            (NodeRef::Function(old_fn), NodeRef::Function(new_fn)) => {
+                self.compare_functions(old_fn, new_fn)
+            }
+            (NodeRef::TypeDef(old_def), NodeRef::TypeDef(new_def)) => {
+                self.compare_type_defs(old_def, new_def)
+            }
+            (NodeRef::Trait(old_trait), NodeRef::Trait(new_trait)) => {
+                self.compare_traits(old_trait, new_trait)
+            }
+            (NodeRef::TraitAlias(old_alias), NodeRef::TraitAlias(new_alias)) => {
+                if old_alias.bounds != new_alias.bounds {
+                    self.changed(SemverLevel::Major, "changed bounds".to_string());
+                }
+                self.compare_generics(&old_alias.generic_params, &new_alias.generic_params);
+            }
+            (NodeRef::Value(old_value), NodeRef::Value(new_value)) => {
+                self.type_change("value".to_string(), old_value.type_id, new_value.type_id);
+                if old_value.value != new_value.value {
+                    self.changed(SemverLevel::Patch, "value changed".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn compare_functions(&mut self, old: &FunctionNode, new: &FunctionNode) {
+        if old.parameters.len() != new.parameters.len() {
+            let description = format!(
+                "number of parameters changed from {} to {}",
+                old.parameters.len(),
+                new.parameters.len()
+            );
+            self.changed(SemverLevel::Major, description);
+        } else {
+            for (i, (old_param, new_param)) in
+                old.parameters.iter().zip(&new.parameters).enumerate()
+            {
+                let name = match &new_param.name {
+                    Some(name) => format!("parameter `{}`", name),
+                    None => format!("parameter {}", i),
+                };
+                self.type_change(name, old_param.type_id, new_param.type_id);
+            }
+        }
+
+        match (old.return_type, new.return_type) {
+            (Some(old_ty), Some(new_ty)) => {
+                self.type_change("return value".to_string(), old_ty, new_ty)
+            }
+            (None, None) => {}
+            _ => self.changed(SemverLevel::Major, "return type changed".to_string()),
+        }
+
+        self.compare_generics(&old.generic_params, &new.generic_params);
+
+        if old.body.is_some() && new.body.is_some() && old.body != new.body {
+            self.changed(SemverLevel::Patch, "body changed".to_string());
+        }
+    }
+
+    fn compare_type_defs(&mut self, old: &TypeDefNode, new: &TypeDefNode) {
+        match (old, new) {
+            (TypeDefNode::Struct(old_s), TypeDefNode::Struct(new_s)) => {
+                let non_exhaustive = is_non_exhaustive(&new_s.attributes);
+                self.compare_fields("field", &old_s.fields, &new_s.fields, non_exhaustive);
+                self.compare_generics(&old_s.generic_params, &new_s.generic_params);
+            }
+            (TypeDefNode::Union(old_u), TypeDefNode::Union(new_u)) => {
+                self.compare_fields("field", &old_u.fields, &new_u.fields, true);
+                self.compare_generics(&old_u.generic_params, &new_u.generic_params);
+            }
+            (TypeDefNode::Enum(old_e), TypeDefNode::Enum(new_e)) => {
+                let non_exhaustive = is_non_exhaustive(&new_e.attributes);
+                for old_variant in &old_e.variants {
+                    match new_e.variants.iter().find(|v| v.name == old_variant.name) {
+                        None => self.push(
+                            ChangeKind::Removed,
+                            SemverLevel::Major,
+                            format!("removed variant `{}`", old_variant.name),
+                        ),
+                        Some(new_variant) => {
+                            let what = format!("field of variant `{}`", old_variant.name);
+                            let variant_non_exhaustive = is_non_exhaustive(&new_variant.attributes);
+                            self.compare_fields(
+                                &what,
+                                &old_variant.fields,
+                                &new_variant.fields,
+                                variant_non_exhaustive,
+                            );
+                        }
+                    }
+                }
+                for new_variant in &new_e.variants {
+                    if old_e.variants.iter().any(|v| v.name == new_variant.name) {
+                        continue;
+                    }
+                    // Matches on an exhaustive enum must cover every variant
+                    let level = if non_exhaustive {
+                        SemverLevel::Minor
+                    } else {
+                        SemverLevel::Major
+                    };
+                    self.push(
+                        ChangeKind::Added,
+                        level,
+                        format!("added variant `{}`", new_variant.name),
+                    );
+                }
+                self.compare_generics(&old_e.generic_params, &new_e.generic_params);
+            }
+            (TypeDefNode::TypeAlias(old_ta), TypeDefNode::TypeAlias(new_ta)) => {
+                self.type_change("aliased type".to_string(), old_ta.type_id, new_ta.type_id);
+                self.compare_generics(&old_ta.generic_params, &new_ta.generic_params);
+            }
+            _ => {}
+        }
+    }
+
+    // Compare the fields of a struct, union or variant. Public fields are matched by
+    // name, or by position for tuple fields.
+    fn compare_fields(
+        &mut self,
+        what: &str,
+        old: &[FieldNode],
+        new: &[FieldNode],
+        non_exhaustive: bool,
+    ) {
+        let is_public = |f: &FieldNode| f.visibility == VisibilityKind::Public;
+        let key = |f: &FieldNode, i: usize| field_name(f, i);
+        let old_public: Vec<(String, &FieldNode)> = old
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| is_public(f))
+            .map(|(i, f)| (key(f, i), f))
+            .collect();
+        let new_public: Vec<(String, &FieldNode)> = new
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| is_public(f))
+            .map(|(i, f)| (key(f, i), f))
+            .collect();
+
+        for (name, old_field) in &old_public {
+            match new_public.iter().find(|(n, _)| n == name) {
+                None => self.push(
+                    ChangeKind::Removed,
+                    SemverLevel::Major,
+                    format!("removed public {} `{}`", what, name),
+                ),
+                Some((_, new_field)) => self.type_change(
+                    format!("{} `{}`", what, name),
+                    old_field.type_id,
+                    new_field.type_id,
+                ),
+            }
+        }
+
+        // Values with only public fields can be built with a literal and destructured
+        // exhaustively, which any new field breaks
+        let was_constructible = !non_exhaustive && old.iter().all(is_public);
+        for (name, _) in &new_public {
+            if !old_public.iter().any(|(n, _)| n == name) {
+                let level = if was_constructible {
+                    SemverLevel::Major
+                } else {
+                    SemverLevel::Minor
+                };
+                self.push(
+                    ChangeKind::Added,
+                    level,
+                    format!("added public {} `{}`", what, name),
+                );
+            }
+        }
+        if was_constructible && new.iter().any(|f| !is_public(f)) {
+            self.changed(
+                SemverLevel::Major,
+                format!("added private {}, which prevents construction", what),
+            );
+        }
+    }
+
+    fn compare_traits(&mut self, old: &TraitNode, new: &TraitNode) {
+        // Supertraits add requirements for implementors and guarantees for users, so
+        // adding and removing them are both breaking
+        let mut old_supers: Vec<TypeId> = old.super_traits.clone();
+        let mut new_supers: Vec<TypeId> = new.super_traits.clone();
+        old_supers.sort();
+        new_supers.sort();
+        if old_supers != new_supers {
+            self.changed(SemverLevel::Major, "changed supertraits".to_string());
+        }
+
+        for old_assoc in &old.assoc_types {
+            if !new.assoc_types.iter().any(|t| t.name == old_assoc.name) {
+                self.push(
+                    ChangeKind::Removed,
+                    SemverLevel::Major,
+                    format!("removed associated type `{}`", old_assoc.name),
+                );
+            }
+        }
+        for new_assoc in &new.assoc_types {
+            if old.assoc_types.iter().any(|t| t.name == new_assoc.name) {
+                continue;
+            }
+            let level = match new_assoc.type_id {
+                Some(_) => SemverLevel::Minor,
+                None => SemverLevel::Major,
+            };
+            self.push(
+                ChangeKind::Added,
+                level,
+                format!("added associated type `{}`", new_assoc.name),
+            );
+        }
+
+        // A method that lost its default must now be written by every implementor
+        for old_method in &old.methods {
+            let Some(new_method) = new.methods.iter().find(|m| m.name == old_method.name) else {
+                continue;
+            };
+            if old_method.body.is_some() && new_method.body.is_none() {
+                self.changed(
+                    SemverLevel::Major,
+                    format!("removed the default of method `{}`", old_method.name),
+                );
+            }
+        }
+
+        self.compare_generics(&old.generic_params, &new.generic_params);
+    }
+
+    // Compare type and const parameters by name. Adding bounds (or parameters without
+    // defaults) breaks users, removing bounds only breaks implementors of traits.
+    fn compare_generics(&mut self, old: &[GenericParamNode], new: &[GenericParamNode]) {
+        fn name(param: &GenericParamNode) -> Option<&str> {
+            match &param.kind {
+                GenericParamKind::Type { name, .. } | GenericParamKind::Const { name, .. } => {
+                    Some(name)
+                }
+                GenericParamKind::Lifetime { .. } => None,
+            }
+        }
+        fn find<'p>(params: &'p [GenericParamNode], wanted: &str) -> Option<&'p GenericParamKind> {
+            params
+                .iter()
+                .find(|p| name(p) == Some(wanted))
+                .map(|p| &p.kind)
+        }
+
+        for old_param in old {
+            let Some(param_name) = name(old_param) else {
+                continue;
+            };
+            match (&old_param.kind, find(new, param_name)) {
+                (_, None) => self.push(
+                    ChangeKind::Removed,
+                    SemverLevel::Major,
+                    format!("removed generic parameter `{}`", param_name),
+                ),
+                (
+                    GenericParamKind::Type {
+                        bounds: old_bounds, ..
+                    },
+                    Some(GenericParamKind::Type {
+                        bounds: new_bounds, ..
+                    }),
+                ) => {
+                    let added = new_bounds.iter().any(|b| !old_bounds.contains(b));
+                    let removed = old_bounds.iter().any(|b| !new_bounds.contains(b));
+                    if added {
+                        self.changed(
+                            SemverLevel::Major,
+                            format!("tightened bounds on `{}`", param_name),
+                        );
+                    } else if removed {
+                        self.changed(
+                            SemverLevel::Minor,
+                            format!("loosened bounds on `{}`", param_name),
+                        );
+                    }
+                }
+                (
+                    GenericParamKind::Const {
+                        type_id: old_ty, ..
+                    },
+                    Some(GenericParamKind::Const {
+                        type_id: new_ty, ..
+                    }),
+                ) => self.type_change(
+                    format!("const parameter `{}`", param_name),
+                    *old_ty,
+                    *new_ty,
+                ),
+                _ => self.changed(
+                    SemverLevel::Major,
+                    format!("changed the kind of generic parameter `{}`", param_name),
+                ),
+            }
+        }
+
+        for new_param in new {
+            let Some(param_name) = name(new_param) else {
+                continue;
+            };
+            if find(old, param_name).is_some() {
+                continue;
+            }
+            let level = match &new_param.kind {
+                GenericParamKind::Type {
+                    default: Some(_), ..
+                } => SemverLevel::Minor,
+                _ => SemverLevel::Major,
+            };
+            self.push(
+                ChangeKind::Added,
+                level,
+                format!("added generic parameter `{}`", param_name),
+            );
+        }
+    }
+}
@@ -2,19 +2,40 @@ use crate::parser::nodes::NodeId;
 
 use serde::{Deserialize, Serialize};
 
-pub type TypeId = usize;
+// Types share the ID space of nodes, so relations can point at either. The ID of a type
+// is derived from how it is written.
+pub type TypeId = NodeId;
 
 // ANCHOR: TypeNode
 // Represents a type reference with full metadata
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeNode {
     pub id: TypeId,
     pub kind: TypeKind,
     // Reference to related types (e.g., generic arguments)
     pub related_types: Vec<TypeId>,
+    // Every way the type is written, e.g. both `Vec<u8>` and `std::vec::Vec<u8>`
+    pub spellings: Vec<String>,
+    // What the path of a `Named` type refers to, `None` for other kinds of types
+    pub resolution: Option<Resolution>,
 }
 //ANCHOR_END: TypeNode
 
+// What the path of a `Named` type refers to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    // Defined in the analyzed code, linked to its definition by a `ResolvesTo` relation
+    Local,
+    // Defined in another crate, e.g. `std` or a dependency
+    External { crate_name: String },
+    // A built-in type such as `u8` or `str`
+    Primitive,
+    // A generic parameter or `Self`, or an associated type of one like `T::Item`
+    Generic,
+    // Nothing in scope has this name
+    Unresolved,
+}
+
 // ANCHOR: TypeKind_defn
 // Different kinds of types
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -80,7 +101,7 @@ pub enum TypeKind {
 }
 
 // Represents a generic parameter
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenericParamNode {
     pub id: NodeId,
     pub kind: GenericParamKind,
@@ -88,7 +109,7 @@ pub struct GenericParamNode {
 
 // ANCHOR: generic_param_kind
 // Different kinds of generic parameters
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum GenericParamKind {
     Type {
         name: String,
@@ -107,7 +128,7 @@ pub enum GenericParamKind {
 //ANCHOR_END: generic_param_kind
 
 // Different kinds of visibility
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum VisibilityKind {
     Public,
     Crate,
@@ -0,0 +1,292 @@
+use crate::error::{Result, SynParserError};
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use syn::parse::Parser;
+
+// Directories used to locate the file backing an out-of-line module (`mod foo;`)
+//
+// Rust looks for the children of a module in different places depending on whether the
+// module's file "owns" its directory (crate roots, `mod.rs` files and files loaded through
+// `#[path]`) or not (`foo.rs`, whose children live in `foo/`), and resolves `#[path]`
+// attributes relative to yet another directory when inside inline `mod bar { .. }` blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ModuleDirs {
+    // Directory searched for `name.rs` and `name/mod.rs`
+    children: PathBuf,
+    // Directory that `#[path = "..."]` values are relative to
+    path_attr: PathBuf,
+}
+
+impl ModuleDirs {
+    // Directories for the items of `file`.
+    // `owns_directory` is true for crate roots, `mod.rs` files and `#[path]` files.
+    pub(crate) fn for_file(file: &Path, owns_directory: bool) -> Self {
+        let parent = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let children = if owns_directory {
+            parent.clone()
+        } else {
+            match file.file_stem() {
+                Some(stem) => parent.join(stem),
+                None => parent.clone(),
+            }
+        };
+
+        Self {
+            children,
+            path_attr: parent,
+        }
+    }
+
+    // Directories for the items of an inline module `mod name { .. }`
+    pub(crate) fn inline_child(&self, name: &str, path_attr: Option<&str>) -> Self {
+        let dir = match path_attr {
+            Some(path) => self.path_attr.join(path),
+            None => self.children.join(name),
+        };
+
+        Self {
+            children: dir.clone(),
+            path_attr: dir,
+        }
+    }
+
+    // Locate the file backing `mod name;` and the directories for the items inside it
+    pub(crate) fn resolve(&self, name: &str, path_attr: Option<&str>) -> Option<(PathBuf, Self)> {
+        if let Some(path) = path_attr {
+            let file = self.path_attr.join(path);
+            return file.is_file().then(|| {
+                let dirs = Self::for_file(&file, true);
+                (file, dirs)
+            });
+        }
+
+        let flat = self.children.join(format!("{}.rs", name));
+        if flat.is_file() {
+            let dirs = Self::for_file(&flat, false);
+            return Some((flat, dirs));
+        }
+
+        let nested = self.children.join(name).join("mod.rs");
+        if nested.is_file() {
+            let dirs = Self::for_file(&nested, true);
+            return Some((nested, dirs));
+        }
+
+        None
+    }
+}
+
+// Find the root file of a crate: `root` itself if it is a file, otherwise
+// `src/lib.rs` or `src/main.rs` inside the crate directory.
+pub(crate) fn find_crate_root(root: &Path) -> Option<PathBuf> {
+    if root.is_file() {
+        return Some(root.to_path_buf());
+    }
+
+    ["src/lib.rs", "src/main.rs"]
+        .iter()
+        .map(|candidate| root.join(candidate))
+        .find(|candidate| candidate.is_file())
+}
+
+// Value of a `#[path = "..."]` attribute, if present
+pub(crate) fn path_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("path"))
+        .find_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }),
+                ..
+            }) => Some(lit_str.value()),
+            _ => None,
+        })
+}
+
+// Attributes of an item, empty for items that cannot have any
+pub(crate) fn item_attributes(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Enum(item) => &item.attrs,
+        syn::Item::ExternCrate(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::ForeignMod(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Macro(item) => &item.attrs,
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        syn::Item::Trait(item) => &item.attrs,
+        syn::Item::TraitAlias(item) => &item.attrs,
+        syn::Item::Type(item) => &item.attrs,
+        syn::Item::Union(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+// Attributes of an item inside an `extern` block
+pub(crate) fn foreign_item_attributes(item: &syn::ForeignItem) -> &[syn::Attribute] {
+    match item {
+        syn::ForeignItem::Fn(item) => &item.attrs,
+        syn::ForeignItem::Static(item) => &item.attrs,
+        syn::ForeignItem::Type(item) => &item.attrs,
+        syn::ForeignItem::Macro(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+// Whether attributes mark an item as test-only: `#[test]`, `#[cfg(test)]` or
+// `#[cfg(all(test, ..))]`
+pub(crate) fn is_test_only(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path().is_ident("test") {
+            return true;
+        }
+        if !attr.path().is_ident("cfg") {
+            return false;
+        }
+        match attr.parse_args::<syn::Meta>() {
+            Ok(syn::Meta::Path(path)) => path.is_ident("test"),
+            Ok(syn::Meta::List(list)) if list.path.is_ident("all") => list
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                )
+                .map(|predicates| predicates.iter().any(|p| p.path().is_ident("test")))
+                .unwrap_or(false),
+            _ => false,
+        }
+    })
+}
+
+// Read a Rust source file
+pub(crate) fn read_source(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|err| SynParserError::io(path, err))
+}
+
+// Parse the contents of a Rust source file
+pub(crate) fn parse_source(path: &Path, source: &str) -> Result<syn::File> {
+    syn::parse_file(source).map_err(|err| SynParserError::parse(path, &err))
+}
+
+// Parse `source` one item at a time, keeping every item that parses and an error for
+// every chunk of tokens that does not. Fails only when the source cannot be tokenized,
+// e.g. because of unbalanced delimiters.
+//
+// Items are split at top-level `;` and `{ .. }` tokens. A brace that closes a block
+// inside an expression (`const X: i32 = { 1 } + 1;`) does not end the item, so a chunk
+// that fails to parse after a brace keeps growing unless the next token starts a new item.
+pub(crate) fn parse_items_tolerant(
+    source: &str,
+) -> std::result::Result<(syn::File, Vec<syn::Error>), syn::Error> {
+    let tokens: TokenStream = source
+        .parse()
+        .map_err(|err: proc_macro2::LexError| syn::Error::new(err.span(), err))?;
+
+    let mut attrs = Vec::new();
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut chunk: Vec<TokenTree> = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let is_inner_attr = matches!(&token, TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket)
+            && is_inner_attr_prefix(&chunk);
+        let closes_brace = matches!(&token, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace)
+            && !matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ';');
+        let ends_chunk =
+            matches!(&token, TokenTree::Punct(p) if p.as_char() == ';') || closes_brace;
+        chunk.push(token);
+
+        if is_inner_attr {
+            let stream: TokenStream = chunk.drain(..).collect();
+            match syn::Attribute::parse_inner.parse2(stream) {
+                Ok(inner) => attrs.extend(inner),
+                Err(err) => errors.push(err),
+            }
+            continue;
+        }
+        if !ends_chunk {
+            continue;
+        }
+
+        let stream: TokenStream = chunk.iter().cloned().collect();
+        match syn::parse2::<syn::Item>(stream) {
+            Ok(item) => {
+                items.push(item);
+                chunk.clear();
+            }
+            Err(_) if closes_brace && !starts_item(tokens.peek()) => {}
+            Err(err) => {
+                errors.push(locate_error(err, &chunk));
+                chunk.clear();
+            }
+        }
+    }
+
+    if !chunk.is_empty() {
+        let stream: TokenStream = chunk.iter().cloned().collect();
+        match syn::parse2::<syn::Item>(stream) {
+            Ok(item) => items.push(item),
+            Err(err) => errors.push(locate_error(err, &chunk)),
+        }
+    }
+
+    let file = syn::File {
+        shebang: None,
+        attrs,
+        items,
+    };
+    Ok((file, errors))
+}
+
+// Whether `chunk` is the `#!` that starts an inner attribute
+fn is_inner_attr_prefix(chunk: &[TokenTree]) -> bool {
+    matches!(
+        chunk,
+        [TokenTree::Punct(hash), TokenTree::Punct(bang)]
+            if hash.as_char() == '#' && bang.as_char() == '!'
+    )
+}
+
+// Whether `token` can begin a new item
+fn starts_item(token: Option<&TokenTree>) -> bool {
+    const ITEM_KEYWORDS: &[&str] = &[
+        "pub",
+        "fn",
+        "struct",
+        "enum",
+        "union",
+        "trait",
+        "impl",
+        "mod",
+        "use",
+        "const",
+        "static",
+        "type",
+        "extern",
+        "unsafe",
+        "async",
+        "macro_rules",
+    ];
+    match token {
+        None => true,
+        Some(TokenTree::Punct(p)) => p.as_char() == '#',
+        Some(TokenTree::Ident(ident)) => ITEM_KEYWORDS.iter().any(|kw| ident == kw),
+        _ => false,
+    }
+}
+
+// Errors at the end of a chunk carry no position, point them at the chunk instead
+fn locate_error(err: syn::Error, chunk: &[TokenTree]) -> syn::Error {
+    match chunk.first() {
+        Some(first) if err.span().start().line == 0 => syn::Error::new(first.span(), err),
+        _ => err,
+    }
+}
@@ -3,10 +3,25 @@ use crate::parser::nodes::*;
 use crate::parser::relations::*;
 use crate::parser::types::*;
 
+use crate::config::ParseOptions;
+use crate::error::{Result, SynParserError};
+use crate::parser::comments::{attach, classify, file_node_spans, scan_comments, span_of};
+use crate::parser::resolve::resolve_names;
+use crate::parser::utils::{
+    find_crate_root, foreign_item_attributes, is_test_only, item_attributes, parse_items_tolerant,
+    parse_source, path_attribute, read_source, ModuleDirs,
+};
+
 use quote::ToTokens;
-use std::collections::HashMap;
-use std::path::Path;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread::ThreadId;
 use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::ItemMod;
 use syn::{
     visit::{self, Visit},
@@ -15,15 +30,305 @@ use syn::{
     TypePath, TypeReference, Visibility,
 };
 
-pub fn analyze_code(file_path: &Path) -> Result<CodeGraph, syn::Error> {
-    let file = syn::parse_file(&std::fs::read_to_string(file_path).unwrap())?;
-    let mut visitor_state = VisitorState::new();
+pub fn analyze_code(file_path: &Path) -> Result<CodeGraph> {
+    analyze_code_with_options(file_path, &ParseOptions::default())
+}
+
+/// Like `analyze_code`, but items that fail to parse are recorded as `Diagnostic`s on
+/// the root module instead of failing the whole analysis.
+pub fn analyze_code_tolerant(file_path: &Path) -> Result<CodeGraph> {
+    let options = ParseOptions {
+        error_tolerant: true,
+        ..ParseOptions::default()
+    };
+    analyze_code_with_options(file_path, &options)
+}
+
+/// Analyze a single file with the given options.
+pub fn analyze_code_with_options(file_path: &Path, options: &ParseOptions) -> Result<CodeGraph> {
+    // Out-of-line modules (`mod foo;`) are recorded but not followed when analyzing a
+    // single file, see `analyze_crate`.
+    analyze_root(file_path, "root", None, None, options)
+}
+
+/// Analyze a whole crate, following `mod foo;` declarations (including `#[path]`
+/// attributes) into the files that back them.
+///
+/// `root` is either the crate directory, in which case analysis starts at `src/lib.rs`
+/// or `src/main.rs`, or the crate root file itself.
+pub fn analyze_crate(root: &Path) -> Result<CodeGraph> {
+    analyze_crate_with_options(root, &ParseOptions::default())
+}
+
+/// Like `analyze_crate`, but produces a partial graph when parts of the crate are broken.
+///
+/// Items that fail to parse, and module files that are missing or cannot be tokenized,
+/// are recorded as `Diagnostic`s on the `ModuleNode` they belong to while the rest of
+/// the crate is still analyzed. Only an unreadable crate root is an error.
+pub fn analyze_crate_tolerant(root: &Path) -> Result<CodeGraph> {
+    let options = ParseOptions {
+        error_tolerant: true,
+        ..ParseOptions::default()
+    };
+    analyze_crate_with_options(root, &options)
+}
+
+/// Analyze a whole crate with the given options.
+pub fn analyze_crate_with_options(root: &Path, options: &ParseOptions) -> Result<CodeGraph> {
+    let root_file = crate_root_file(root)?;
+    let module_dirs = ModuleDirs::for_file(&root_file, true);
+    analyze_root(&root_file, "crate", None, Some(module_dirs), options)
+}
+
+// Analyze a member crate of a workspace. Its package name seeds the namespaces of its
+// node IDs, which are otherwise the same for items at the same path in every crate.
+pub(crate) fn analyze_member_with_options(
+    root: &Path,
+    package: &str,
+    options: &ParseOptions,
+) -> Result<CodeGraph> {
+    let root_file = crate_root_file(root)?;
+    let module_dirs = ModuleDirs::for_file(&root_file, true);
+    analyze_root(
+        &root_file,
+        "crate",
+        Some(package),
+        Some(module_dirs),
+        options,
+    )
+}
+
+// The root file of the crate at `root`, see `analyze_crate`
+pub(crate) fn crate_root_file(root: &Path) -> Result<PathBuf> {
+    find_crate_root(root).ok_or_else(|| {
+        SynParserError::resolution(root, "no crate root (src/lib.rs or src/main.rs) found")
+    })
+}
+
+// Build the graph for a root file, following out-of-line modules when `module_dirs`
+// is given
+fn analyze_root(
+    file_path: &Path,
+    root_name: &str,
+    package: Option<&str>,
+    module_dirs: Option<ModuleDirs>,
+    options: &ParseOptions,
+) -> Result<CodeGraph> {
+    analyze_files(
+        file_path,
+        root_name,
+        package,
+        module_dirs,
+        options,
+        &|file_module, source| visit_file(file_module, source, options),
+    )
+}
+
+// ANCHOR: FileModule
+// A file backing a module, and where that module is in the crate. Visiting the same
+// contents as the same `FileModule` always gives the same `FileFragment`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileModule {
+    pub(crate) file: PathBuf,
+    pub(crate) module_id: NodeId,
+    // Canonical path of the module, e.g. `["crate", "net"]`
+    pub(crate) module_path: Vec<String>,
+    // Package of the crate when it is analyzed as part of a workspace, see `namespace`
+    pub(crate) package: Option<String>,
+    // Where to look for the files of out-of-line modules, `None` when they are not followed
+    pub(crate) module_dirs: Option<ModuleDirs>,
+}
+//ANCHOR_END: FileModule
+
+// The nodes produced by visiting one file, to be merged with the fragments of the other
+// files of the crate before names are resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FileFragment {
+    // `modules[0]` holds what the file adds to the module it backs: its items,
+    // submodules, imports, inner attributes and diagnostics
+    graph: CodeGraph,
+    type_scopes: HashMap<TypeId, NodeId>,
+    // Node IDs given out while visiting, apart from the ID of the module the file backs
+    ids: Vec<NodeId>,
+    // Out-of-line modules declared in the file, in the order they are declared
+    module_decls: Vec<ModuleDecl>,
+}
+
+// A `mod foo;` declaration whose file is loaded when the fragments are merged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModuleDecl {
+    module_id: NodeId,
+    name: String,
+    module_path: Vec<String>,
+    path_attr: Option<String>,
+    // Directories of the module the declaration is in
+    parent_dirs: ModuleDirs,
+    // File the declaration is in, and the 1-based position of the module's name there
+    file: PathBuf,
+    line: usize,
+    column: usize,
+}
+
+// Build the graph of a crate from the fragments of its files. `fragment` produces the
+// fragment of a file given its contents, by visiting it or from a cache. Files are
+// visited in parallel, but merged in the order a single thread would visit them, so
+// the graph is the same on every run.
+pub(crate) fn analyze_files(
+    file_path: &Path,
+    root_name: &str,
+    package: Option<&str>,
+    module_dirs: Option<ModuleDirs>,
+    options: &ParseOptions,
+    fragment: &FragmentFn,
+) -> Result<CodeGraph> {
+    let source = read_source(file_path)?;
+    let module_path = vec![root_name.to_string()];
+    let package = package.map(str::to_string);
+    let root = FileModule {
+        file: file_path.to_path_buf(),
+        module_id: VisitorState::new(&module_path, package.clone()).module_id(),
+        module_path,
+        package,
+        module_dirs,
+    };
+    let mut root_fragment = fragment(&root, source)?;
+    let module_decls = std::mem::take(&mut root_fragment.module_decls);
+    let caller = std::thread::current().id();
+    let loaded = load_modules(
+        module_decls,
+        fragment,
+        &root,
+        &[canonical(file_path)],
+        caller,
+    );
+
+    let mut assembly = Assembly::new(root_fragment, options);
+    assembly.add_modules(loaded, &HashMap::new());
+    if let Some(err) = assembly.module_error {
+        return Err(err);
+    }
+
+    let mut graph = assembly.graph;
+    resolve_names(&mut graph, root.module_id, &assembly.type_scopes);
+    Ok(graph)
+}
 
-    // Create the root module first
-    let root_module_id = visitor_state.next_node_id();
-    visitor_state.code_graph.modules.push(ModuleNode {
-        id: root_module_id,
-        name: "root".to_string(),
+// Produces the fragment of a file, see `analyze_files`
+pub(crate) type FragmentFn<'a> = dyn Fn(&FileModule, String) -> Result<FileFragment> + Sync + 'a;
+
+// A declared module with the fragment of its file, and the modules that file declares
+struct LoadedModule {
+    decl: ModuleDecl,
+    fragment: Result<(FileFragment, Vec<LoadedModule>)>,
+}
+
+// Locate, read and visit the files of the declared modules and of the modules they
+// declare in turn, on the thread pool. `root` is the file module of the crate root, whose
+// package the modules share. `loading` holds the files of the modules the
+// declarations are nested in, so that `#[path]` attributes that lead back to one of
+// them are reported instead of being followed forever.
+fn load_modules(
+    decls: Vec<ModuleDecl>,
+    fragment: &FragmentFn,
+    root: &FileModule,
+    loading: &[PathBuf],
+    caller: ThreadId,
+) -> Vec<LoadedModule> {
+    decls
+        .into_par_iter()
+        .map(|decl| {
+            let loaded = load_module(&decl, fragment, root, loading);
+            // Parsing with span locations keeps the source of every parsed file in a
+            // thread-local map until the spans of the thread are invalidated. Nothing
+            // refers to the spans of a file once its fragment is built, so free them on
+            // the pool's threads, but leave those of the caller's thread alone.
+            if std::thread::current().id() != caller {
+                proc_macro2::extra::invalidate_current_thread_spans();
+            }
+            let fragment = loaded.map(|(mut child, file)| {
+                let module_decls = std::mem::take(&mut child.module_decls);
+                let loading = [loading, &[file]].concat();
+                (
+                    child,
+                    load_modules(module_decls, fragment, root, &loading, caller),
+                )
+            });
+            LoadedModule { decl, fragment }
+        })
+        .collect()
+}
+
+// Visit the file of a declared module, returning its fragment and canonical path
+fn load_module(
+    decl: &ModuleDecl,
+    fragment: &FragmentFn,
+    root: &FileModule,
+    loading: &[PathBuf],
+) -> Result<(FileFragment, PathBuf)> {
+    let (file_path, child_dirs) = decl
+        .parent_dirs
+        .resolve(&decl.name, decl.path_attr.as_deref())
+        .ok_or_else(|| {
+            SynParserError::resolution(
+                &decl.file,
+                format!(
+                    "file not found for module `{}` (line {})",
+                    decl.name, decl.line
+                ),
+            )
+        })?;
+    let canonical_path = canonical(&file_path);
+    if loading.contains(&canonical_path) {
+        return Err(SynParserError::resolution(
+            &decl.file,
+            format!(
+                "module `{}` (line {}) includes {}, which it is already part of",
+                decl.name,
+                decl.line,
+                file_path.display()
+            ),
+        ));
+    }
+    let file_module = FileModule {
+        file: file_path.clone(),
+        module_id: decl.module_id,
+        module_path: decl.module_path.clone(),
+        package: root.package.clone(),
+        module_dirs: Some(child_dirs),
+    };
+    let source = read_source(&file_path)?;
+    Ok((fragment(&file_module, source)?, canonical_path))
+}
+
+// The path of `file` with links and `..` resolved, to tell when two paths name one file
+fn canonical(file: &Path) -> PathBuf {
+    file.canonicalize().unwrap_or_else(|_| file.to_path_buf())
+}
+
+// Visit the contents of a single file. Fails only on a syntax error outside of tolerant
+// mode.
+pub(crate) fn visit_file(
+    file_module: &FileModule,
+    source: String,
+    options: &ParseOptions,
+) -> Result<FileFragment> {
+    let file_path = &file_module.file;
+    let mut visitor_state =
+        VisitorState::new(&file_module.module_path, file_module.package.clone());
+    visitor_state.current_file = file_path.clone();
+    visitor_state.module_dirs = file_module.module_dirs.clone();
+    visitor_state.options = options.clone();
+
+    // The module the file backs comes first. For out-of-line modules it only collects
+    // what the file adds to the node of the declaration in the parent file, so it has no
+    // span of its own.
+    let module_id = file_module.module_id;
+    visitor_state.used_ids.insert(module_id);
+    visitor_state.current_module = module_id;
+    visitor_state.add_module(ModuleNode {
+        id: module_id,
+        name: file_module.module_path.last().cloned().unwrap_or_default(),
+        path: file_module.module_path.join("::"),
         visibility: VisibilityKind::Inherited,
         attributes: Vec::new(),
         docstring: None,
@@ -31,36 +336,220 @@ pub fn analyze_code(file_path: &Path) -> Result<CodeGraph, syn::Error> {
         items: Vec::new(),
         imports: Vec::new(),
         exports: Vec::new(),
+        diagnostics: Vec::new(),
+        span: if file_module.module_path.len() == 1 {
+            Span::whole_file(file_path.clone(), &source)
+        } else {
+            Span::whole_file(PathBuf::new(), "")
+        },
     });
 
+    let file = if options.error_tolerant {
+        visitor_state.parse_tolerant(module_id, file_path, &source)
+    } else {
+        parse_source(file_path, &source)?
+    };
+    visitor_state.add_file_attributes(module_id, &file.attrs);
+    visitor_state.current_source = source;
+
     let mut visitor = CodeVisitor::new(&mut visitor_state);
-    visitor.visit_file(&file);
-
-    // Add relations between root module and top-level items
-    for module in &visitor_state.code_graph.modules {
-        if module.id != root_module_id {
-            visitor_state.code_graph.relations.push(Relation {
-                source: root_module_id,
-                target: module.id,
-                kind: RelationKind::Contains,
-            });
+    visitor.visit_module_items(module_id, &file.items);
+    visitor_state.attach_comments(module_id);
+
+    let mut ids: Vec<NodeId> = visitor_state
+        .used_ids
+        .into_iter()
+        .filter(|id| *id != module_id)
+        .collect();
+    ids.sort();
+    Ok(FileFragment {
+        graph: visitor_state.code_graph,
+        type_scopes: visitor_state.type_scopes,
+        ids,
+        module_decls: visitor_state.module_decls,
+    })
+}
+
+// The graph of a crate being put together from the fragments of its files
+struct Assembly<'o> {
+    graph: CodeGraph,
+    // Position of each module in `graph.modules`
+    module_index: HashMap<NodeId, usize>,
+    // Node IDs of the fragments merged so far
+    used_ids: HashSet<NodeId>,
+    type_scopes: HashMap<TypeId, NodeId>,
+    // First error hit while loading the file of an out-of-line module
+    module_error: Option<SynParserError>,
+    options: &'o ParseOptions,
+}
+
+impl<'o> Assembly<'o> {
+    fn new(root: FileFragment, options: &'o ParseOptions) -> Self {
+        let module_index = index_modules(&root.graph.modules, 0);
+        let mut used_ids: HashSet<NodeId> = root.ids.into_iter().collect();
+        used_ids.insert(root.graph.modules[0].id);
+        Self {
+            graph: root.graph,
+            module_index,
+            used_ids,
+            type_scopes: root.type_scopes,
+            module_error: None,
+            options,
         }
     }
 
-    Ok(visitor_state.code_graph)
+    // Merge the fragments of loaded modules, depth first so nested modules come before
+    // the next sibling. `remapped` holds the IDs that changed when the fragment
+    // declaring the modules was merged.
+    fn add_modules(&mut self, loaded: Vec<LoadedModule>, remapped: &HashMap<NodeId, NodeId>) {
+        for LoadedModule { mut decl, fragment } in loaded {
+            let visited_as = decl.module_id;
+            if let Some(&module_id) = remapped.get(&decl.module_id) {
+                decl.module_id = module_id;
+            }
+            match fragment {
+                Ok((child, nested)) => {
+                    let child_remapped = self.merge(decl.module_id, visited_as, child);
+                    self.add_modules(nested, &child_remapped);
+                }
+                Err(err) => self.record_module_error(&decl, err),
+            }
+        }
+    }
+
+    // Add the nodes of a fragment for the module `module_id`, visited as `visited_as`, to
+    // the graph, returning the IDs that had to change.
+    //
+    // IDs are derived from paths, so fragments only share IDs when a module is declared
+    // twice, like `#[cfg(unix)] mod imp;` and `#[cfg(windows)] mod imp;`. The IDs of the
+    // later fragment get a disambiguator, as they would in a single file.
+    fn merge(
+        &mut self,
+        module_id: NodeId,
+        visited_as: NodeId,
+        fragment: FileFragment,
+    ) -> HashMap<NodeId, NodeId> {
+        let FileFragment {
+            mut graph,
+            mut type_scopes,
+            ids,
+            ..
+        } = fragment;
+
+        let mut remapped = HashMap::new();
+        if module_id != visited_as {
+            remapped.insert(visited_as, module_id);
+        }
+        for id in ids {
+            let mut new_id = id;
+            let mut disambiguator = 1;
+            while !self.used_ids.insert(new_id) {
+                let parts = [id.element().to_string(), disambiguator.to_string()];
+                new_id = NodeId::new(id.namespace(), stable_hash(&[&parts[0], &parts[1]]));
+                disambiguator += 1;
+            }
+            if new_id != id {
+                remapped.insert(id, new_id);
+            }
+        }
+        graph.remap_node_ids(&remapped);
+        for scope in type_scopes.values_mut() {
+            if let Some(&new_id) = remapped.get(scope) {
+                *scope = new_id;
+            }
+        }
+
+        let file_module = graph.modules.remove(0);
+        if let Some(module) = self.module_mut(module_id) {
+            module.items = file_module.items;
+            module.submodules = file_module.submodules;
+            module.imports = file_module.imports;
+            module.attributes.extend(file_module.attributes);
+            if module.docstring.is_none() {
+                module.docstring = file_module.docstring;
+            }
+            module.diagnostics.extend(file_module.diagnostics);
+        }
+
+        self.module_index
+            .extend(index_modules(&graph.modules, self.graph.modules.len()));
+        self.graph.append(graph);
+        self.type_scopes.extend(type_scopes);
+        remapped
+    }
+
+    fn module_mut(&mut self, module_id: NodeId) -> Option<&mut ModuleNode> {
+        let index = *self.module_index.get(&module_id)?;
+        self.graph.modules.get_mut(index)
+    }
+
+    // Handle a failure to load the file of a declared module: record it as a diagnostic
+    // at the declaration in tolerant mode, otherwise keep the first error
+    fn record_module_error(&mut self, decl: &ModuleDecl, err: SynParserError) {
+        if self.options.error_tolerant {
+            let diagnostic = Diagnostic {
+                file: decl.file.clone(),
+                line: decl.line,
+                column: decl.column,
+                message: err.to_string(),
+            };
+            if let Some(module) = self.module_mut(decl.module_id) {
+                module.diagnostics.push(diagnostic);
+            }
+        } else if self.module_error.is_none() {
+            self.module_error = Some(err);
+        }
+    }
+}
+
+// Positions of `modules` in a list they are appended to at `offset`
+fn index_modules(modules: &[ModuleNode], offset: usize) -> HashMap<NodeId, usize> {
+    modules
+        .iter()
+        .enumerate()
+        .map(|(i, module)| (module.id, offset + i))
+        .collect()
 }
 
 // State for the visitor
 struct VisitorState {
     code_graph: CodeGraph,
-    next_node_id: NodeId,
-    next_type_id: TypeId,
-    // Maps existing types to their IDs to avoid duplication
-    type_map: HashMap<String, TypeId>,
+    // Position of each module in `code_graph.modules`
+    module_index: HashMap<NodeId, usize>,
+    // Canonical path of the module whose items are currently being visited, e.g.
+    // `["crate", "net", "tcp"]`
+    module_path: Vec<String>,
+    // Package of the crate in a workspace, see `namespace`
+    package: Option<String>,
+    // IDs given out so far, to disambiguate elements that would share an ID
+    used_ids: HashSet<NodeId>,
+    // Maps the types written in each module to their IDs to avoid duplication
+    type_map: HashMap<(NodeId, String), TypeId>,
+    // Module whose items are currently being visited
+    current_module: NodeId,
+    // Module each type is written in, used to resolve named types after visiting
+    type_scopes: HashMap<TypeId, NodeId>,
+    // File whose items are currently being visited, and its contents
+    current_file: PathBuf,
+    current_source: String,
+    // Where to look for the files of out-of-line modules, `None` when they are not followed
+    module_dirs: Option<ModuleDirs>,
+    // Out-of-line modules declared so far, whose files are loaded after this one
+    module_decls: Vec<ModuleDecl>,
+    // Function whose body is being visited, which the calls in it are attributed to
+    current_fn: Option<NodeId>,
+    // Methods of the impl or trait being visited, by name
+    method_ids: HashMap<String, NodeId>,
+    // Parameters and `let` bindings in scope in the current function body, with their
+    // types as paths when written down, which tell the types of method call receivers
+    bindings: HashMap<String, Option<Vec<String>>>,
+    // Which items to record and how much of them to keep
+    options: ParseOptions,
 }
 
 impl VisitorState {
-    fn new() -> Self {
+    // State for visiting the items of the module at `module_path`
+    fn new(module_path: &[String], package: Option<String>) -> Self {
         Self {
             code_graph: CodeGraph {
                 functions: Vec::new(),
@@ -69,52 +558,247 @@ impl VisitorState {
                 impls: Vec::new(),
                 traits: Vec::new(),
                 private_traits: Vec::new(),
+                trait_aliases: Vec::new(),
                 relations: Vec::new(),
                 modules: Vec::new(),
                 values: Vec::new(),
                 macros: Vec::new(),
+                foreign_mods: Vec::new(),
+                macro_invocations: Vec::new(),
+                calls: Vec::new(),
+                block_imports: Vec::new(),
+                comments: Vec::new(),
             },
-            next_node_id: 0,
-            next_type_id: 0,
+            module_index: HashMap::new(),
+            module_path: module_path.to_vec(),
+            package,
+            used_ids: HashSet::new(),
             type_map: HashMap::new(),
+            current_module: NodeId::from_compact(0),
+            type_scopes: HashMap::new(),
+            current_file: PathBuf::new(),
+            current_source: String::new(),
+            module_dirs: None,
+            module_decls: Vec::new(),
+            current_fn: None,
+            method_ids: HashMap::new(),
+            bindings: HashMap::new(),
+            options: ParseOptions::default(),
         }
     }
 
-    fn next_node_id(&mut self) -> NodeId {
-        let id = self.next_node_id;
-        self.next_node_id += 1;
-        id
+    // Namespace of the IDs of the current module and its items: the hash of its
+    // canonical path, preceded by the package name for the members of a workspace
+    fn namespace(&self) -> u64 {
+        let path = self.module_path.join("::");
+        match &self.package {
+            Some(package) => stable_hash(&[package, &path]),
+            None => stable_hash(&[&path]),
+        }
+    }
+
+    // ID of the current module
+    fn module_id(&mut self) -> NodeId {
+        let namespace = self.namespace();
+        self.unique_id(namespace, &["mod"])
+    }
+
+    // Canonical path of an item with the given name declared in the current module
+    fn item_path(&self, name: &str) -> String {
+        format!("{}::{}", self.module_path.join("::"), name)
+    }
+
+    // ID of an item of the given kind declared in the current module
+    fn item_id(&mut self, kind: &str, name: &str) -> NodeId {
+        let namespace = self.namespace();
+        self.unique_id(namespace, &[kind, name])
     }
 
-    fn next_type_id(&mut self) -> TypeId {
-        let id = self.next_type_id;
-        self.next_type_id += 1;
+    // ID of an element of the given kind inside `parent`, like a field or a parameter
+    fn child_id(&mut self, parent: NodeId, kind: &str, name: &str) -> NodeId {
+        let parent_element = parent.element().to_string();
+        self.unique_id(parent.namespace(), &[&parent_element, kind, name])
+    }
+
+    // Add a disambiguator to `parts` until the resulting ID has not been given out yet
+    fn unique_id(&mut self, namespace: u64, parts: &[&str]) -> NodeId {
+        let mut id = NodeId::new(namespace, stable_hash(parts));
+        let mut disambiguator = 1;
+        while !self.used_ids.insert(id) {
+            let disambiguator_str = disambiguator.to_string();
+            let mut disambiguated = parts.to_vec();
+            disambiguated.push(&disambiguator_str);
+            id = NodeId::new(namespace, stable_hash(&disambiguated));
+            disambiguator += 1;
+        }
         id
     }
 
+    // Parse a file item by item, recording what does not parse as diagnostics
+    fn parse_tolerant(&mut self, module_id: NodeId, path: &Path, source: &str) -> syn::File {
+        let (file, errors) = match parse_items_tolerant(source) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                let file = syn::File {
+                    shebang: None,
+                    attrs: Vec::new(),
+                    items: Vec::new(),
+                };
+                (file, vec![err])
+            }
+        };
+
+        let diagnostics = errors
+            .iter()
+            .map(|err| Diagnostic::from_syn_error(path.to_path_buf(), err))
+            .collect();
+        self.add_diagnostics(module_id, diagnostics);
+        file
+    }
+
+    // Record a call site in the body of the current function. Calls outside of function
+    // bodies, like in the initializer of a constant, are not recorded.
+    fn add_call(
+        &mut self,
+        kind: CallKind,
+        path: Vec<String>,
+        receiver: Option<Receiver>,
+        span: Span,
+    ) {
+        let Some(caller) = self.current_fn else {
+            return;
+        };
+        let id = self.child_id(caller, "call", &path.join("::"));
+        self.code_graph.calls.push(CallNode {
+            id,
+            caller,
+            module: self.current_module,
+            kind,
+            path,
+            receiver,
+            callee: None,
+            callee_path: None,
+            resolution: None,
+            confidence: None,
+            candidates: Vec::new(),
+            span,
+        });
+    }
+
+    // Bring the binding of a parameter or `let` with a type into scope, like `x: &Config`
+    fn bind_typed(&mut self, pat_type: &PatType) {
+        if let (Pat::Ident(pat), Some(path)) = (&*pat_type.pat, receiver_type(&pat_type.ty)) {
+            self.bindings.insert(pat.ident.to_string(), Some(path));
+        }
+    }
+
+    // Whether `path` names a parameter or `let` binding in scope rather than an item
+    fn is_local(&self, path: &syn::Path) -> bool {
+        path.get_ident()
+            .is_some_and(|ident| self.bindings.contains_key(&ident.to_string()))
+    }
+
+    // Record imports nested in a block, see `CodeGraph::block_imports`
+    fn add_block_imports(&mut self, imports: Vec<ImportNode>) {
+        let source = self.current_fn.unwrap_or(self.current_module);
+        for import in imports {
+            self.code_graph.relations.push(Relation {
+                source,
+                target: import.id,
+                kind: RelationKind::Uses,
+            });
+            self.code_graph.block_imports.push(import);
+        }
+    }
+
+    fn add_module(&mut self, module: ModuleNode) {
+        self.module_index
+            .insert(module.id, self.code_graph.modules.len());
+        self.code_graph.modules.push(module);
+    }
+
+    fn module_mut(&mut self, module_id: NodeId) -> Option<&mut ModuleNode> {
+        let index = *self.module_index.get(&module_id)?;
+        self.code_graph.modules.get_mut(index)
+    }
+
+    fn add_diagnostics(&mut self, module_id: NodeId, diagnostics: Vec<Diagnostic>) {
+        if let Some(module) = self.module_mut(module_id) {
+            module.diagnostics.extend(diagnostics);
+        }
+    }
+
+    // Inner attributes and `//!` docs of a file belong to the module it backs
+    fn add_file_attributes(&mut self, module_id: NodeId, attrs: &[syn::Attribute]) {
+        let attributes = self.extract_attributes(attrs);
+        let docstring = self.extract_docstring(attrs);
+        if let Some(module) = self.module_mut(module_id) {
+            module.attributes.extend(attributes);
+            if module.docstring.is_none() {
+                module.docstring = docstring;
+            }
+        }
+    }
+
+    // ID of the type written as `key` in the current module. The same spelling can name
+    // different types in different modules, so types are interned per module while
+    // visiting, and merged by what they resolve to afterwards.
+    fn type_id(&self, key: &str) -> TypeId {
+        NodeId::new(
+            self.current_module.namespace(),
+            stable_hash(&["type_ref", key]),
+        )
+    }
+
     // Get or create a type ID
     fn get_or_create_type(&mut self, ty: &Type) -> TypeId {
         // Convert type to a string representation for caching
         let type_str = ty.to_token_stream().to_string();
+        let map_key = (self.current_module, type_str);
 
-        if let Some(&id) = self.type_map.get(&type_str) {
+        if let Some(&id) = self.type_map.get(&map_key) {
             return id;
         }
 
         let (type_kind, related_types) = self.process_type(ty);
 
-        let id = self.next_type_id();
-        self.type_map.insert(type_str, id);
-
+        let id = self.type_id(&map_key.1);
+        self.type_scopes.insert(id, self.current_module);
         self.code_graph.type_graph.push(TypeNode {
             id,
             kind: type_kind,
             related_types,
+            spellings: vec![map_key.1.clone()],
+            resolution: None,
         });
+        self.type_map.insert(map_key, id);
 
         id
     }
 
+    // Get or create a type that is not written as a `syn::Type`, like the type of a
+    // `self` receiver, identified by `key`
+    fn get_or_create_synthetic_type(
+        &mut self,
+        key: String,
+        kind: TypeKind,
+        related_types: Vec<TypeId>,
+    ) -> TypeId {
+        let id = self.type_id(&key);
+        if let Entry::Vacant(entry) = self.type_map.entry((self.current_module, key)) {
+            self.type_scopes.insert(id, self.current_module);
+            self.code_graph.type_graph.push(TypeNode {
+                id,
+                kind,
+                related_types,
+                spellings: vec![entry.key().1.clone()],
+                resolution: None,
+            });
+            entry.insert(id);
+        }
+        id
+    }
+
     // Process a type and get its kind and related types
     fn process_type(&mut self, ty: &Type) -> (TypeKind, Vec<TypeId>) {
         let mut related_types = Vec::new();
@@ -240,23 +924,11 @@ impl VisitorState {
                     .filter_map(|bound| {
                         match bound {
                             syn::TypeParamBound::Trait(trait_bound) => {
-                                let _path_string = trait_bound.path.to_token_stream().to_string();
-                                // Create a synthetic type for the trait bound
-                                let bound_id = self.next_type_id();
-                                self.code_graph.type_graph.push(TypeNode {
-                                    id: bound_id,
-                                    kind: TypeKind::Named {
-                                        path: trait_bound
-                                            .path
-                                            .segments
-                                            .iter()
-                                            .map(|seg| seg.ident.to_string())
-                                            .collect(),
-                                        is_fully_qualified: false,
-                                    },
-                                    related_types: Vec::new(),
-                                });
-                                Some(bound_id)
+                                // Bounds are interned like the path types they are written as
+                                Some(self.get_or_create_type(&Type::Path(TypePath {
+                                    qself: None,
+                                    path: trait_bound.path.clone(),
+                                })))
                             }
                             _ => None,
                         }
@@ -276,23 +948,11 @@ impl VisitorState {
                     .filter_map(|bound| {
                         match bound {
                             syn::TypeParamBound::Trait(trait_bound) => {
-                                let _path_string = trait_bound.path.to_token_stream().to_string();
-                                // Create a synthetic type for the trait bound
-                                let bound_id = self.next_type_id();
-                                self.code_graph.type_graph.push(TypeNode {
-                                    id: bound_id,
-                                    kind: TypeKind::Named {
-                                        path: trait_bound
-                                            .path
-                                            .segments
-                                            .iter()
-                                            .map(|seg| seg.ident.to_string())
-                                            .collect(),
-                                        is_fully_qualified: false,
-                                    },
-                                    related_types: Vec::new(),
-                                });
-                                Some(bound_id)
+                                // Bounds are interned like the path types they are written as
+                                Some(self.get_or_create_type(&Type::Path(TypePath {
+                                    qself: None,
+                                    path: trait_bound.path.clone(),
+                                })))
                             }
                             _ => None,
                         }
@@ -323,14 +983,10 @@ impl VisitorState {
     fn convert_visibility(&self, vis: &Visibility) -> VisibilityKind {
         match vis {
             Visibility::Public(_) => VisibilityKind::Public,
+            // Restrictions are recorded as the path of the module they reach from the
+            // crate root, so `pub(super)` in `crate::net::tcp` becomes `crate::net`
             Visibility::Restricted(restricted) => {
-                let path = restricted
-                    .path
-                    .segments
-                    .iter()
-                    .map(|seg| seg.ident.to_string())
-                    .collect();
-                VisibilityKind::Restricted(path)
+                VisibilityKind::Restricted(self.restricted_module(&restricted.path))
             }
             // Private visibility shows up as Inherited in syn, which in Rust means
             // visibility is limited to the current module and its descendants
@@ -338,11 +994,36 @@ impl VisitorState {
         }
     }
 
+    // Path from the crate root of the module named by a `pub(in path)` restriction
+    fn restricted_module(&self, path: &syn::Path) -> Vec<String> {
+        let segments = path.segments.iter().map(|seg| seg.ident.to_string());
+        let mut module = vec!["crate".to_string()];
+        match path.segments.first() {
+            Some(first) if first.ident == "self" || first.ident == "super" => {
+                module.extend(self.module_path[1..].iter().cloned());
+                for segment in segments {
+                    match segment.as_str() {
+                        "super" if module.len() > 1 => {
+                            module.pop();
+                        }
+                        "self" | "super" => {}
+                        _ => module.push(segment),
+                    }
+                }
+            }
+            Some(first) if first.ident == "crate" => module.extend(segments.skip(1)),
+            // Paths of the 2015 edition start at the crate root
+            _ => module.extend(segments),
+        }
+        module
+    }
+
     // Process a function parameter
-    fn process_fn_arg(&mut self, arg: &FnArg) -> Option<ParameterNode> {
+    fn process_fn_arg(&mut self, fn_id: NodeId, arg: &FnArg) -> Option<ParameterNode> {
         match arg {
             FnArg::Typed(PatType { pat, ty, .. }) => {
                 let type_id = self.get_or_create_type(ty);
+                let id = self.child_id(fn_id, "param", &pat.to_token_stream().to_string());
 
                 // Extract parameter name and mutability
                 let (name, is_mutable) = match &**pat {
@@ -353,16 +1034,16 @@ impl VisitorState {
                 };
 
                 Some(ParameterNode {
-                    id: self.next_node_id(),
+                    id,
                     name,
                     type_id,
                     is_mutable,
                     is_self: false,
+                    span: self.span(arg),
                 })
             }
             FnArg::Receiver(receiver) => {
                 // Create a special self type
-                let self_type_id = self.next_type_id();
                 let mut related_types = Vec::new();
 
                 // If we have an explicit type for self, include it
@@ -372,28 +1053,30 @@ impl VisitorState {
                 related_types.push(inner_type_id);
                 // }
 
-                self.code_graph.type_graph.push(TypeNode {
-                    id: self_type_id,
-                    kind: TypeKind::Named {
+                // One self type for each way of writing the receiver
+                let self_type_id = self.get_or_create_synthetic_type(
+                    format!("self: {}", ty_ref.to_token_stream()),
+                    TypeKind::Named {
                         path: vec!["Self".to_string()],
                         is_fully_qualified: false,
                     },
                     related_types,
-                });
+                );
 
                 Some(ParameterNode {
-                    id: self.next_node_id(),
+                    id: self.child_id(fn_id, "param", "self"),
                     name: Some("self".to_string()),
                     type_id: self_type_id,
                     is_mutable: receiver.mutability.is_some(),
                     is_self: true,
+                    span: self.span(arg),
                 })
             }
         }
     }
 
-    // Process generic parameters
-    fn process_generics(&mut self, generics: &Generics) -> Vec<GenericParamNode> {
+    // Process the generic parameters of the item `parent`
+    fn process_generics(&mut self, parent: NodeId, generics: &Generics) -> Vec<GenericParamNode> {
         let mut params = Vec::new();
 
         for param in &generics.params {
@@ -409,19 +1092,10 @@ impl VisitorState {
                         .map(|bound| self.process_type_bound(bound))
                         .collect();
 
-                    let default_type = default.as_ref().map(|expr| {
-                        let path = expr.to_token_stream().to_string();
-                        if let Some(&id) = self.type_map.get(&path) {
-                            id
-                        } else {
-                            let id = self.next_type_id();
-                            self.get_or_create_type(expr);
-                            id
-                        }
-                    });
+                    let default_type = default.as_ref().map(|ty| self.get_or_create_type(ty));
 
                     params.push(GenericParamNode {
-                        id: self.next_node_id(),
+                        id: self.child_id(parent, "generic", &ident.to_string()),
                         kind: GenericParamKind::Type {
                             name: ident.to_string(),
                             bounds,
@@ -437,7 +1111,7 @@ impl VisitorState {
                         .collect();
 
                     params.push(GenericParamNode {
-                        id: self.next_node_id(),
+                        id: self.child_id(parent, "generic", &lifetime_def.lifetime.to_string()),
                         kind: GenericParamKind::Lifetime {
                             name: lifetime_def.lifetime.ident.to_string(),
                             bounds,
@@ -448,7 +1122,7 @@ impl VisitorState {
                     let type_id = self.get_or_create_type(&const_param.ty);
 
                     params.push(GenericParamNode {
-                        id: self.next_node_id(),
+                        id: self.child_id(parent, "generic", &const_param.ident.to_string()),
                         kind: GenericParamKind::Const {
                             name: const_param.ident.to_string(),
                             type_id,
@@ -461,6 +1135,170 @@ impl VisitorState {
         params
     }
 
+    // Create one import for every leaf of a `use` tree
+    fn process_use(&mut self, use_item: &syn::ItemUse) -> Vec<ImportNode> {
+        let mut imports = Vec::new();
+        self.process_use_tree(&use_item.tree, &mut Vec::new(), &use_item.vis, &mut imports);
+        imports
+    }
+
+    fn process_use_tree(
+        &mut self,
+        tree: &syn::UseTree,
+        prefix: &mut Vec<String>,
+        vis: &Visibility,
+        imports: &mut Vec<ImportNode>,
+    ) {
+        // `use a::b::{self}` imports `a::b` itself
+        let leaf_path = |prefix: &[String], name: &syn::Ident| {
+            let mut path = prefix.to_vec();
+            if name != "self" || path.is_empty() {
+                path.push(name.to_string());
+            }
+            path
+        };
+
+        let (path, alias, is_glob) = match tree {
+            syn::UseTree::Path(use_path) => {
+                prefix.push(use_path.ident.to_string());
+                self.process_use_tree(&use_path.tree, prefix, vis, imports);
+                prefix.pop();
+                return;
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.process_use_tree(tree, prefix, vis, imports);
+                }
+                return;
+            }
+            syn::UseTree::Name(name) => (leaf_path(prefix, &name.ident), None, false),
+            syn::UseTree::Rename(rename) => (
+                leaf_path(prefix, &rename.ident),
+                Some(rename.rename.to_string()),
+                false,
+            ),
+            syn::UseTree::Glob(_) => (prefix.clone(), None, true),
+        };
+
+        let name = match &alias {
+            Some(alias) => format!("{} as {}", path.join("::"), alias),
+            None if is_glob => format!("{}::*", path.join("::")),
+            None => path.join("::"),
+        };
+        imports.push(ImportNode {
+            id: self.item_id("use", &name),
+            path,
+            alias,
+            is_glob,
+            visibility: self.convert_visibility(vis),
+            kind: ImportKind::UseStatement,
+            span: self.span(tree),
+        });
+    }
+
+    // Create the import for an `extern crate` item
+    fn process_extern_crate(&mut self, extern_crate: &syn::ItemExternCrate) -> ImportNode {
+        ImportNode {
+            id: self.item_id("extern_crate", &extern_crate.to_token_stream().to_string()),
+            path: vec![extern_crate.ident.to_string()],
+            alias: extern_crate
+                .rename
+                .as_ref()
+                .map(|(_, rename)| rename.to_string()),
+            is_glob: false,
+            visibility: self.convert_visibility(&extern_crate.vis),
+            kind: ImportKind::ExternCrate,
+            span: self.span(extern_crate),
+        }
+    }
+
+    // Process an associated type declared in a trait
+    fn process_trait_assoc_type(
+        &mut self,
+        trait_id: NodeId,
+        item: &syn::TraitItemType,
+    ) -> AssocTypeNode {
+        let id = self.child_id(trait_id, "assoc_type", &item.ident.to_string());
+        let generic_params = self.process_generics(id, &item.generics);
+        let bounds = item
+            .bounds
+            .iter()
+            .map(|bound| self.process_type_bound(bound))
+            .collect();
+        let type_id = item
+            .default
+            .as_ref()
+            .map(|(_, ty)| self.get_or_create_type(ty));
+
+        AssocTypeNode {
+            id,
+            name: item.ident.to_string(),
+            generic_params,
+            bounds,
+            type_id,
+            attributes: self.extract_attributes(&item.attrs),
+            docstring: self.extract_docstring(&item.attrs),
+            span: self.span(item),
+        }
+    }
+
+    // Process an associated type given a value in an impl
+    fn process_impl_assoc_type(
+        &mut self,
+        impl_id: NodeId,
+        item: &syn::ImplItemType,
+    ) -> AssocTypeNode {
+        let id = self.child_id(impl_id, "assoc_type", &item.ident.to_string());
+        let generic_params = self.process_generics(id, &item.generics);
+        let type_id = self.get_or_create_type(&item.ty);
+
+        AssocTypeNode {
+            id,
+            name: item.ident.to_string(),
+            generic_params,
+            bounds: Vec::new(),
+            type_id: Some(type_id),
+            attributes: self.extract_attributes(&item.attrs),
+            docstring: self.extract_docstring(&item.attrs),
+            span: self.span(item),
+        }
+    }
+
+    // Process an associated constant, adding a relation to its type
+    fn process_assoc_const(
+        &mut self,
+        parent: NodeId,
+        ident: &syn::Ident,
+        ty: &Type,
+        value: Option<&syn::Expr>,
+        attrs: &[syn::Attribute],
+        span: Span,
+    ) -> AssocConstNode {
+        let id = self.child_id(parent, "assoc_const", &ident.to_string());
+        let type_id = self.get_or_create_type(ty);
+        self.code_graph.relations.push(Relation {
+            source: id,
+            target: type_id,
+            kind: RelationKind::ValueType,
+        });
+
+        let (value, value_normalized) = match value {
+            Some(expr) => self.body(expr),
+            None => (None, None),
+        };
+
+        AssocConstNode {
+            id,
+            name: ident.to_string(),
+            type_id,
+            value,
+            value_normalized,
+            attributes: self.extract_attributes(attrs),
+            docstring: self.extract_docstring(attrs),
+            span,
+        }
+    }
+
     fn process_type_bound(&mut self, bound: &syn::TypeParamBound) -> TypeId {
         match bound {
             syn::TypeParamBound::Trait(trait_bound) => {
@@ -469,20 +1307,25 @@ impl VisitorState {
                     path: trait_bound.path.clone(),
                 }))
             }
-            syn::TypeParamBound::Lifetime(_) => {
+            syn::TypeParamBound::Lifetime(lifetime) => {
                 // Create a synthetic type for the lifetime bound
-                let type_id = self.next_type_id();
-                self.code_graph.type_graph.push(TypeNode {
-                    id: type_id,
-                    kind: TypeKind::Named {
+                self.get_or_create_synthetic_type(
+                    lifetime.to_string(),
+                    TypeKind::Named {
                         path: vec!["lifetime".to_string()],
                         is_fully_qualified: false,
                     },
-                    related_types: Vec::new(),
-                });
-                type_id
+                    Vec::new(),
+                )
+            }
+            _ => {
+                let type_str = bound.to_token_stream().to_string();
+                self.get_or_create_synthetic_type(
+                    type_str.clone(),
+                    TypeKind::Unknown { type_str },
+                    Vec::new(),
+                )
             }
-            _ => self.next_type_id(),
         }
     }
 
@@ -546,6 +1389,85 @@ impl VisitorState {
             value: Some(attr.to_token_stream().to_string()),
         }
     }
+    // Record the comments of the current file, attached to the nodes visited from it.
+    // `file_module` is the module the file backs.
+    fn attach_comments(&mut self, file_module: NodeId) {
+        if !self.options.include_comments {
+            return;
+        }
+
+        let scanned = scan_comments(&self.current_source);
+        let spans = file_node_spans(&self.code_graph, &self.current_file);
+        let attached: Vec<_> = scanned
+            .iter()
+            .map(|comment| attach(comment, &scanned, &spans, &self.current_source, file_module))
+            .collect();
+
+        for (comment, (target, placement, module)) in scanned.iter().zip(attached) {
+            let text = self.current_source[comment.range.clone()].to_string();
+            let id = self.child_id(target, "comment", &text);
+
+            self.code_graph.relations.push(Relation {
+                source: id,
+                target,
+                kind: RelationKind::Annotates,
+            });
+            self.code_graph.comments.push(CommentNode {
+                id,
+                kind: classify(&text),
+                text,
+                placement,
+                target,
+                module,
+                span: span_of(
+                    &self.current_file,
+                    &self.current_source,
+                    comment.range.clone(),
+                ),
+            });
+        }
+    }
+
+    // Location of a syntax node in the file being visited
+    fn span(&self, node: &impl Spanned) -> Span {
+        let span = node.span();
+        let (start, end) = (span.start(), span.end());
+        Span {
+            file: self.current_file.clone(),
+            start_line: start.line,
+            start_col: start.column,
+            end_line: end.line,
+            end_col: end.column,
+            byte_range: span.byte_range(),
+        }
+    }
+
+    // The body of a node as stored on it: its original text from the current file and,
+    // if `normalized_bodies` is set, its token-string form. `None` when bodies are excluded.
+    fn body(&self, node: &impl ToTokens) -> (Option<String>, Option<String>) {
+        self.body_at(node.span(), node.to_token_stream().to_string())
+    }
+
+    // Like `body`, for text that is not a single syntax node
+    fn body_at(
+        &self,
+        span: proc_macro2::Span,
+        normalized: String,
+    ) -> (Option<String>, Option<String>) {
+        let text = self
+            .current_source
+            .get(span.byte_range())
+            .filter(|text| !text.is_empty())
+            .map_or_else(|| normalized.clone(), str::to_string);
+
+        let normalized = if self.options.normalized_bodies {
+            self.options.body(normalized)
+        } else {
+            None
+        };
+        (self.options.body(text), normalized)
+    }
+
     fn extract_attributes(&self, attrs: &[syn::Attribute]) -> Vec<Attribute> {
         attrs
             .iter()
@@ -555,6 +1477,104 @@ impl VisitorState {
     }
 }
 
+// Segments of a path, without generic arguments
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|seg| seg.ident.to_string())
+        .collect()
+}
+
+// Path of the type methods are looked up in for receivers of type `ty`. References are
+// auto-dereferenced, and trait objects and `impl Trait` have the methods of their trait.
+fn receiver_type(ty: &Type) -> Option<Vec<String>> {
+    match ty {
+        Type::Reference(reference) => receiver_type(&reference.elem),
+        Type::Paren(paren) => receiver_type(&paren.elem),
+        Type::Path(type_path) if type_path.qself.is_none() => Some(path_segments(&type_path.path)),
+        Type::TraitObject(syn::TypeTraitObject { bounds, .. })
+        | Type::ImplTrait(syn::TypeImplTrait { bounds, .. }) => {
+            bounds.iter().find_map(|bound| match bound {
+                syn::TypeParamBound::Trait(bound) => Some(path_segments(&bound.path)),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+// What the receiver expression of a method call is, given the bindings in scope
+fn receiver(expr: &syn::Expr, bindings: &HashMap<String, Option<Vec<String>>>) -> Receiver {
+    match expr {
+        syn::Expr::Path(expr) if expr.qself.is_none() => match expr.path.get_ident() {
+            Some(ident) if ident == "self" => Receiver::SelfValue,
+            Some(ident) => bindings
+                .get(&ident.to_string())
+                .and_then(Option::as_ref)
+                .map_or(Receiver::Unknown, |path| Receiver::Typed(path.clone())),
+            None => Receiver::Unknown,
+        },
+        syn::Expr::Field(field) => match (&field.member, receiver(&field.base, bindings)) {
+            (_, Receiver::Unknown) | (syn::Member::Unnamed(_), _) => Receiver::Unknown,
+            (syn::Member::Named(name), base) => Receiver::Field(Box::new(base), name.to_string()),
+        },
+        syn::Expr::Struct(expr) if expr.qself.is_none() => {
+            Receiver::Typed(path_segments(&expr.path))
+        }
+        syn::Expr::Reference(syn::ExprReference { expr, .. })
+        | syn::Expr::Paren(syn::ExprParen { expr, .. })
+        | syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Deref(_),
+            expr,
+            ..
+        }) => receiver(expr, bindings),
+        _ => Receiver::Unknown,
+    }
+}
+
+// Standard macros whose arguments are expressions separated by commas
+const EXPR_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "dbg",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "format_args",
+    "panic",
+    "print",
+    "println",
+    "todo",
+    "unimplemented",
+    "unreachable",
+    "vec",
+    "write",
+    "writeln",
+];
+
+// Arguments of an invocation of one of `EXPR_MACROS`, including both operands of
+// `vec![elem; n]`. `None` for other macros and arguments that are not expressions.
+fn macro_arguments(mac: &syn::Macro) -> Option<Punctuated<syn::Expr, syn::Token![,]>> {
+    let name = mac.path.segments.last()?.ident.to_string();
+    if !EXPR_MACROS.contains(&name.as_str()) {
+        return None;
+    }
+    let repeat = |input: syn::parse::ParseStream| {
+        let mut args = Punctuated::new();
+        args.push(input.parse()?);
+        input.parse::<syn::Token![;]>()?;
+        args.push(input.parse()?);
+        Ok(args)
+    };
+    mac.parse_body_with(Punctuated::parse_terminated)
+        .or_else(|_| mac.parse_body_with(repeat))
+        .ok()
+}
+
 // Visitor implementation
 struct CodeVisitor<'a> {
     state: &'a mut VisitorState,
@@ -565,35 +1585,215 @@ impl<'a> CodeVisitor<'a> {
         Self { state }
     }
 
-    // Helper method to extract path segments from a use tree
-    fn extract_use_path(use_tree: &syn::UseTree, path_segments: &mut Vec<String>) {
-        match use_tree {
-            syn::UseTree::Path(path) => {
-                path_segments.push(path.ident.to_string());
-                CodeVisitor::extract_use_path(&path.tree, path_segments);
-            }
-            syn::UseTree::Name(name) => {
-                path_segments.push(name.ident.to_string());
+    // Visit the items of a module body, recording the nodes they produce as the
+    // module's items and submodules
+    fn visit_module_items(&mut self, module_id: NodeId, mod_items: &[syn::Item]) {
+        let parent_module = std::mem::replace(&mut self.state.current_module, module_id);
+        let mut submodules = Vec::new();
+        let mut items = Vec::new();
+        let mut imports = Vec::new();
+
+        for item in mod_items {
+            if !self.state.options.include_tests && is_test_only(item_attributes(item)) {
+                continue;
             }
-            syn::UseTree::Rename(rename) => {
-                path_segments.push(format!("{} as {}", rename.ident, rename.rename));
+
+            // Items declared inside an `extern` block belong to the enclosing module too
+            let mut foreign_items = Vec::new();
+
+            let item_id = match item {
+                syn::Item::Fn(func) => self.record_fn(func),
+                syn::Item::Struct(strct) => self.record_struct(strct),
+                syn::Item::Enum(enm) => self.record_enum(enm),
+                syn::Item::Type(type_alias) => self.record_type_alias(type_alias),
+                syn::Item::Union(union_def) => self.record_union(union_def),
+                syn::Item::Impl(impl_block) => self.record_impl(impl_block),
+                syn::Item::Trait(trt) => self.record_trait(trt),
+                syn::Item::TraitAlias(item_alias) => self.record_trait_alias(item_alias),
+                syn::Item::Const(item_const) => self.record_const(item_const),
+                syn::Item::Static(item_static) => self.record_static(item_static),
+                syn::Item::Macro(item_macro) => self.record_macro_rules(item_macro),
+                syn::Item::Mod(md) => {
+                    let submodule_id = self.record_module(md);
+                    submodules.extend(submodule_id);
+                    submodule_id
+                }
+                syn::Item::Use(use_item) => {
+                    imports.extend(self.state.process_use(use_item));
+                    None
+                }
+                syn::Item::ExternCrate(extern_crate) => {
+                    imports.push(self.state.process_extern_crate(extern_crate));
+                    None
+                }
+                syn::Item::ForeignMod(foreign_mod) => {
+                    let (foreign_mod_id, declared) = self.record_foreign_mod(foreign_mod);
+                    foreign_items = declared;
+                    Some(foreign_mod_id)
+                }
+                _ => {
+                    visit::visit_item(self, item);
+                    None
+                }
+            };
+
+            for id in item_id.into_iter().chain(foreign_items) {
+                items.push(id);
+                // Add "Contains" relation between the module and its item
+                self.state.code_graph.relations.push(Relation {
+                    source: module_id,
+                    target: id,
+                    kind: RelationKind::Contains,
+                });
             }
-            syn::UseTree::Glob(_) => {
-                path_segments.push("*".to_string());
+        }
+
+        // Add "Uses" relations between the module and its imports
+        for import in &imports {
+            self.state.code_graph.relations.push(Relation {
+                source: module_id,
+                target: import.id,
+                kind: RelationKind::Uses,
+            });
+        }
+
+        if let Some(module) = self.state.module_mut(module_id) {
+            module.items = items;
+            module.submodules = submodules;
+            module.imports = imports;
+        }
+        self.state.current_module = parent_module;
+    }
+
+    // Record a function declared in an `extern` block, if it passes the visibility filter
+    fn visit_foreign_fn(&mut self, func: &syn::ForeignItemFn) -> Option<NodeId> {
+        if !self.state.options.allows_visibility(&func.vis) {
+            return None;
+        }
+
+        let fn_id = self.state.item_id("fn", &func.sig.ident.to_string());
+
+        // Process function parameters
+        let mut parameters = Vec::new();
+        for arg in &func.sig.inputs {
+            if let Some(param) = self.state.process_fn_arg(fn_id, arg) {
+                self.state.code_graph.relations.push(Relation {
+                    source: fn_id,
+                    target: param.id,
+                    kind: RelationKind::FunctionParameter,
+                });
+                parameters.push(param);
             }
-            syn::UseTree::Group(group) => {
-                for tree in &group.items {
-                    let mut new_path = path_segments.clone();
-                    CodeVisitor::extract_use_path(tree, &mut new_path);
-                }
+        }
+
+        // Extract return type if it exists
+        let return_type = match &func.sig.output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) => {
+                let type_id = self.state.get_or_create_type(ty);
+                self.state.code_graph.relations.push(Relation {
+                    source: fn_id,
+                    target: type_id,
+                    kind: RelationKind::FunctionReturn,
+                });
+                Some(type_id)
             }
+        };
+
+        let generic_params = self.state.process_generics(fn_id, &func.sig.generics);
+        let docstring = self.state.extract_docstring(&func.attrs);
+        let attributes = self.state.extract_attributes(&func.attrs);
+
+        // Foreign functions have no body
+        self.state.code_graph.functions.push(FunctionNode {
+            id: fn_id,
+            name: func.sig.ident.to_string(),
+            path: self.state.item_path(&func.sig.ident.to_string()),
+            visibility: self.state.convert_visibility(&func.vis),
+            parameters,
+            return_type,
+            generic_params,
+            attributes,
+            docstring,
+            body: None,
+            body_normalized: None,
+            is_foreign: true,
+            span: self.state.span(func),
+        });
+        Some(fn_id)
+    }
+
+    // Record a static declared in an `extern` block, if it passes the visibility filter
+    fn visit_foreign_static(&mut self, item_static: &syn::ForeignItemStatic) -> Option<NodeId> {
+        if !self.state.options.allows_visibility(&item_static.vis) {
+            return None;
         }
+
+        let static_id = self.state.item_id("static", &item_static.ident.to_string());
+        let type_id = self.state.get_or_create_type(&item_static.ty);
+        let docstring = self.state.extract_docstring(&item_static.attrs);
+        let attributes = self.state.extract_attributes(&item_static.attrs);
+
+        self.state.code_graph.values.push(ValueNode {
+            id: static_id,
+            name: item_static.ident.to_string(),
+            path: self.state.item_path(&item_static.ident.to_string()),
+            visibility: self.state.convert_visibility(&item_static.vis),
+            type_id,
+            kind: ValueKind::ForeignStatic {
+                is_mutable: matches!(item_static.mutability, syn::StaticMutability::Mut(_)),
+            },
+            value: None,
+            value_normalized: None,
+            attributes,
+            docstring,
+            span: self.state.span(item_static),
+        });
+
+        // Add relation between static and its type
+        self.state.code_graph.relations.push(Relation {
+            source: static_id,
+            target: type_id,
+            kind: RelationKind::ValueType,
+        });
+        Some(static_id)
     }
-}
 
-impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
-    // Visit function definitions
-    fn visit_item_fn(&mut self, func: &'ast ItemFn) {
+    // Record an opaque type declared in an `extern` block, if it passes the visibility filter
+    fn visit_foreign_type(&mut self, item_type: &syn::ForeignItemType) -> Option<NodeId> {
+        if !self.state.options.allows_visibility(&item_type.vis) {
+            return None;
+        }
+
+        let type_id = self
+            .state
+            .item_id("foreign_type", &item_type.ident.to_string());
+        let docstring = self.state.extract_docstring(&item_type.attrs);
+        let attributes = self.state.extract_attributes(&item_type.attrs);
+
+        self.state
+            .code_graph
+            .defined_types
+            .push(TypeDefNode::ForeignType(ForeignTypeNode {
+                id: type_id,
+                name: item_type.ident.to_string(),
+                path: self.state.item_path(&item_type.ident.to_string()),
+                visibility: self.state.convert_visibility(&item_type.vis),
+                attributes,
+                docstring,
+                span: self.state.span(item_type),
+            }));
+        Some(type_id)
+    }
+
+    // Record a function definition, or a procedural macro along with its function
+    fn record_fn(&mut self, func: &ItemFn) -> Option<NodeId> {
+        // Skip functions that don't pass the visibility filter before allocating any
+        // IDs or relations for them
+        if !self.state.options.allows_visibility(&func.vis) {
+            return None;
+        }
+
         // Check if this function is a procedural macro
         let is_proc_macro = func.attrs.iter().any(|attr| {
             attr.path().is_ident("proc_macro")
@@ -602,8 +1802,8 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         });
 
         if is_proc_macro {
-            let macro_id = self.state.next_node_id();
             let macro_name = func.sig.ident.to_string();
+            let macro_id = self.state.item_id("macro", &macro_name);
 
             // Determine the kind of procedural macro
             let proc_macro_kind = if func
@@ -626,12 +1826,13 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             let docstring = self.state.extract_docstring(&func.attrs);
             let attributes = self.state.extract_attributes(&func.attrs);
 
-            // Extract function body as a string
-            let body = Some(func.block.to_token_stream().to_string());
+            // Extract the function body's source text
+            let (body, body_normalized) = self.state.body(&func.block);
 
             // Create the macro node
             let macro_node = MacroNode {
                 id: macro_id,
+                path: self.state.item_path(&macro_name),
                 name: macro_name,
                 visibility: self.state.convert_visibility(&func.vis),
                 kind: MacroKind::ProcedureMacro {
@@ -641,19 +1842,21 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 attributes,
                 docstring,
                 body,
+                body_normalized,
+                span: self.state.span(func),
             };
 
             // Add the macro to the code graph
             self.state.code_graph.macros.push(macro_node);
         }
 
-        let fn_id = self.state.next_node_id();
         let fn_name = func.sig.ident.to_string();
+        let fn_id = self.state.item_id("fn", &fn_name);
 
         // Process function parameters
         let mut parameters = Vec::new();
         for arg in &func.sig.inputs {
-            if let Some(param) = self.state.process_fn_arg(arg) {
+            if let Some(param) = self.state.process_fn_arg(fn_id, arg) {
                 // Add relation between function and parameter
                 self.state.code_graph.relations.push(Relation {
                     source: fn_id,
@@ -680,42 +1883,280 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         };
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&func.sig.generics);
+        let generic_params = self.state.process_generics(fn_id, &func.sig.generics);
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&func.attrs);
         let attributes = self.state.extract_attributes(&func.attrs);
 
-        // Extract function body as a string
-        let body = Some(func.block.to_token_stream().to_string());
+        // Extract the function body's source text
+        let (body, body_normalized) = self.state.body(&func.block);
+
+        // Store function info
+        self.state.code_graph.functions.push(FunctionNode {
+            id: fn_id,
+            path: self.state.item_path(&fn_name),
+            name: fn_name,
+            visibility: self.state.convert_visibility(&func.vis),
+            parameters,
+            return_type,
+            generic_params,
+            attributes,
+            docstring,
+            body,
+            body_normalized,
+            is_foreign: false,
+            span: self.state.span(func),
+        });
+
+        // Continue visiting the function body, attributing the calls in it to the function
+        let outer_fn = self.state.current_fn.replace(fn_id);
+        let outer_bindings = std::mem::take(&mut self.state.bindings);
+        visit::visit_item_fn(self, func);
+        self.state.current_fn = outer_fn;
+        self.state.bindings = outer_bindings;
+        Some(fn_id)
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
+    // Items are recorded by the `record_*` methods, which return the ID of the node they
+    // add so that `visit_module_items` can list it among the module's items
+    fn visit_item_fn(&mut self, func: &'ast ItemFn) {
+        self.record_fn(func);
+    }
+
+    fn visit_item_struct(&mut self, item_struct: &'ast ItemStruct) {
+        self.record_struct(item_struct);
+    }
+
+    fn visit_item_type(&mut self, item_type: &'ast syn::ItemType) {
+        self.record_type_alias(item_type);
+    }
+
+    fn visit_item_union(&mut self, item_union: &'ast syn::ItemUnion) {
+        self.record_union(item_union);
+    }
+
+    fn visit_item_enum(&mut self, item_enum: &'ast ItemEnum) {
+        self.record_enum(item_enum);
+    }
+
+    fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
+        self.record_impl(item_impl);
+    }
+
+    fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+        self.record_trait(item_trait);
+    }
+
+    fn visit_item_trait_alias(&mut self, item_alias: &'ast syn::ItemTraitAlias) {
+        self.record_trait_alias(item_alias);
+    }
+
+    fn visit_item_mod(&mut self, module: &'ast ItemMod) {
+        self.record_module(module);
+    }
+
+    fn visit_item_const(&mut self, item_const: &'ast syn::ItemConst) {
+        self.record_const(item_const);
+    }
+
+    fn visit_item_static(&mut self, item_static: &'ast syn::ItemStatic) {
+        self.record_static(item_static);
+    }
+
+    fn visit_item_foreign_mod(&mut self, foreign_mod: &'ast syn::ItemForeignMod) {
+        self.record_foreign_mod(foreign_mod);
+    }
+
+    fn visit_item_macro(&mut self, item_macro: &'ast syn::ItemMacro) {
+        self.record_macro_rules(item_macro);
+    }
+
+    // Imports in module bodies are recorded by `visit_module_items`, so the ones visited
+    // here are nested in function bodies or other blocks
+    fn visit_item_use(&mut self, use_item: &'ast syn::ItemUse) {
+        let imports = self.state.process_use(use_item);
+        self.state.add_block_imports(imports);
+    }
+
+    fn visit_item_extern_crate(&mut self, extern_crate: &'ast syn::ItemExternCrate) {
+        let import = self.state.process_extern_crate(extern_crate);
+        self.state.add_block_imports(vec![import]);
+    }
+
+    fn visit_impl_item_fn(&mut self, method: &'ast syn::ImplItemFn) {
+        let method_id = self.state.method_ids.get(&method.sig.ident.to_string());
+        let outer_fn = std::mem::replace(&mut self.state.current_fn, method_id.copied());
+        let outer_bindings = std::mem::take(&mut self.state.bindings);
+        visit::visit_impl_item_fn(self, method);
+        self.state.current_fn = outer_fn;
+        self.state.bindings = outer_bindings;
+    }
+
+    fn visit_trait_item_fn(&mut self, method: &'ast syn::TraitItemFn) {
+        let method_id = self.state.method_ids.get(&method.sig.ident.to_string());
+        let outer_fn = std::mem::replace(&mut self.state.current_fn, method_id.copied());
+        let outer_bindings = std::mem::take(&mut self.state.bindings);
+        visit::visit_trait_item_fn(self, method);
+        self.state.current_fn = outer_fn;
+        self.state.bindings = outer_bindings;
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        match &*call.func {
+            // Calls of closures and functions held by local bindings are not recorded
+            syn::Expr::Path(func) if !self.state.is_local(&func.path) => {
+                let span = self.state.span(call);
+                self.state
+                    .add_call(CallKind::Function, path_segments(&func.path), None, span);
+                // Skip the path itself, which is not a reference to the function
+                for arg in &call.args {
+                    self.visit_expr(arg);
+                }
+            }
+            // Calls of closures and of functions returned by other expressions
+            _ => visit::visit_expr_call(self, call),
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let span = self.state.span(call);
+        let receiver = receiver(&call.receiver, &self.state.bindings);
+        let path = vec![call.method.to_string()];
+        self.state
+            .add_call(CallKind::Method, path, Some(receiver), span);
+        visit::visit_expr_method_call(self, call);
+    }
+
+    // Parameters with a type bring a binding of that type into scope
+    fn visit_fn_arg(&mut self, arg: &'ast FnArg) {
+        visit::visit_fn_arg(self, arg);
+        if let FnArg::Typed(pat_type) = arg {
+            self.state.bind_typed(pat_type);
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        // The initializer is evaluated before the names it binds are in scope
+        if let Some(init) = &local.init {
+            self.visit_local_init(init);
+        }
+        self.visit_pat(&local.pat);
+        if let Pat::Type(pat_type) = &local.pat {
+            self.state.bind_typed(pat_type);
+        }
+    }
+
+    // Names bound by patterns shadow earlier bindings and the items of the module. Their
+    // types are not known unless written down.
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        self.state.bindings.insert(pat.ident.to_string(), None);
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        let outer_bindings = self.state.bindings.clone();
+        for input in &closure.inputs {
+            self.visit_pat(input);
+            if let Pat::Type(pat_type) = input {
+                self.state.bind_typed(pat_type);
+            }
+        }
+        self.visit_expr(&closure.body);
+        self.state.bindings = outer_bindings;
+    }
+
+    // Bindings go out of scope at the end of their block, match arm or loop
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        let outer_bindings = self.state.bindings.clone();
+        visit::visit_block(self, block);
+        self.state.bindings = outer_bindings;
+    }
+
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        let outer_bindings = self.state.bindings.clone();
+        visit::visit_arm(self, arm);
+        self.state.bindings = outer_bindings;
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast syn::ExprForLoop) {
+        let outer_bindings = self.state.bindings.clone();
+        visit::visit_expr_for_loop(self, for_loop);
+        self.state.bindings = outer_bindings;
+    }
+
+    // Any path in a body may name a function, like `parse` in `iter.map(parse)`. Those
+    // that do not are dropped once names are resolved, except for local bindings, which
+    // are left out here as they would resolve to items of the same name.
+    fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+        if expr.qself.is_some() || !self.state.is_local(&expr.path) {
+            let span = self.state.span(expr);
+            self.state
+                .add_call(CallKind::Reference, path_segments(&expr.path), None, span);
+        }
+        visit::visit_expr_path(self, expr);
+    }
+
+    // Visit macro invocations
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Get the macro name
+        let macro_path = mac.path.to_token_stream().to_string();
+
+        // Invocations get IDs inside their function (or module, outside of functions),
+        // so that edits elsewhere in the file don't renumber them
+        let parent = self.state.current_fn.unwrap_or(self.state.current_module);
+        let invocation_id = self.state.child_id(parent, "macro_call", &macro_path);
+
+        // The macro may be defined in another file, so invocations are linked to their
+        // definitions once every file has been visited, see `resolve_names`
+        let invocation = MacroInvocationNode {
+            id: invocation_id,
+            path: macro_path,
+            macro_id: None,
+            span: self.state.span(mac),
+        };
+        self.state.code_graph.macro_invocations.push(invocation);
 
-        // Store function info
-        self.state.code_graph.functions.push(FunctionNode {
-            id: fn_id,
-            name: fn_name,
-            visibility: self.state.convert_visibility(&func.vis),
-            parameters,
-            return_type,
-            generic_params,
-            attributes,
-            docstring,
-            body,
-        });
+        // Continue visiting
+        visit::visit_macro(self, mac);
 
-        // Continue visiting the function body
-        visit::visit_item_fn(self, func);
+        // syn leaves the tokens of macros alone, but the arguments of some standard
+        // macros are expressions, which may contain calls
+        if let Some(args) = macro_arguments(mac) {
+            for arg in &args {
+                match arg {
+                    // Named arguments of format strings, like `width = size(x)`
+                    syn::Expr::Assign(assign) => self.visit_expr(&assign.right),
+                    _ => self.visit_expr(arg),
+                }
+            }
+        }
     }
+}
+
+impl<'a> CodeVisitor<'a> {
+    // Record struct definitions
+    fn record_struct(&mut self, item_struct: &ItemStruct) -> Option<NodeId> {
+        // Skip structs that don't pass the visibility filter before allocating any
+        // IDs or relations for them
+        if !self.state.options.allows_visibility(&item_struct.vis) {
+            return None;
+        }
 
-    // Visit struct definitions
-    fn visit_item_struct(&mut self, item_struct: &'ast ItemStruct) {
-        let struct_id = self.state.next_node_id();
         let struct_name = item_struct.ident.to_string();
+        let struct_id = self.state.item_id("struct", &struct_name);
 
         // Process fields
         let mut fields = Vec::new();
-        for field in &item_struct.fields {
-            let field_id = self.state.next_node_id();
+        for (i, field) in item_struct.fields.iter().enumerate() {
             let field_name = field.ident.as_ref().map(|ident| ident.to_string());
+            let field_id = self.state.child_id(
+                struct_id,
+                "field",
+                field_name.as_deref().unwrap_or(&i.to_string()),
+            );
             let type_id = self.state.get_or_create_type(&field.ty);
 
             let field_node = FieldNode {
@@ -724,6 +2165,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 type_id,
                 visibility: self.state.convert_visibility(&field.vis),
                 attributes: self.state.extract_attributes(&field.attrs),
+                span: self.state.span(field),
             };
 
             // Add relation between struct and field
@@ -737,75 +2179,97 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         }
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&item_struct.generics);
+        let generic_params = self
+            .state
+            .process_generics(struct_id, &item_struct.generics);
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_struct.attrs);
         let attributes = self.state.extract_attributes(&item_struct.attrs);
 
-        // Store struct info only if public
-        if matches!(item_struct.vis, Visibility::Public(_)) {
-            self.state
-                .code_graph
-                .defined_types
-                .push(TypeDefNode::Struct(StructNode {
-                    id: struct_id,
-                    name: struct_name,
-                    visibility: self.state.convert_visibility(&item_struct.vis),
-                    fields,
-                    generic_params,
-                    attributes,
-                    docstring,
-                }));
+        // Store struct info
+        self.state
+            .code_graph
+            .defined_types
+            .push(TypeDefNode::Struct(StructNode {
+                id: struct_id,
+                path: self.state.item_path(&struct_name),
+                name: struct_name,
+                visibility: self.state.convert_visibility(&item_struct.vis),
+                fields,
+                generic_params,
+                attributes,
+                docstring,
+                span: self.state.span(item_struct),
+            }));
 
-            visit::visit_item_struct(self, item_struct);
-        }
+        visit::visit_item_struct(self, item_struct);
+        Some(struct_id)
     }
 
-    // Visit type alias definitions
-    fn visit_item_type(&mut self, item_type: &'ast syn::ItemType) {
-        let type_alias_id = self.state.next_node_id();
+    // Record type alias definitions
+    fn record_type_alias(&mut self, item_type: &syn::ItemType) -> Option<NodeId> {
+        // Skip type aliases that don't pass the visibility filter before allocating any
+        // IDs or relations for them
+        if !self.state.options.allows_visibility(&item_type.vis) {
+            return None;
+        }
+
+        let type_alias_id = self
+            .state
+            .item_id("type_alias", &item_type.ident.to_string());
         let type_alias_name = item_type.ident.to_string();
 
         // Process the aliased type
         let type_id = self.state.get_or_create_type(&item_type.ty);
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&item_type.generics);
+        let generic_params = self
+            .state
+            .process_generics(type_alias_id, &item_type.generics);
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_type.attrs);
         let attributes = self.state.extract_attributes(&item_type.attrs);
 
-        // Store type alias info only if public
-        if matches!(item_type.vis, Visibility::Public(_)) {
-            self.state
-                .code_graph
-                .defined_types
-                .push(TypeDefNode::TypeAlias(TypeAliasNode {
-                    id: type_alias_id,
-                    name: type_alias_name,
-                    visibility: self.state.convert_visibility(&item_type.vis),
-                    type_id,
-                    generic_params,
-                    attributes,
-                    docstring,
-                }));
+        // Store type alias info
+        self.state
+            .code_graph
+            .defined_types
+            .push(TypeDefNode::TypeAlias(TypeAliasNode {
+                id: type_alias_id,
+                path: self.state.item_path(&type_alias_name),
+                name: type_alias_name,
+                visibility: self.state.convert_visibility(&item_type.vis),
+                type_id,
+                generic_params,
+                attributes,
+                docstring,
+                span: self.state.span(item_type),
+            }));
 
-            visit::visit_item_type(self, item_type);
-        }
+        visit::visit_item_type(self, item_type);
+        Some(type_alias_id)
     }
 
-    // Visit union definitions
-    fn visit_item_union(&mut self, item_union: &'ast syn::ItemUnion) {
-        let union_id = self.state.next_node_id();
+    // Record union definitions
+    fn record_union(&mut self, item_union: &syn::ItemUnion) -> Option<NodeId> {
+        // Skip unions that don't pass the visibility filter before allocating any
+        // IDs or relations for them
+        if !self.state.options.allows_visibility(&item_union.vis) {
+            return None;
+        }
+
         let union_name = item_union.ident.to_string();
+        let union_id = self.state.item_id("union", &union_name);
 
         // Process fields
         let mut fields = Vec::new();
         for field in &item_union.fields.named {
-            let field_id = self.state.next_node_id();
             let field_name = field.ident.as_ref().map(|ident| ident.to_string());
+            let field_id =
+                self.state
+                    .child_id(union_id, "field", field_name.as_deref().unwrap_or_default());
             let type_id = self.state.get_or_create_type(&field.ty);
 
             let field_node = FieldNode {
@@ -814,6 +2278,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 type_id,
                 visibility: self.state.convert_visibility(&field.vis),
                 attributes: self.state.extract_attributes(&field.attrs),
+                span: self.state.span(field),
             };
 
             // Add relation between union and field
@@ -827,49 +2292,60 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         }
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&item_union.generics);
+        let generic_params = self.state.process_generics(union_id, &item_union.generics);
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_union.attrs);
         let attributes = self.state.extract_attributes(&item_union.attrs);
 
-        // Store union info only if public
-        if matches!(item_union.vis, Visibility::Public(_)) {
-            self.state
-                .code_graph
-                .defined_types
-                .push(TypeDefNode::Union(UnionNode {
-                    id: union_id,
-                    name: union_name,
-                    visibility: self.state.convert_visibility(&item_union.vis),
-                    fields,
-                    generic_params,
-                    attributes,
-                    docstring,
-                }));
+        // Store union info
+        self.state
+            .code_graph
+            .defined_types
+            .push(TypeDefNode::Union(UnionNode {
+                id: union_id,
+                path: self.state.item_path(&union_name),
+                name: union_name,
+                visibility: self.state.convert_visibility(&item_union.vis),
+                fields,
+                generic_params,
+                attributes,
+                docstring,
+                span: self.state.span(item_union),
+            }));
 
-            visit::visit_item_union(self, item_union);
-        }
+        visit::visit_item_union(self, item_union);
+        Some(union_id)
     }
 
-    // Visit enum definitions
-    fn visit_item_enum(&mut self, item_enum: &'ast ItemEnum) {
-        let enum_id = self.state.next_node_id();
+    // Record enum definitions
+    fn record_enum(&mut self, item_enum: &ItemEnum) -> Option<NodeId> {
+        // Skip enums that don't pass the visibility filter before allocating any
+        // IDs or relations for them
+        if !self.state.options.allows_visibility(&item_enum.vis) {
+            return None;
+        }
+
         let enum_name = item_enum.ident.to_string();
+        let enum_id = self.state.item_id("enum", &enum_name);
 
         // Process variants
         let mut variants = Vec::new();
         for variant in &item_enum.variants {
-            let variant_id = self.state.next_node_id();
             let variant_name = variant.ident.to_string();
+            let variant_id = self.state.child_id(enum_id, "variant", &variant_name);
 
             // Process fields of the variant
             let mut fields = Vec::new();
             match &variant.fields {
                 syn::Fields::Named(fields_named) => {
                     for field in &fields_named.named {
-                        let field_id = self.state.next_node_id();
                         let field_name = field.ident.as_ref().map(|ident| ident.to_string());
+                        let field_id = self.state.child_id(
+                            variant_id,
+                            "field",
+                            field_name.as_deref().unwrap_or_default(),
+                        );
                         let type_id = self.state.get_or_create_type(&field.ty);
 
                         let field_node = FieldNode {
@@ -878,14 +2354,15 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                             type_id,
                             visibility: self.state.convert_visibility(&field.vis),
                             attributes: self.state.extract_attributes(&field.attrs),
+                            span: self.state.span(field),
                         };
 
                         fields.push(field_node);
                     }
                 }
                 syn::Fields::Unnamed(fields_unnamed) => {
-                    for (_, field) in fields_unnamed.unnamed.iter().enumerate() {
-                        let field_id = self.state.next_node_id();
+                    for (i, field) in fields_unnamed.unnamed.iter().enumerate() {
+                        let field_id = self.state.child_id(variant_id, "field", &i.to_string());
                         let type_id = self.state.get_or_create_type(&field.ty);
 
                         let field_node = FieldNode {
@@ -894,6 +2371,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                             type_id,
                             visibility: self.state.convert_visibility(&field.vis),
                             attributes: self.state.extract_attributes(&field.attrs),
+                            span: self.state.span(field),
                         };
 
                         fields.push(field_node);
@@ -916,6 +2394,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 fields,
                 discriminant,
                 attributes: self.state.extract_attributes(&variant.attrs),
+                span: self.state.span(variant),
             };
 
             // Add relation between enum and variant
@@ -929,34 +2408,44 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         }
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&item_enum.generics);
+        let generic_params = self.state.process_generics(enum_id, &item_enum.generics);
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_enum.attrs);
         let attributes = self.state.extract_attributes(&item_enum.attrs);
 
-        // Store enum info only if public
-        if matches!(item_enum.vis, Visibility::Public(_)) {
-            self.state
-                .code_graph
-                .defined_types
-                .push(TypeDefNode::Enum(EnumNode {
-                    id: enum_id,
-                    name: enum_name,
-                    visibility: self.state.convert_visibility(&item_enum.vis),
-                    variants,
-                    generic_params,
-                    attributes,
-                    docstring,
-                }));
+        // Store enum info
+        self.state
+            .code_graph
+            .defined_types
+            .push(TypeDefNode::Enum(EnumNode {
+                id: enum_id,
+                path: self.state.item_path(&enum_name),
+                name: enum_name,
+                visibility: self.state.convert_visibility(&item_enum.vis),
+                variants,
+                generic_params,
+                attributes,
+                docstring,
+                span: self.state.span(item_enum),
+            }));
 
-            visit::visit_item_enum(self, item_enum);
-        }
+        visit::visit_item_enum(self, item_enum);
+        Some(enum_id)
     }
 
-    // Visit impl blocks
-    fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
-        let impl_id = self.state.next_node_id();
+    // Record impl blocks
+    fn record_impl(&mut self, item_impl: &ItemImpl) -> Option<NodeId> {
+        // Impls have no name, so they are identified by what they implement
+        let impl_name = match &item_impl.trait_ {
+            Some((_, path, _)) => format!(
+                "impl {} for {}",
+                path.to_token_stream(),
+                item_impl.self_ty.to_token_stream()
+            ),
+            None => format!("impl {}", item_impl.self_ty.to_token_stream()),
+        };
+        let impl_id = self.state.item_id("impl", &impl_name);
 
         // Process self type
         let self_type_id = self.state.get_or_create_type(&item_impl.self_ty);
@@ -967,52 +2456,39 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 qself: None,
                 path: path.clone(),
             });
-            let trait_id = self.state.get_or_create_type(&ty);
-            trait_id
+            self.state.get_or_create_type(&ty)
         });
 
-        // Skip impl blocks for non-public traits
-        if let Some(trait_type_id) = trait_type_id {
-            if let Some(trait_type) = self
-                .state
-                .code_graph
-                .type_graph
-                .iter()
-                .find(|t| t.id == trait_type_id)
-            {
-                if let TypeKind::Named { path, .. } = &trait_type.kind {
-                    let trait_name = path.last().unwrap_or(&String::new()).to_string();
-                    let trait_def = self
-                        .state
-                        .code_graph
-                        .traits
-                        .iter()
-                        .find(|t| t.name == trait_name);
-
-                    if let Some(trait_def) = trait_def {
-                        if !matches!(trait_def.visibility, VisibilityKind::Public) {
-                            // Skip this impl as the trait is not public
-                            return;
-                        }
-                    } else {
-                        // Trait definition not found, skip this impl
-                        return;
-                    }
-                }
-            }
-        }
-
-        // Process methods
+        // Process methods and associated items
         let mut methods = Vec::new();
+        let mut assoc_types = Vec::new();
+        let mut assoc_consts = Vec::new();
         for item in &item_impl.items {
-            if let syn::ImplItem::Fn(method) = item {
-                let method_node_id = self.state.next_node_id();
+            if let syn::ImplItem::Type(item_type) = item {
+                assoc_types.push(self.state.process_impl_assoc_type(impl_id, item_type));
+            } else if let syn::ImplItem::Const(item_const) = item {
+                assoc_consts.push(self.state.process_assoc_const(
+                    impl_id,
+                    &item_const.ident,
+                    &item_const.ty,
+                    Some(&item_const.expr),
+                    &item_const.attrs,
+                    self.state.span(item_const),
+                ));
+            } else if let syn::ImplItem::Fn(method) = item {
+                // Methods of trait impls are as visible as the trait, so only inherent
+                // methods are filtered
+                if item_impl.trait_.is_none() && !self.state.options.allows_visibility(&method.vis)
+                {
+                    continue;
+                }
                 let method_name = method.sig.ident.to_string();
+                let method_node_id = self.state.child_id(impl_id, "method", &method_name);
 
                 // Process method parameters
                 let mut parameters = Vec::new();
                 for arg in &method.sig.inputs {
-                    if let Some(param) = self.state.process_fn_arg(arg) {
+                    if let Some(param) = self.state.process_fn_arg(method_node_id, arg) {
                         // Add relation between method and parameter
                         self.state.code_graph.relations.push(Relation {
                             source: method_node_id,
@@ -1039,18 +2515,22 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 };
 
                 // Process generic parameters for methods
-                let generic_params = self.state.process_generics(&method.sig.generics);
+                let generic_params = self
+                    .state
+                    .process_generics(method_node_id, &method.sig.generics);
 
                 // Extract doc comments and other attributes for methods
                 let docstring = self.state.extract_docstring(&method.attrs);
                 let attributes = self.state.extract_attributes(&method.attrs);
 
-                // Extract method body as a string
-                let body = Some(method.block.to_token_stream().to_string());
+                // Extract the method body's source text
+                let (body, body_normalized) = self.state.body(&method.block);
 
                 // Store method info
                 let method_node = FunctionNode {
                     id: method_node_id,
+                    // Set once the types of the impl are resolved
+                    path: String::new(),
                     name: method_name,
                     visibility: self.state.convert_visibility(&method.vis),
                     parameters,
@@ -1059,13 +2539,16 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                     attributes,
                     docstring,
                     body,
+                    body_normalized,
+                    is_foreign: false,
+                    span: self.state.span(method),
                 };
                 methods.push(method_node);
             }
         }
 
         // Process generic parameters for impl block
-        let generic_params = self.state.process_generics(&item_impl.generics);
+        let generic_params = self.state.process_generics(impl_id, &item_impl.generics);
 
         // Store impl info
         let impl_node = ImplNode {
@@ -1074,7 +2557,18 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             trait_type: trait_type_id,
             methods,
             generic_params,
+            assoc_types,
+            assoc_consts,
+            // Traits may be defined after their impls, so they are resolved once the whole
+            // crate has been visited
+            trait_ref: None,
+            span: self.state.span(item_impl),
         };
+        let method_ids = impl_node
+            .methods
+            .iter()
+            .map(|method| (method.name.clone(), method.id))
+            .collect();
         self.state.code_graph.impls.push(impl_node);
 
         // Add relation: ImplementsFor or ImplementsTrait
@@ -1094,77 +2588,51 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 target: trait_type_id,
                 kind: RelationKind::ImplementsTrait,
             });
-
-            // Debug: Print trait type information
-            if let Some(trait_type) = self
-                .state
-                .code_graph
-                .type_graph
-                .iter()
-                .find(|t| t.id == trait_type_id)
-            {
-                if let TypeKind::Named { path, .. } = &trait_type.kind {
-                    println!("Found trait implementation: {:?}", path);
-                    // Specific check for DefaultTrait implementation
-                    if path.last().unwrap_or(&String::new()) == "DefaultTrait" {
-                        if let Some(self_type) = self
-                            .state
-                            .code_graph
-                            .type_graph
-                            .iter()
-                            .find(|t| t.id == self_type_id)
-                        {
-                            if let TypeKind::Named { path, .. } = &self_type.kind {
-                                println!("Self type for DefaultTrait: {:?}", path);
-                                if path.last().unwrap_or(&String::new()) == "ModuleStruct" {
-                                    println!("Found DefaultTrait implementation for ModuleStruct");
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Debug: Print self type information
-            if let Some(self_type) = self
-                .state
-                .code_graph
-                .type_graph
-                .iter()
-                .find(|t| t.id == self_type_id)
-            {
-                if let TypeKind::Named { path, .. } = &self_type.kind {
-                    println!("Self type: {:?}", path);
-                }
-            }
-
-            // Debug: Print all methods in the impl
-            if let Some(debug_impl) = &self.state.code_graph.impls.last() {
-                for method in &debug_impl.methods {
-                    println!("Found method {} in impl {}", method.name, impl_id);
-                }
-            }
         }
 
+        // Continue visiting the methods, see `visit_impl_item_fn`
+        let outer_methods = std::mem::replace(&mut self.state.method_ids, method_ids);
         visit::visit_item_impl(self, item_impl);
+        self.state.method_ids = outer_methods;
+        Some(impl_id)
     }
 
-    // Visit trait definitions
-    fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
-        let trait_id = self.state.next_node_id();
+    // Record trait definitions
+    fn record_trait(&mut self, item_trait: &ItemTrait) -> Option<NodeId> {
+        if !self.state.options.include_private_traits
+            && !matches!(item_trait.vis, Visibility::Public(_))
+        {
+            return None;
+        }
+
         let trait_name = item_trait.ident.to_string();
+        let trait_id = self.state.item_id("trait", &trait_name);
+        let trait_path = self.state.item_path(&trait_name);
 
-        // Process methods
+        // Process methods and associated items
         let mut methods = Vec::new();
+        let mut assoc_types = Vec::new();
+        let mut assoc_consts = Vec::new();
         for item in &item_trait.items {
-            if let syn::TraitItem::Fn(method) = item {
-                let method_node_id = self.state.next_node_id();
+            if let syn::TraitItem::Type(item_type) = item {
+                assoc_types.push(self.state.process_trait_assoc_type(trait_id, item_type));
+            } else if let syn::TraitItem::Const(item_const) = item {
+                assoc_consts.push(self.state.process_assoc_const(
+                    trait_id,
+                    &item_const.ident,
+                    &item_const.ty,
+                    item_const.default.as_ref().map(|(_, expr)| expr),
+                    &item_const.attrs,
+                    self.state.span(item_const),
+                ));
+            } else if let syn::TraitItem::Fn(method) = item {
                 let method_name = method.sig.ident.to_string();
+                let method_node_id = self.state.child_id(trait_id, "method", &method_name);
 
                 // Process method parameters
                 let mut parameters = Vec::new();
                 for arg in &method.sig.inputs {
-                    if let Some(param) = self.state.process_fn_arg(arg) {
+                    if let Some(param) = self.state.process_fn_arg(method_node_id, arg) {
                         // Add relation between method and parameter
                         self.state.code_graph.relations.push(Relation {
                             source: method_node_id,
@@ -1191,21 +2659,24 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 };
 
                 // Process generic parameters for methods
-                let generic_params = self.state.process_generics(&method.sig.generics);
+                let generic_params = self
+                    .state
+                    .process_generics(method_node_id, &method.sig.generics);
 
                 // Extract doc comments and other attributes for methods
                 let docstring = self.state.extract_docstring(&method.attrs);
                 let attributes = self.state.extract_attributes(&method.attrs);
 
                 // Extract method body if available (trait methods may have default implementations)
-                let body = method
-                    .default
-                    .as_ref()
-                    .map(|block| block.to_token_stream().to_string());
+                let (body, body_normalized) = match &method.default {
+                    Some(block) => self.state.body(block),
+                    None => (None, None),
+                };
 
                 // Store method info
                 let method_node = FunctionNode {
                     id: method_node_id,
+                    path: format!("{}::{}", trait_path, method_name),
                     name: method_name,
                     visibility: VisibilityKind::Public, // Trait methods are always public
                     parameters,
@@ -1214,41 +2685,47 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                     attributes,
                     docstring,
                     body,
+                    body_normalized,
+                    is_foreign: false,
+                    span: self.state.span(method),
                 };
                 methods.push(method_node);
             }
         }
 
         // Process generic parameters
-        let generic_params = self.state.process_generics(&item_trait.generics);
+        let generic_params = self.state.process_generics(trait_id, &item_trait.generics);
 
         // Process super traits
         let super_traits: Vec<TypeId> = item_trait
             .supertraits
             .iter()
-            .map(|bound| {
-                let ty = Type::TraitObject(syn::TypeTraitObject {
-                    dyn_token: None,
-                    bounds: syn::punctuated::Punctuated::from_iter(vec![bound.clone()]),
-                });
-                self.state.get_or_create_type(&ty)
-            })
+            .map(|bound| self.state.process_type_bound(bound))
             .collect();
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_trait.attrs);
         let attributes = self.state.extract_attributes(&item_trait.attrs);
 
+        let method_ids = methods
+            .iter()
+            .map(|method| (method.name.clone(), method.id))
+            .collect();
+
         // Store trait info
         let trait_node = TraitNode {
             id: trait_id,
             name: trait_name.clone(),
+            path: trait_path,
             visibility: self.state.convert_visibility(&item_trait.vis),
             methods,
             generic_params,
             super_traits: super_traits.clone(),
+            assoc_types,
+            assoc_consts,
             attributes,
             docstring,
+            span: self.state.span(item_trait),
         };
         self.state.code_graph.traits.push(trait_node);
         // }
@@ -1262,18 +2739,63 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             });
         }
 
+        // Continue visiting the default methods, see `visit_trait_item_fn`
+        let outer_methods = std::mem::replace(&mut self.state.method_ids, method_ids);
         visit::visit_item_trait(self, item_trait);
+        self.state.method_ids = outer_methods;
+        Some(trait_id)
     }
 
-    fn visit_item_mod(&mut self, module: &'ast ItemMod) {
-        // Extract module information
-        let module_id = self.state.next_node_id();
-        let module_name = module.ident.to_string();
+    // Record trait aliases
+    fn record_trait_alias(&mut self, item_alias: &syn::ItemTraitAlias) -> Option<NodeId> {
+        if !self.state.options.include_private_traits
+            && !matches!(item_alias.vis, Visibility::Public(_))
+        {
+            return None;
+        }
 
-        // Process inner items if available
-        let mut submodules = Vec::new();
-        let mut items = Vec::new();
+        let alias_id = self
+            .state
+            .item_id("trait_alias", &item_alias.ident.to_string());
+        let generic_params = self.state.process_generics(alias_id, &item_alias.generics);
+        let bounds: Vec<TypeId> = item_alias
+            .bounds
+            .iter()
+            .map(|bound| self.state.process_type_bound(bound))
+            .collect();
 
+        // Add relation for the aliased traits
+        for bound_id in &bounds {
+            self.state.code_graph.relations.push(Relation {
+                source: alias_id,
+                target: *bound_id,
+                kind: RelationKind::Inherits,
+            });
+        }
+
+        // Extract doc comments and other attributes
+        let docstring = self.state.extract_docstring(&item_alias.attrs);
+        let attributes = self.state.extract_attributes(&item_alias.attrs);
+
+        self.state.code_graph.trait_aliases.push(TraitAliasNode {
+            id: alias_id,
+            name: item_alias.ident.to_string(),
+            path: self.state.item_path(&item_alias.ident.to_string()),
+            visibility: self.state.convert_visibility(&item_alias.vis),
+            generic_params,
+            bounds,
+            attributes,
+            docstring,
+            span: self.state.span(item_alias),
+        });
+
+        visit::visit_item_trait_alias(self, item_alias);
+        Some(alias_id)
+    }
+
+    fn record_module(&mut self, module: &ItemMod) -> Option<NodeId> {
+        // Extract module information
+        let module_name = module.ident.to_string();
         // Determine module visibility
         // For private modules like 'mod private_module', we need to set Restricted visibility
         let visibility =
@@ -1284,290 +2806,74 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 self.state.convert_visibility(&module.vis)
             };
 
-        if let Some((_, mod_items)) = &module.content {
-            for item in mod_items {
-                let item_id = self.state.next_node_id();
-                items.push(item_id);
-
-                match item {
-                    syn::Item::Fn(func) => {
-                        self.visit_item_fn(func);
-                    }
-                    syn::Item::Struct(strct) => {
-                        self.visit_item_struct(strct);
-                    }
-                    syn::Item::Enum(enm) => {
-                        self.visit_item_enum(enm);
-                    }
-                    syn::Item::Impl(impl_block) => {
-                        self.visit_item_impl(impl_block);
-                    }
-                    syn::Item::Trait(trt) => {
-                        self.visit_item_trait(trt);
-                    }
-                    syn::Item::Type(type_alias) => {
-                        self.visit_item_type(type_alias);
-                    }
-                    syn::Item::Union(union_def) => {
-                        self.visit_item_union(union_def);
-                    }
-                    syn::Item::Mod(md) => {
-                        submodules.push(item_id); // Add to submodules
-                        self.visit_item_mod(md); // Recursive call
-                    }
-                    syn::Item::Use(use_item) => {
-                        self.visit_item_use(use_item);
-                    }
-                    syn::Item::ExternCrate(extern_crate) => {
-                        self.visit_item_extern_crate(extern_crate);
-                    }
-                    syn::Item::Const(item_const) => {
-                        self.visit_item_const(item_const);
-                    }
-                    syn::Item::Static(item_static) => {
-                        self.visit_item_static(item_static);
-                    }
-                    syn::Item::Macro(item_macro) => {
-                        self.visit_item_macro(item_macro);
-                    }
-                    // Add other item types as needed
-                    _ => {}
-                }
-            }
-        }
+        // Everything inside the module lives in the namespace of its path
+        self.state.module_path.push(module_name.clone());
+        let module_id = self.state.module_id();
 
-        // Add module to graph
-        self.state.code_graph.modules.push(ModuleNode {
+        // Add module to graph before its items, so it precedes its submodules
+        let attributes = self.state.extract_attributes(&module.attrs);
+        let docstring = self.state.extract_docstring(&module.attrs);
+        self.state.add_module(ModuleNode {
             id: module_id,
-            name: module_name,
+            name: module_name.clone(),
+            path: self.state.module_path.join("::"),
             visibility,
-            attributes: self.state.extract_attributes(&module.attrs),
-            docstring: self.state.extract_docstring(&module.attrs),
-            submodules,
-            items,
+            attributes,
+            docstring,
+            submodules: Vec::new(),
+            items: Vec::new(),
             imports: Vec::new(),
             exports: Vec::new(),
+            diagnostics: Vec::new(),
+            span: self.state.span(module),
         });
 
-        // Add "Contains" relations between the module and its items
-        if let Some((_, mod_items)) = &module.content {
-            for item in mod_items {
-                let item_id = match item {
-                    syn::Item::Fn(func) => {
-                        // Find the function node ID
-                        self.state
-                            .code_graph
-                            .functions
-                            .iter()
-                            .find(|f| f.name == func.sig.ident.to_string())
-                            .map(|f| f.id)
-                    }
-                    syn::Item::Struct(strct) => {
-                        // Find the struct node ID
-                        self.state
-                            .code_graph
-                            .defined_types
-                            .iter()
-                            .find(|def| match def {
-                                TypeDefNode::Struct(s) => s.name == strct.ident.to_string(),
-                                _ => false,
-                            })
-                            .map(|def| match def {
-                                TypeDefNode::Struct(s) => s.id,
-                                _ => 0, // Should never happen
-                            })
-                    }
-                    syn::Item::Enum(enm) => {
-                        // Find the enum node ID
-                        self.state
-                            .code_graph
-                            .defined_types
-                            .iter()
-                            .find(|def| match def {
-                                TypeDefNode::Enum(e) => e.name == enm.ident.to_string(),
-                                _ => false,
-                            })
-                            .map(|def| match def {
-                                TypeDefNode::Enum(e) => e.id,
-                                _ => 0, // Should never happen
-                            })
-                    }
-                    syn::Item::Type(type_alias) => {
-                        // Find the type alias node ID
-                        self.state
-                            .code_graph
-                            .defined_types
-                            .iter()
-                            .find(|def| match def {
-                                TypeDefNode::TypeAlias(ta) => {
-                                    ta.name == type_alias.ident.to_string()
-                                }
-                                _ => false,
-                            })
-                            .map(|def| match def {
-                                TypeDefNode::TypeAlias(ta) => ta.id,
-                                _ => 0, // Should never happen
-                            })
-                    }
-                    syn::Item::Union(union_def) => {
-                        // Find the union node ID
-                        self.state
-                            .code_graph
-                            .defined_types
-                            .iter()
-                            .find(|def| match def {
-                                TypeDefNode::Union(u) => u.name == union_def.ident.to_string(),
-                                _ => false,
-                            })
-                            .map(|def| match def {
-                                TypeDefNode::Union(u) => u.id,
-                                _ => 0, // Should never happen
-                            })
-                    }
-                    syn::Item::Trait(trt) => {
-                        // Find the trait node ID
-                        self.state
-                            .code_graph
-                            .traits
-                            .iter()
-                            .find(|t| t.name == trt.ident.to_string())
-                            .map(|t| t.id)
-                    }
-                    syn::Item::Const(item_const) => {
-                        // Find the constant node ID
-                        self.state
-                            .code_graph
-                            .values
-                            .iter()
-                            .find(|v| {
-                                v.name == item_const.ident.to_string()
-                                    && v.kind == ValueKind::Constant
-                            })
-                            .map(|v| v.id)
-                    }
-                    syn::Item::Static(item_static) => {
-                        // Find the static node ID
-                        self.state
-                            .code_graph
-                            .values
-                            .iter()
-                            .find(|v| {
-                                v.name == item_static.ident.to_string()
-                                    && matches!(v.kind, ValueKind::Static { .. })
-                            })
-                            .map(|v| v.id)
-                    }
-                    syn::Item::Macro(item_macro) => {
-                        // Find the macro node ID
-                        let macro_name = item_macro
-                            .ident
-                            .as_ref()
-                            .map(|ident| ident.to_string())
-                            .unwrap_or_else(|| "unnamed_macro".to_string());
-
-                        self.state
-                            .code_graph
-                            .macros
-                            .iter()
-                            .find(|m| m.name == macro_name)
-                            .map(|m| m.id)
-                    }
-                    _ => None,
-                };
+        let path_attr = path_attribute(&module.attrs);
+        let parent_dirs = self.state.module_dirs.clone();
 
-                if let Some(id) = item_id {
-                    self.state.code_graph.relations.push(Relation {
-                        source: module_id,
-                        target: id,
-                        kind: RelationKind::Contains,
+        match &module.content {
+            Some((_, mod_items)) => {
+                self.state.module_dirs = parent_dirs
+                    .as_ref()
+                    .map(|dirs| dirs.inline_child(&module_name, path_attr.as_deref()));
+                self.visit_module_items(module_id, mod_items);
+            }
+            None => {
+                // Out-of-line module, only followed when analyzing a whole crate. Its
+                // file is loaded once this one is done, see `analyze_files`.
+                if let Some(dirs) = &parent_dirs {
+                    let start = module.ident.span().start();
+                    self.state.module_decls.push(ModuleDecl {
+                        module_id,
+                        name: module_name.clone(),
+                        module_path: self.state.module_path.clone(),
+                        path_attr: path_attr.clone(),
+                        parent_dirs: dirs.clone(),
+                        file: self.state.current_file.clone(),
+                        line: start.line,
+                        column: start.column + 1,
                     });
                 }
             }
         }
 
-        // Continue visiting inner items (this is redundant now, remove it)
-        // visit::visit_item_mod(self, module);
-    }
-
-    // Visit use statements
-    fn visit_item_use(&mut self, use_item: &'ast syn::ItemUse) {
-        // Create an import node
-        let import_id = self.state.next_node_id();
-
-        // Process the use path
-        let mut path_segments = Vec::new();
-        let current_path = &use_item.tree;
-
-        // Extract path segments from the use tree
-        CodeVisitor::extract_use_path(current_path, &mut path_segments);
-
-        // Create relations for the used types
-        if !path_segments.is_empty() {
-            // Create a synthetic type for the imported item
-            let type_id = self.state.next_type_id();
-            self.state.code_graph.type_graph.push(TypeNode {
-                id: type_id,
-                kind: TypeKind::Named {
-                    path: path_segments.clone(),
-                    is_fully_qualified: false,
-                },
-                related_types: Vec::new(),
-            });
-
-            // Add a Uses relation
-            self.state.code_graph.relations.push(Relation {
-                source: import_id,
-                target: type_id,
-                kind: RelationKind::Uses,
-            });
-        }
-
-        // Continue visiting
-        visit::visit_item_use(self, use_item);
-    }
-
-    // Visit extern crate statements
-    fn visit_item_extern_crate(&mut self, extern_crate: &'ast syn::ItemExternCrate) {
-        // Create an import node for extern crate
-        let import_id = self.state.next_node_id();
-
-        // Get the crate name
-        let crate_name = extern_crate.ident.to_string();
-
-        // Create a synthetic type for the extern crate
-        let type_id = self.state.next_type_id();
-        self.state.code_graph.type_graph.push(TypeNode {
-            id: type_id,
-            kind: TypeKind::Named {
-                path: vec![crate_name.clone()],
-                is_fully_qualified: false,
-            },
-            related_types: Vec::new(),
-        });
-
-        // Add a Uses relation
-        self.state.code_graph.relations.push(Relation {
-            source: import_id,
-            target: type_id,
-            kind: RelationKind::Uses,
-        });
-
-        // Continue visiting
-        visit::visit_item_extern_crate(self, extern_crate);
+        self.state.module_dirs = parent_dirs;
+        self.state.module_path.pop();
+        Some(module_id)
     }
 
-    // Visit constant items
-    fn visit_item_const(&mut self, item_const: &'ast syn::ItemConst) {
-        // Check if the constant is public
-        if matches!(item_const.vis, Visibility::Public(_)) {
-            let const_id = self.state.next_node_id();
+    // Record constant items
+    fn record_const(&mut self, item_const: &syn::ItemConst) -> Option<NodeId> {
+        // Check if the constant passes the visibility filter
+        let const_id = if self.state.options.allows_visibility(&item_const.vis) {
             let const_name = item_const.ident.to_string();
+            let const_id = self.state.item_id("const", &const_name);
 
             // Process the type
             let type_id = self.state.get_or_create_type(&item_const.ty);
 
-            // Extract the value expression as a string
-            let value = Some(item_const.expr.to_token_stream().to_string());
+            // Extract the value expression's source text
+            let (value, value_normalized) = self.state.body(&item_const.expr);
 
             // Extract doc comments and other attributes
             let docstring = self.state.extract_docstring(&item_const.attrs);
@@ -1576,13 +2882,16 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             // Create the constant node
             let const_node = ValueNode {
                 id: const_id,
+                path: self.state.item_path(&const_name),
                 name: const_name,
                 visibility: self.state.convert_visibility(&item_const.vis),
                 type_id,
                 kind: ValueKind::Constant,
                 value,
+                value_normalized,
                 attributes,
                 docstring,
+                span: self.state.span(item_const),
             };
 
             // Add the constant to the code graph
@@ -1594,24 +2903,28 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 target: type_id,
                 kind: RelationKind::ValueType,
             });
-        }
+            Some(const_id)
+        } else {
+            None
+        };
 
         // Continue visiting
         visit::visit_item_const(self, item_const);
+        const_id
     }
 
-    // Visit static items
-    fn visit_item_static(&mut self, item_static: &'ast syn::ItemStatic) {
-        // Check if the static variable is public
-        if matches!(item_static.vis, Visibility::Public(_)) {
-            let static_id = self.state.next_node_id();
+    // Record static items
+    fn record_static(&mut self, item_static: &syn::ItemStatic) -> Option<NodeId> {
+        // Check if the static variable passes the visibility filter
+        let static_id = if self.state.options.allows_visibility(&item_static.vis) {
             let static_name = item_static.ident.to_string();
+            let static_id = self.state.item_id("static", &static_name);
 
             // Process the type
             let type_id = self.state.get_or_create_type(&item_static.ty);
 
-            // Extract the value expression as a string
-            let value = Some(item_static.expr.to_token_stream().to_string());
+            // Extract the value expression's source text
+            let (value, value_normalized) = self.state.body(&item_static.expr);
 
             // Extract doc comments and other attributes
             let docstring = self.state.extract_docstring(&item_static.attrs);
@@ -1620,6 +2933,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             // Create the static node
             let static_node = ValueNode {
                 id: static_id,
+                path: self.state.item_path(&static_name),
                 name: static_name,
                 visibility: self.state.convert_visibility(&item_static.vis),
                 type_id,
@@ -1627,8 +2941,10 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                     is_mutable: matches!(item_static.mutability, syn::StaticMutability::Mut(_)),
                 },
                 value,
+                value_normalized,
                 attributes,
                 docstring,
+                span: self.state.span(item_static),
             };
 
             // Add the static to the code graph
@@ -1640,24 +2956,71 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 target: type_id,
                 kind: RelationKind::ValueType,
             });
-        }
+            Some(static_id)
+        } else {
+            None
+        };
 
         // Continue visiting
         visit::visit_item_static(self, item_static);
+        static_id
     }
 
-    // Visit macro definitions (macro_rules!)
-    fn visit_item_macro(&mut self, item_macro: &'ast syn::ItemMacro) {
-        // Only process macros with #[macro_export]
-        if !item_macro
+    // Record an `extern` block, returning its ID and the IDs of the items declared in it
+    fn record_foreign_mod(&mut self, foreign_mod: &syn::ItemForeignMod) -> (NodeId, Vec<NodeId>) {
+        let abi = foreign_mod.abi.name.as_ref().map(|name| name.value());
+        let foreign_mod_id = self
+            .state
+            .item_id("extern", abi.as_deref().unwrap_or_default());
+
+        let mut items = Vec::new();
+        for item in &foreign_mod.items {
+            if !self.state.options.include_tests && is_test_only(foreign_item_attributes(item)) {
+                continue;
+            }
+            let item_id = match item {
+                syn::ForeignItem::Fn(func) => self.visit_foreign_fn(func),
+                syn::ForeignItem::Static(item_static) => self.visit_foreign_static(item_static),
+                syn::ForeignItem::Type(item_type) => self.visit_foreign_type(item_type),
+                // Macro invocations and unparsed tokens declare nothing we can see
+                _ => None,
+            };
+            items.extend(item_id);
+        }
+
+        // Extract doc comments and other attributes
+        let docstring = self.state.extract_docstring(&foreign_mod.attrs);
+        let attributes = self.state.extract_attributes(&foreign_mod.attrs);
+
+        self.state.code_graph.foreign_mods.push(ForeignModNode {
+            id: foreign_mod_id,
+            abi,
+            is_unsafe: foreign_mod.unsafety.is_some(),
+            items: items.clone(),
+            attributes,
+            docstring,
+            span: self.state.span(foreign_mod),
+        });
+        (foreign_mod_id, items)
+    }
+
+    // Record macro definitions (macro_rules!)
+    fn record_macro_rules(&mut self, item_macro: &syn::ItemMacro) -> Option<NodeId> {
+        let is_exported = item_macro
             .attrs
             .iter()
-            .any(|attr| attr.path().is_ident("macro_export"))
-        {
-            return;
+            .any(|attr| attr.path().is_ident("macro_export"));
+
+        // Item-position invocations like `thread_local! { .. }` are not definitions
+        if item_macro.ident.is_none() {
+            visit::visit_item_macro(self, item_macro);
+            return None;
         }
 
-        let macro_id = self.state.next_node_id();
+        // Only process macro definitions, exported ones unless options say otherwise
+        if self.state.options.exported_macros_only && !is_exported {
+            return None;
+        }
 
         // Get the macro name
         let macro_name = item_macro
@@ -1665,9 +3028,17 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
             .as_ref()
             .map(|ident| ident.to_string())
             .unwrap_or_else(|| "unnamed_macro".to_string());
+        let macro_id = self.state.item_id("macro", &macro_name);
 
-        // Extract the macro body
-        let body = Some(item_macro.mac.tokens.to_string());
+        // Extract the macro body, including its delimiters and any comments inside them
+        let delimiters = match &item_macro.mac.delimiter {
+            syn::MacroDelimiter::Paren(paren) => paren.span,
+            syn::MacroDelimiter::Brace(brace) => brace.span,
+            syn::MacroDelimiter::Bracket(bracket) => bracket.span,
+        };
+        let (body, body_normalized) = self
+            .state
+            .body_at(delimiters.join(), item_macro.mac.tokens.to_string());
 
         // Extract doc comments and other attributes
         let docstring = self.state.extract_docstring(&item_macro.attrs);
@@ -1679,7 +3050,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
 
         // Very basic parsing of macro rules - in a real implementation,
         // you would want to use a more sophisticated approach
-        for (_i, rule) in tokens_str.split(";").enumerate() {
+        for rule in tokens_str.split(";") {
             if rule.trim().is_empty() {
                 continue;
             }
@@ -1690,7 +3061,7 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
                 let expansion = rule[(idx + 2)..].trim().to_string();
 
                 rules.push(MacroRuleNode {
-                    id: self.state.next_node_id(),
+                    id: self.state.child_id(macro_id, "rule", &pattern),
                     pattern,
                     expansion,
                 });
@@ -1700,45 +3071,30 @@ impl<'a, 'ast> Visit<'ast> for CodeVisitor<'a> {
         // Create the macro node
         let macro_node = MacroNode {
             id: macro_id,
+            // Exported macros are used through the crate root, whatever module defines them
+            path: if is_exported {
+                format!("{}::{}", self.state.module_path[0], macro_name)
+            } else {
+                self.state.item_path(&macro_name)
+            },
             name: macro_name,
-            visibility: VisibilityKind::Public, // Macros with #[macro_export] are public
+            // Macros with #[macro_export] are public, others are scoped to their module
+            visibility: if is_exported {
+                VisibilityKind::Public
+            } else {
+                VisibilityKind::Inherited
+            },
             kind: MacroKind::DeclarativeMacro,
             rules,
             attributes,
             docstring,
             body,
+            body_normalized,
+            span: self.state.span(item_macro),
         };
 
         // Add the macro to the code graph
         self.state.code_graph.macros.push(macro_node);
-    }
-
-    // Visit macro invocations
-    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
-        // Create a node ID for this macro invocation
-        let invocation_id = self.state.next_node_id();
-
-        // Get the macro name
-        let macro_path = mac.path.to_token_stream().to_string();
-
-        // Find if this macro is defined in our code graph
-        let defined_macro = self
-            .state
-            .code_graph
-            .macros
-            .iter()
-            .find(|m| m.name == macro_path.split("::").last().unwrap_or(&macro_path));
-
-        if let Some(defined_macro) = defined_macro {
-            // Add a relation between the invocation and the macro definition
-            self.state.code_graph.relations.push(Relation {
-                source: invocation_id,
-                target: defined_macro.id,
-                kind: RelationKind::MacroUse,
-            });
-        }
-
-        // Continue visiting
-        visit::visit_macro(self, mac);
+        Some(macro_id)
     }
 }
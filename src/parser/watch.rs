@@ -0,0 +1,236 @@
+use crate::config::ParseOptions;
+use crate::error::{Result, SynParserError};
+use crate::parser::cache::{analyze_crate_incremental_with_options, AnalysisCache};
+use crate::parser::diff::{diff, NodeMatch, NodeModification, NodeSummary};
+use crate::parser::graph::CodeGraph;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// How long the files of a crate have to stay untouched before it is analyzed again, so
+// that a save touching several files leads to a single analysis
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+// ANCHOR: GraphEvent
+// A change to the graph of a watched crate, see `CrateWatcher::subscribe`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GraphEvent {
+    NodeAdded(NodeSummary),
+    NodeRemoved(NodeSummary),
+    NodeModified(NodeModification),
+    NodeMoved(NodeMatch),
+    NodeRenamed(NodeMatch),
+    // Analyzing the changed crate failed, e.g. on a syntax error. The graph stays as it
+    // was until the next successful analysis.
+    AnalysisFailed { message: String },
+}
+//ANCHOR_END: GraphEvent
+
+// State shared by a `CrateWatcher` and its thread
+struct Shared {
+    graph: RwLock<Arc<CodeGraph>>,
+    subscribers: Mutex<Vec<Sender<GraphEvent>>>,
+}
+
+impl Shared {
+    // Send events to every subscriber, forgetting those whose receiver is gone
+    fn publish(&self, events: Vec<GraphEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|subscriber| {
+            events
+                .iter()
+                .all(|event| subscriber.send(event.clone()).is_ok())
+        });
+    }
+}
+
+/// Keeps the graph of a crate up to date while its files change.
+///
+/// The crate directory is watched for changes to `.rs` files, leaving out `target/` and
+/// hidden directories. After each change the crate is analyzed again incrementally, so
+/// only the changed files are visited, and the differences to the previous graph are
+/// sent to subscribers as `GraphEvent`s.
+/// Watching stops when the `CrateWatcher` is dropped.
+pub struct CrateWatcher {
+    shared: Arc<Shared>,
+    // Dropping the watcher closes the channel the thread waits on, which ends it
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CrateWatcher {
+    /// Analyze the crate at `root` and start watching it for changes.
+    ///
+    /// `root` is the crate directory, as for `analyze_crate`. Fails if the first
+    /// analysis fails or the directory cannot be watched.
+    pub fn start(root: &Path, options: ParseOptions) -> Result<Self> {
+        let mut cache = AnalysisCache::new();
+        let graph = analyze_crate_incremental_with_options(root, &options, &mut cache)?;
+        let shared = Arc::new(Shared {
+            graph: RwLock::new(Arc::new(graph)),
+            subscribers: Mutex::new(Vec::new()),
+        });
+
+        let watch_error = |err: notify::Error| SynParserError::Watch {
+            path: root.to_path_buf(),
+            message: err.to_string(),
+        };
+        let (changes, changed) = mpsc::channel();
+        let roots: Vec<PathBuf> = std::iter::once(root.to_path_buf())
+            .chain(root.canonicalize().ok())
+            .collect();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if event
+                        .paths
+                        .iter()
+                        .any(|path| is_source_file(path) && !is_ignored(&roots, path))
+                    {
+                        let _ = changes.send(());
+                    }
+                }
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+
+        let reanalysis = Reanalysis {
+            root: root.to_path_buf(),
+            options,
+            cache,
+            shared: Arc::clone(&shared),
+        };
+        let thread = std::thread::spawn(move || reanalysis.run(changed));
+
+        Ok(Self {
+            shared,
+            watcher: Some(watcher),
+            thread: Some(thread),
+        })
+    }
+
+    /// Receive the changes made to the graph from now on.
+    pub fn subscribe(&self) -> Receiver<GraphEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender);
+        receiver
+    }
+
+    /// The graph as of the last successful analysis.
+    pub fn graph(&self) -> Arc<CodeGraph> {
+        let graph = self
+            .shared
+            .graph
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&graph)
+    }
+}
+
+impl Drop for CrateWatcher {
+    fn drop(&mut self) {
+        self.watcher = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Analyzes a watched crate again whenever its files change
+struct Reanalysis {
+    root: PathBuf,
+    options: ParseOptions,
+    cache: AnalysisCache,
+    shared: Arc<Shared>,
+}
+
+impl Reanalysis {
+    fn run(mut self, changed: Receiver<()>) {
+        while changed.recv().is_ok() {
+            // Wait for the files to settle
+            loop {
+                match changed.recv_timeout(SETTLE_TIME) {
+                    Ok(()) => continue,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            let events = match analyze_crate_incremental_with_options(
+                &self.root,
+                &self.options,
+                &mut self.cache,
+            ) {
+                Ok(graph) => self.replace_graph(graph),
+                Err(err) => vec![GraphEvent::AnalysisFailed {
+                    message: err.to_string(),
+                }],
+            };
+            // The root file is parsed on this thread, whose spans nothing else uses, see
+            // `load_modules` for the other files
+            proc_macro2::extra::invalidate_current_thread_spans();
+            self.shared.publish(events);
+        }
+    }
+
+    // Make `graph` the current graph, returning how it differs from the previous one
+    fn replace_graph(&self, graph: CodeGraph) -> Vec<GraphEvent> {
+        // Only this thread replaces the graph, so the diff can be computed without holding
+        // the lock that readers wait on
+        let previous = Arc::clone(
+            &self
+                .shared
+                .graph
+                .read()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        let changes = diff(&previous, &graph);
+        *self
+            .shared
+            .graph
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Arc::new(graph);
+
+        let mut events = Vec::new();
+        events.extend(changes.added.into_iter().map(GraphEvent::NodeAdded));
+        events.extend(changes.removed.into_iter().map(GraphEvent::NodeRemoved));
+        events.extend(changes.modified.into_iter().map(GraphEvent::NodeModified));
+        events.extend(changes.moved.into_iter().map(GraphEvent::NodeMoved));
+        events.extend(changes.renamed.into_iter().map(GraphEvent::NodeRenamed));
+        events
+    }
+}
+
+fn is_source_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rs")
+}
+
+// Whether `path` is under `target/` or a hidden directory of the crate, where builds and
+// tools write files that are not part of it. `roots` holds the crate directory as given
+// and canonicalized, as events may name paths either way.
+fn is_ignored(roots: &[PathBuf], path: &Path) -> bool {
+    let Some(relative) = roots.iter().find_map(|root| path.strip_prefix(root).ok()) else {
+        return false;
+    };
+    relative.starts_with("target")
+        || relative
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+}
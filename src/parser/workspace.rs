@@ -0,0 +1,349 @@
+use crate::config::ParseOptions;
+use crate::error::{Result, SynParserError};
+use crate::parser::api::public_api;
+use crate::parser::graph::CodeGraph;
+use crate::parser::nodes::NodeId;
+use crate::parser::relations::RelationKind;
+use crate::parser::types::TypeKind;
+use crate::parser::visitor::analyze_member_with_options;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// ANCHOR: WorkspaceGraph
+// Graph of a whole Cargo workspace: one graph per member crate plus the relations
+// between items of different crates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceGraph {
+    pub crates: Vec<CrateNode>,
+    pub relations: Vec<CrateRelation>,
+}
+//ANCHOR_END: WorkspaceGraph
+
+// Represents a member crate of the workspace
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrateNode {
+    // Crate name as used in paths (`my-crate` becomes `my_crate`)
+    pub name: String,
+    // Directory containing the crate's Cargo.toml
+    pub root: PathBuf,
+    // Root module of the crate in `graph`
+    pub root_module: NodeId,
+    pub graph: CodeGraph,
+}
+
+// Represents a relation between nodes of two different crates.
+// `source` and `target` are IDs within the graphs of `source_crate` and `target_crate`;
+// `source` is an import or a type path that names the target.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrateRelation {
+    pub source_crate: String,
+    pub source: NodeId,
+    pub target_crate: String,
+    pub target: NodeId,
+    pub kind: RelationKind,
+}
+
+impl WorkspaceGraph {
+    // Find a member crate by its path name
+    pub fn find_crate(&self, name: &str) -> Option<&CrateNode> {
+        self.crates.iter().find(|c| c.name == name)
+    }
+}
+
+/// Analyze every member of the Cargo workspace whose manifest is in `root`, then link
+/// paths that start with the name of a sibling crate (`use other_crate::Item`) to the
+/// item they name in that crate's graph. Paths are matched against the public paths of
+/// the sibling, so items it re-exports with `pub use` are found too.
+///
+/// A manifest with only a `[package]` section is treated as a workspace of one crate.
+pub fn analyze_workspace(root: &Path) -> Result<WorkspaceGraph> {
+    analyze_workspace_with_options(root, &ParseOptions::default())
+}
+
+/// Analyze a workspace, parsing every member crate with the given options.
+pub fn analyze_workspace_with_options(
+    root: &Path,
+    options: &ParseOptions,
+) -> Result<WorkspaceGraph> {
+    let manifest = read_manifest(&root.join("Cargo.toml"))?;
+
+    let mut member_dirs = Vec::new();
+    if manifest.contains_key("package") {
+        member_dirs.push(root.to_path_buf());
+    }
+    if let Some(workspace) = manifest.get("workspace").and_then(|w| w.as_table()) {
+        let excluded: Vec<PathBuf> = string_array(workspace.get("exclude"))
+            .iter()
+            .map(|path| root.join(path))
+            .collect();
+        for pattern in string_array(workspace.get("members")) {
+            for dir in expand_member_pattern(root, &pattern) {
+                if !excluded.contains(&dir) && !member_dirs.contains(&dir) {
+                    member_dirs.push(dir);
+                }
+            }
+        }
+    }
+
+    // Parse every member first, so dependencies can be matched against package names
+    let mut members = Vec::new();
+    for dir in member_dirs {
+        let member_manifest = read_manifest(&dir.join("Cargo.toml"))?;
+        let Some(package_name) = member_manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+        else {
+            // Virtual manifests nested in the member list have nothing to analyze
+            continue;
+        };
+        let crate_name = member_manifest
+            .get("lib")
+            .and_then(|lib| lib.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or(&package_name)
+            .replace('-', "_");
+
+        let graph = analyze_member_with_options(&dir, &package_name, options)?;
+        members.push((
+            package_name,
+            member_manifest,
+            CrateNode {
+                name: crate_name,
+                root: dir,
+                // The root module is always the first one
+                root_module: graph.modules[0].id,
+                graph,
+            },
+        ));
+    }
+
+    let package_to_crate: HashMap<String, String> = members
+        .iter()
+        .map(|(package, _, node)| (package.clone(), node.name.clone()))
+        .collect();
+    let workspace_deps = manifest
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.as_table());
+
+    let member_paths: Vec<HashMap<String, NodeId>> = members
+        .iter()
+        .map(|(_, _, node)| public_paths(node))
+        .collect();
+
+    let mut relations = Vec::new();
+    for (_, member_manifest, crate_node) in &members {
+        let extern_names = sibling_dependencies(member_manifest, workspace_deps, &package_to_crate);
+        for (extern_name, target_name) in &extern_names {
+            let target = members.iter().position(|(_, _, c)| &c.name == target_name);
+            if let Some(index) = target {
+                let target = &members[index].2;
+                link_paths(
+                    crate_node,
+                    extern_name,
+                    target,
+                    &member_paths[index],
+                    &mut relations,
+                );
+            }
+        }
+    }
+
+    Ok(WorkspaceGraph {
+        crates: members.into_iter().map(|(_, _, node)| node).collect(),
+        relations,
+    })
+}
+
+fn read_manifest(path: &Path) -> Result<toml::Table> {
+    let contents = std::fs::read_to_string(path).map_err(|err| SynParserError::io(path, err))?;
+    contents
+        .parse::<toml::Table>()
+        .map_err(|err| SynParserError::Manifest {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })
+}
+
+fn string_array(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Expand a `members` entry such as `crates/*` into the crate directories it matches
+fn expand_member_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        let mut next = Vec::new();
+        for dir in &dirs {
+            if component.contains(['*', '?']) {
+                let Ok(entries) = std::fs::read_dir(dir) else {
+                    continue;
+                };
+                let mut matched: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .filter(|entry| wildcard_match(component, &entry.file_name().to_string_lossy()))
+                    .map(|entry| entry.path())
+                    .collect();
+                matched.sort();
+                next.extend(matched);
+            } else {
+                next.push(dir.join(component));
+            }
+        }
+        dirs = next;
+    }
+
+    dirs.into_iter()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .collect()
+}
+
+// Match `name` against a pattern where `*` is any run of characters and `?` any one character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// Dependencies of a member on other workspace members, as (name used in paths, crate name)
+fn sibling_dependencies(
+    manifest: &toml::Table,
+    workspace_deps: Option<&toml::Table>,
+    package_to_crate: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut siblings = Vec::new();
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        let Some(deps) = manifest.get(section).and_then(|d| d.as_table()) else {
+            continue;
+        };
+        for (key, dep) in deps {
+            // `foo = { workspace = true }` takes its `package` from the workspace entry
+            let inherited = dep
+                .get("workspace")
+                .and_then(|w| w.as_bool())
+                .unwrap_or(false);
+            let source = if inherited {
+                workspace_deps.and_then(|w| w.get(key)).unwrap_or(dep)
+            } else {
+                dep
+            };
+            let package = source
+                .get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(key);
+
+            if let Some(crate_name) = package_to_crate.get(package) {
+                let entry = (key.replace('-', "_"), crate_name.clone());
+                if !siblings.contains(&entry) {
+                    siblings.push(entry);
+                }
+            }
+        }
+    }
+    siblings
+}
+
+// Add a relation from every import and type path in `source` that starts with
+// `extern_name` to the item it names in `target`, found among `target_paths`
+fn link_paths(
+    source: &CrateNode,
+    extern_name: &str,
+    target: &CrateNode,
+    target_paths: &HashMap<String, NodeId>,
+    relations: &mut Vec<CrateRelation>,
+) {
+    // A module of the same name at the crate root shadows the dependency
+    let shadowed = source
+        .graph
+        .modules
+        .iter()
+        .find(|m| m.id == source.root_module)
+        .is_some_and(|root| {
+            root.submodules.iter().any(|id| {
+                source
+                    .graph
+                    .modules
+                    .iter()
+                    .any(|m| m.id == *id && m.name == extern_name)
+            })
+        });
+    if shadowed {
+        return;
+    }
+
+    let imports = source
+        .graph
+        .modules
+        .iter()
+        .flat_map(|m| &m.imports)
+        .map(|import| (import.id, &import.path));
+    let types = source
+        .graph
+        .type_graph
+        .iter()
+        .filter_map(|t| match &t.kind {
+            TypeKind::Named { path, .. } => Some((t.id, path)),
+            _ => None,
+        });
+
+    for (id, path) in imports.chain(types) {
+        if path.first().map(String::as_str) != Some(extern_name) {
+            continue;
+        }
+
+        if let Some(target_id) = target_paths.get(&path[1..].join("::")) {
+            relations.push(CrateRelation {
+                source_crate: source.name.clone(),
+                source: id,
+                target_crate: target.name.clone(),
+                target: *target_id,
+                kind: RelationKind::Uses,
+            });
+        }
+    }
+}
+
+// Paths other crates can name the items of `target` by, without the leading `crate`,
+// e.g. `model::Record`. Re-exported items are found under every path that names them.
+fn public_paths(target: &CrateNode) -> HashMap<String, NodeId> {
+    let mut paths = HashMap::new();
+    for item in public_api(&target.graph).public_items() {
+        for path in &item.public_paths {
+            let relative = path.split_once("::").map_or("", |(_, rest)| rest);
+            paths.entry(relative.to_string()).or_insert(item.node.id());
+        }
+    }
+    paths
+}
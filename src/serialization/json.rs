@@ -0,0 +1,18 @@
+use crate::error::{Result, SynParserError};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+// JSON format serialization
+
+// Save a `CodeGraph`, or anything else serializable like a `GraphDiff`
+pub fn save_to_json<T: Serialize>(value: &T, output_path: &Path) -> Result<()> {
+    let json_string = serde_json::to_string_pretty(value)?;
+
+    let mut output_file =
+        File::create(output_path).map_err(|err| SynParserError::write(output_path, err))?;
+    output_file
+        .write_all(json_string.as_bytes())
+        .map_err(|err| SynParserError::write(output_path, err))?;
+    Ok(())
+}
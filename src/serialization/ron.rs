@@ -1,15 +1,20 @@
-use crate::parser::graph::CodeGraph;  // Update the import path
+use crate::error::{Result, SynParserError};
 use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 // RON format serialization
 
-pub fn save_to_ron(code_graph: &CodeGraph, output_path: &Path) -> std::io::Result<()> {
+// Save a `CodeGraph`, or anything else serializable like a `GraphDiff`
+pub fn save_to_ron<T: Serialize>(value: &T, output_path: &Path) -> Result<()> {
     let pretty_config = PrettyConfig::default();
-    let ron_string = to_string_pretty(code_graph, pretty_config).expect("Serialization failed");
+    let ron_string = to_string_pretty(value, pretty_config)?;
 
-    let mut output_file = File::create(output_path)?;
-    output_file.write_all(ron_string.as_bytes())?;
+    let mut output_file =
+        File::create(output_path).map_err(|err| SynParserError::write(output_path, err))?;
+    output_file
+        .write_all(ron_string.as_bytes())
+        .map_err(|err| SynParserError::write(output_path, err))?;
     Ok(())
 }
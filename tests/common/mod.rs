@@ -1,8 +1,9 @@
 use std::path::Path;
 use syn_parser::parser::graph::CodeGraph;
 use syn_parser::parser::nodes::*;
+use syn_parser::parser::relations::RelationKind;
 use syn_parser::parser::types::{GenericParamNode, GenericParamKind};
-use syn_parser::parser::visitor::analyze_code;
+use syn_parser::parser::visitor::{analyze_code, analyze_crate};
 
 /// Parse a fixture file and return the resulting CodeGraph
 pub fn parse_fixture(fixture_name: &str) -> CodeGraph {
@@ -10,6 +11,12 @@ pub fn parse_fixture(fixture_name: &str) -> CodeGraph {
     analyze_code(&path).expect("Failed to parse fixture")
 }
 
+/// Parse a fixture crate directory, following its module files
+pub fn parse_fixture_crate(crate_name: &str) -> CodeGraph {
+    let path = Path::new("tests/fixtures").join(crate_name);
+    analyze_crate(&path).expect("Failed to parse fixture crate")
+}
+
 /// Find a struct by name in the code graph
 pub fn find_struct_by_name<'a>(graph: &'a CodeGraph, name: &str) -> Option<&'a StructNode> {
     graph.defined_types.iter().find_map(|def| {
@@ -46,17 +53,26 @@ pub fn find_function_by_name<'a>(graph: &'a CodeGraph, name: &str) -> Option<&'a
     graph.functions.iter().find(|f| f.name == name)
 }
 
-/// Find an impl block for a specific type
+/// Find an impl block for the type with the given name, through the `ResolvesTo`
+/// relation of its self type
 pub fn find_impl_for_type<'a>(graph: &'a CodeGraph, type_name: &str) -> Option<&'a ImplNode> {
-    // This is a simplified implementation - in a real scenario, you'd need to match
-    // the type_id with the actual type name from the type graph
+    let type_ids: Vec<NodeId> = graph
+        .defined_types
+        .iter()
+        .filter_map(|def| match def {
+            TypeDefNode::Struct(s) if s.name == type_name => Some(s.id),
+            TypeDefNode::Enum(e) if e.name == type_name => Some(e.id),
+            TypeDefNode::TypeAlias(ta) if ta.name == type_name => Some(ta.id),
+            TypeDefNode::Union(u) if u.name == type_name => Some(u.id),
+            _ => None,
+        })
+        .collect();
     graph.impls.iter().find(|impl_node| {
-        if let Some(type_node) = graph.type_graph.iter().find(|t| t.id == impl_node.self_type) {
-            // This is a simplification - you'd need to extract the type name from the TypeKind
-            format!("{:?}", type_node.kind).contains(type_name)
-        } else {
-            false
-        }
+        graph.relations.iter().any(|r| {
+            r.source == impl_node.self_type
+                && r.kind == RelationKind::ResolvesTo
+                && type_ids.contains(&r.target)
+        })
     })
 }
 
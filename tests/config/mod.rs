@@ -0,0 +1,2 @@
+// This module will include all configuration tests
+pub mod options_tests;
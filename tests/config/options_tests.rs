@@ -0,0 +1,211 @@
+use crate::common::*;
+use std::path::Path;
+use syn_parser::config::{ParseOptions, VisibilityFilter};
+use syn_parser::parser::nodes::TypeDefNode;
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::{analyze_code_with_options, SynParserError};
+
+fn parse_fixture_with(fixture_name: &str, options: &ParseOptions) -> syn_parser::CodeGraph {
+    let path = Path::new("tests/fixtures").join(fixture_name);
+    analyze_code_with_options(&path, options).expect("Failed to parse fixture")
+}
+
+#[test]
+fn test_default_options_store_private_items() {
+    let graph = parse_fixture("../data/sample.rs");
+
+    assert!(find_struct_by_name(&graph, "PrivateStruct").is_some());
+    assert!(graph.values.iter().any(|v| v.name == "MIN_ITEMS"));
+    assert!(graph.macros.iter().all(|m| m.name != "private_macro"));
+}
+
+#[test]
+fn test_visibility_filter_public() {
+    let options = ParseOptions {
+        visibility: VisibilityFilter::Public,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("../data/sample.rs", &options);
+
+    assert!(find_struct_by_name(&graph, "PrivateStruct").is_none());
+    assert!(graph.values.iter().all(|v| v.name != "MIN_ITEMS"));
+
+    // Filtered items leave no relations behind
+    let field_owners: Vec<_> = graph
+        .relations
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.kind,
+                RelationKind::StructField | RelationKind::EnumVariant
+            )
+        })
+        .map(|r| r.source)
+        .collect();
+    assert!(field_owners.iter().all(|id| {
+        graph.defined_types.iter().any(|def| match def {
+            TypeDefNode::Struct(s) => s.id == *id,
+            TypeDefNode::Enum(e) => e.id == *id,
+            TypeDefNode::Union(u) => u.id == *id,
+            TypeDefNode::TypeAlias(_) | TypeDefNode::ForeignType(_) => false,
+        })
+    }));
+}
+
+#[test]
+fn test_visibility_filter_all() {
+    let options = ParseOptions {
+        visibility: VisibilityFilter::All,
+        exported_macros_only: false,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("../data/sample.rs", &options);
+
+    assert!(find_struct_by_name(&graph, "PrivateStruct").is_some());
+    assert!(graph.values.iter().any(|v| v.name == "MIN_ITEMS"));
+    assert!(graph.macros.iter().any(|m| m.name == "private_macro"));
+}
+
+// Names of the methods stored for the impls of `Widget`
+fn widget_methods(graph: &syn_parser::CodeGraph) -> Vec<&str> {
+    graph
+        .impls
+        .iter()
+        .flat_map(|imp| &imp.methods)
+        .map(|method| method.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_visibility_filter_restricted() {
+    let options = ParseOptions {
+        visibility: VisibilityFilter::Restricted,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("visibility_filter.rs", &options);
+
+    assert!(find_struct_by_name(&graph, "PublicStruct").is_some());
+    assert!(find_struct_by_name(&graph, "CrateStruct").is_some());
+    assert!(find_struct_by_name(&graph, "PrivateStruct").is_none());
+
+    assert!(find_function_by_name(&graph, "public_function").is_some());
+    assert!(find_function_by_name(&graph, "crate_function").is_some());
+    assert!(find_function_by_name(&graph, "private_function").is_none());
+    assert_eq!(
+        widget_methods(&graph),
+        ["public_method", "crate_method", "clone"]
+    );
+}
+
+#[test]
+fn test_visibility_filter_applies_to_functions() {
+    let options = ParseOptions {
+        visibility: VisibilityFilter::Public,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("visibility_filter.rs", &options);
+
+    let functions: Vec<_> = graph.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(functions, ["public_function"]);
+    // Methods of trait impls are kept along with the impl
+    assert_eq!(widget_methods(&graph), ["public_method", "clone"]);
+}
+
+#[test]
+fn test_bodies_can_be_dropped_or_truncated() {
+    let no_bodies = ParseOptions {
+        include_bodies: false,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("functions.rs", &no_bodies);
+    assert!(graph.functions.iter().all(|f| f.body.is_none()));
+
+    let short_bodies = ParseOptions {
+        max_body_length: Some(10),
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("functions.rs", &short_bodies);
+    let function = find_function_by_name(&graph, "default_params").unwrap();
+    assert_eq!(function.body.as_ref().unwrap().chars().count(), 10);
+}
+
+#[test]
+fn test_normalized_bodies() {
+    let options = ParseOptions {
+        normalized_bodies: true,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("bodies.rs", &options);
+
+    let function = find_function_by_name(&graph, "commented").unwrap();
+    let normalized = function.body_normalized.as_ref().unwrap();
+    assert!(normalized.starts_with("{ let doubled = x * 2 ;"));
+    assert!(!normalized.contains("Double the input"));
+    assert!(function.body.as_ref().unwrap().contains("Double the input"));
+}
+
+#[test]
+fn test_tests_can_be_excluded() {
+    let graph = parse_fixture("test_items.rs");
+    assert!(find_function_by_name(&graph, "top_level_test").is_some());
+    assert!(find_module_by_name(&graph, "tests").is_some());
+
+    let options = ParseOptions {
+        include_tests: false,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("test_items.rs", &options);
+    assert!(find_function_by_name(&graph, "production_function").is_some());
+    assert!(find_function_by_name(&graph, "top_level_test").is_none());
+    assert!(find_function_by_name(&graph, "module_test").is_none());
+    assert!(find_function_by_name(&graph, "slow_test_helper").is_none());
+    assert!(find_module_by_name(&graph, "tests").is_none());
+}
+
+#[test]
+fn test_private_traits_can_be_excluded() {
+    let options = ParseOptions {
+        include_private_traits: false,
+        ..ParseOptions::default()
+    };
+    let graph = parse_fixture_with("traits.rs", &options);
+
+    assert!(find_trait_by_name(&graph, "SampleTrait").is_some());
+    assert!(find_trait_by_name(&graph, "PrivateTrait").is_none());
+}
+
+#[test]
+fn test_options_from_toml_file() {
+    let options = ParseOptions::from_file(Path::new("tests/fixtures/options/public_api.toml"))
+        .expect("Failed to load options");
+
+    assert_eq!(
+        options,
+        ParseOptions {
+            include_bodies: false,
+            include_tests: false,
+            include_private_traits: false,
+            ..ParseOptions::default()
+        }
+    );
+}
+
+#[test]
+fn test_options_from_ron_file() {
+    let options = ParseOptions::from_file(Path::new("tests/fixtures/options/full_index.ron"))
+        .expect("Failed to load options");
+
+    assert_eq!(options.visibility, VisibilityFilter::All);
+    assert!(!options.exported_macros_only);
+    assert_eq!(options.max_body_length, Some(40));
+    assert!(options.include_bodies);
+}
+
+#[test]
+fn test_invalid_options_file() {
+    let path = Path::new("tests/fixtures/options/invalid.toml");
+    let err = ParseOptions::from_file(path).expect_err("unknown visibility should fail");
+
+    assert!(matches!(err, SynParserError::Config { .. }));
+    assert_eq!(err.path(), Some(path));
+}
@@ -0,0 +1,38 @@
+//! This file contains traits with associated types and constants for testing the parser
+
+/// A source of items
+pub trait Source {
+    /// The type of item produced
+    type Item: Clone;
+
+    /// A view borrowed from the source
+    type View<'a>: Iterator<Item = &'a Self::Item>
+    where
+        Self: 'a;
+
+    type Error = ();
+
+    const CAPACITY: usize;
+
+    const NAME: &'static str = "source";
+
+    fn next(&mut self) -> Option<Self::Item>;
+}
+
+pub struct Bytes {
+    data: Vec<u8>,
+}
+
+impl Source for Bytes {
+    type Item = u8;
+    type View<'a> = std::slice::Iter<'a, u8>;
+
+    const CAPACITY: usize = 1024;
+
+    fn next(&mut self) -> Option<u8> {
+        self.data.pop()
+    }
+}
+
+/// Sources whose items can be shared between threads
+pub trait SharedSource = Source + Send + Sync;
@@ -0,0 +1,22 @@
+//! This file contains items whose bodies carry comments and formatting
+
+pub fn commented(x: i32) -> i32 {
+    // Double the input
+    let doubled = x * 2;
+
+    /* then add one */
+    doubled + 1
+}
+
+pub const TABLE: [u8; 4] = [
+    1, 2, // small
+    3, 4, // large
+];
+
+#[macro_export]
+macro_rules! square {
+    // Single expression
+    ($x:expr) => {
+        $x * $x
+    };
+}
@@ -0,0 +1,12 @@
+pub struct Complete {
+    pub field: i32,
+}
+
+pub fn unfinished() -> i32 {
+    let x = ;
+    x
+}
+
+pub const WITH_BLOCK: i32 = { 1 } + 1;
+
+pub fn after_broken() {}
@@ -0,0 +1,7 @@
+//! Crate with half-written code for testing error-tolerant analysis
+
+pub mod half_written;
+pub mod unbalanced;
+mod missing;
+
+pub fn intact() {}
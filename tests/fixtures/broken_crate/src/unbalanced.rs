@@ -0,0 +1,3 @@
+pub fn open() {
+    if true {
+}
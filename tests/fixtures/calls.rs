@@ -0,0 +1,69 @@
+mod util {
+    pub fn helper() -> u8 {
+        1
+    }
+
+    pub fn parse(s: &str) -> usize {
+        s.len()
+    }
+}
+
+use util::parse;
+
+pub struct Counter {
+    count: usize,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    pub fn reset() -> Self {
+        let counter = Self::new();
+        std::mem::drop(counter.count);
+        Self::new()
+    }
+
+    pub fn add(&mut self, words: &[&str]) {
+        self.count += words.iter().map(|w| parse(w)).sum::<usize>();
+    }
+}
+
+pub trait Describe {
+    fn name(&self) -> String;
+
+    fn describe(&self) -> String {
+        let rank = util::helper();
+        format!("{} ({})", self.name(), rank)
+    }
+}
+
+pub fn run(words: Vec<&str>) -> Counter {
+    let mut counter = Counter::new();
+    counter.add(&words);
+    let lengths: Vec<usize> = words.into_iter().map(parse).collect();
+    let total = Vec::<u8>::new();
+    let _ = (lengths, total, Some(util::helper()), missing(1));
+    counter
+}
+
+fn missing_caller() -> Option<u8> {
+    serde_json::to_string(&1).ok();
+    None
+}
+
+const LIMIT: u8 = util::helper_value();
+
+pub fn in_macros() -> Vec<usize> {
+    assert_eq!(util::helper(), 1);
+    println!("{} {width}", util::helper(), width = parse("ab"));
+    vec![parse("a"); 2]
+}
+
+pub fn shadowed(words: Vec<&str>) -> usize {
+    let parse = words.len();
+    let _ = parse;
+    let helper = |s: &str| s.len();
+    helper("a")
+}
@@ -0,0 +1,28 @@
+//! This file contains regular comments for testing the parser
+
+// Configuration for the reader
+pub struct Config {
+    // Size of the read buffer
+    pub buffer_size: usize,
+    pub verbose: bool, // print progress
+}
+
+// TODO: support async reads
+pub fn read(config: &Config, data: &[u8]) -> usize {
+    let text = "// not a comment";
+    let raw = r#"/* not a comment either */"#;
+    let quote = '"';
+
+    // SAFETY: `data` is non-empty, checked by the caller
+    let first = unsafe { *data.get_unchecked(0) };
+
+    /* FIXME: ignores the buffer size */
+    first as usize + text.len() + raw.len() + config.buffer_size + quote.len_utf8()
+}
+
+pub mod inner {
+    // HACK: re-exported until callers migrate
+    pub fn legacy() {}
+
+    // Nothing follows this comment
+}
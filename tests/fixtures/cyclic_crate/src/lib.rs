@@ -0,0 +1,5 @@
+//! A crate with a module file that includes itself
+
+pub mod looped;
+
+pub fn intact() {}
@@ -0,0 +1,4 @@
+pub fn in_looped() {}
+
+#[path = "looped.rs"]
+pub mod again;
@@ -0,0 +1,30 @@
+// Graph after moving, renaming, adding, removing and modifying items
+pub mod net {}
+
+pub mod transport {
+    pub fn connect(addr: &str) -> bool {
+        let trimmed = addr.trim();
+        !trimmed.is_empty() && trimmed.contains(':')
+    }
+}
+
+pub fn read_config(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+pub fn unchanged() -> u32 {
+    1
+}
+
+pub fn modified(x: u64) -> u32 {
+    x as u32
+}
+
+pub fn added() {}
+
+pub struct Config {
+    pub name: String,
+}
@@ -0,0 +1,28 @@
+// Graph before the changes in `new.rs`
+pub mod net {
+    pub fn connect(addr: &str) -> bool {
+        let trimmed = addr.trim();
+        !trimmed.is_empty() && trimmed.contains(':')
+    }
+}
+
+pub fn parse_config(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+pub fn unchanged() -> u32 {
+    1
+}
+
+pub fn modified(x: u32) -> u32 {
+    x
+}
+
+pub fn removed() {}
+
+pub struct Config {
+    pub name: String,
+}
@@ -0,0 +1,24 @@
+//! This file contains `extern` blocks for testing the parser
+
+use std::os::raw::{c_char, c_int};
+
+/// Bindings to the compression library
+#[link(name = "z")]
+extern "C" {
+    /// Returns the library version
+    pub fn zlibVersion() -> *const c_char;
+
+    pub fn compress(dest: *mut u8, dest_len: *mut u64, source: *const u8, source_len: u64) -> c_int;
+
+    pub static z_errmsg: *const c_char;
+
+    static mut z_verbose: c_int;
+
+    pub type z_stream;
+}
+
+extern {
+    fn abort() -> !;
+}
+
+pub fn regular_function() {}
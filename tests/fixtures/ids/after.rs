@@ -0,0 +1,44 @@
+//! Fixture for stable IDs, see `before.rs` for the file before the edit
+
+pub fn added_before_everything() {}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub struct Added;
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+}
+
+impl Point {
+    pub fn origin() -> Self {
+        Point { x: 0, y: 0 }
+    }
+}
+
+pub fn zeroth() {
+    println!("zeroth");
+}
+
+pub fn first() {
+    println!("first");
+    println!("again");
+}
+
+pub fn second() {
+    println!("second");
+}
+
+pub mod shapes {
+    pub const ADDED: u8 = 1;
+
+    pub enum Shape {
+        Dot(super::Point),
+        Line { from: super::Point, to: super::Point },
+    }
+}
@@ -0,0 +1,34 @@
+//! Fixture for stable IDs, see `after.rs` for the same file after an edit
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+}
+
+impl Point {
+    pub fn origin() -> Self {
+        Point { x: 0, y: 0 }
+    }
+}
+
+pub fn first() {
+    println!("first");
+    println!("again");
+}
+
+pub fn second() {
+    println!("second");
+}
+
+pub mod shapes {
+    pub enum Shape {
+        Dot(super::Point),
+        Line { from: super::Point, to: super::Point },
+    }
+}
@@ -0,0 +1,48 @@
+//! This file contains impl blocks for testing the parser
+
+use std::fmt;
+
+pub struct Meters(pub f64);
+
+impl Meters {
+    pub fn new(value: f64) -> Self {
+        Meters(value)
+    }
+}
+
+// Implemented before the trait is defined
+impl Measure for Meters {
+    type Unit = f64;
+    const SCALE: f64 = 1.0;
+
+    fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+pub trait Measure {
+    type Unit;
+    const SCALE: f64;
+
+    fn value(&self) -> Self::Unit;
+}
+
+trait Hidden {}
+
+impl Hidden for Meters {}
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}m", self.0)
+    }
+}
+
+impl Clone for Meters {
+    fn clone(&self) -> Self {
+        Meters(self.0)
+    }
+}
+
+impl serde::Serialize for Meters {}
+
+impl Missing for Meters {}
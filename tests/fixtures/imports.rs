@@ -0,0 +1,17 @@
+//! This file contains `use` trees for testing the parser
+
+use std::collections::{hash_map::Entry as MapEntry, HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io::prelude::*;
+pub use std::sync::Arc;
+pub(crate) use std::rc::Rc as Shared;
+extern crate alloc as allocator;
+
+pub mod inner {
+    use super::Shared;
+}
+
+pub fn nested() {
+    use std::fmt::Write as _;
+    extern crate core;
+}
@@ -0,0 +1,4 @@
+// File with a syntax error on line 4
+pub fn valid() {}
+
+pub fn broken( {
@@ -0,0 +1,81 @@
+pub struct Engine {
+    pub level: u8,
+}
+
+impl Engine {
+    pub fn process(&self) -> u8 {
+        self.level
+    }
+
+    pub fn start(&mut self) {
+        self.process();
+    }
+}
+
+pub struct Parser;
+
+impl Parser {
+    pub fn process(&self) -> u8 {
+        0
+    }
+
+    pub fn halt(&self) {}
+}
+
+pub trait Render {
+    fn render(&self) -> String;
+
+    fn show(&self) -> String {
+        self.render()
+    }
+}
+
+pub trait Export {
+    fn render(&self) -> String;
+}
+
+impl Render for Engine {
+    fn render(&self) -> String {
+        String::new()
+    }
+}
+
+impl Export for Engine {
+    fn render(&self) -> String {
+        String::new()
+    }
+}
+
+impl Render for Parser {
+    fn render(&self) -> String {
+        self.process().to_string()
+    }
+}
+
+pub struct Machine {
+    engine: Engine,
+    name: String,
+}
+
+impl Machine {
+    pub fn run(&self, parser: &Parser, item: &dyn Export) -> usize {
+        self.engine.process();
+        parser.process();
+        let engine: Engine = Engine { level: 1 };
+        engine.render();
+        item.render();
+        self.name.len()
+    }
+}
+
+pub fn tick(machine: Machine, other: Engine) {
+    other.show();
+    Engine { level: 2 }.start();
+    let other = machine;
+    other.process();
+    make().halt();
+}
+
+fn make() -> Parser {
+    Parser
+}
@@ -0,0 +1,2 @@
+// Crate root declaring a module without a backing file
+mod missing;
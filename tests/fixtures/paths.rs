@@ -0,0 +1,61 @@
+// Items whose names repeat across modules and impls
+pub mod net {
+    pub mod client {
+        pub struct Client {
+            pub addr: String,
+        }
+
+        impl Client {
+            pub fn new(addr: String) -> Self {
+                Client { addr }
+            }
+        }
+
+        impl Drop for Client {
+            fn drop(&mut self) {}
+        }
+
+        pub enum Error {
+            Refused,
+        }
+    }
+
+    pub mod server {
+        pub struct Server;
+
+        impl Server {
+            pub fn new() -> Self {
+                Server
+            }
+        }
+
+        impl super::Connect for Server {
+            fn connect(&self) -> bool {
+                true
+            }
+        }
+
+        pub enum Error {
+            AddrInUse,
+        }
+    }
+
+    pub trait Connect {
+        fn connect(&self) -> bool;
+    }
+
+    pub const DEFAULT_PORT: u16 = 8080;
+}
+
+impl<'a> net::Connect for &'a [u8] {
+    fn connect(&self) -> bool {
+        false
+    }
+}
+
+#[macro_export]
+macro_rules! connect {
+    () => {};
+}
+
+pub fn main() {}
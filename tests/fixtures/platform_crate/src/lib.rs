@@ -0,0 +1,18 @@
+//! Crate declaring one module for each platform, and a macro used in another file
+
+#[macro_export]
+macro_rules! trace {
+    ($name:expr) => {
+        let _ = $name;
+    };
+}
+
+#[cfg(unix)]
+#[path = "unix.rs"]
+mod imp;
+
+#[cfg(windows)]
+#[path = "windows.rs"]
+mod imp;
+
+pub mod worker;
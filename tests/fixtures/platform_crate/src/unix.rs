@@ -0,0 +1,5 @@
+pub struct Handle(pub i32);
+
+pub fn spawn() -> Handle {
+    Handle(1)
+}
@@ -0,0 +1,5 @@
+pub struct Handle(pub isize);
+
+pub fn spawn() -> Handle {
+    Handle(2)
+}
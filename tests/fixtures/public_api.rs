@@ -0,0 +1,62 @@
+// Items reachable from other crates through public modules and re-exports
+pub mod net {
+    pub struct Client;
+
+    impl Client {
+        pub fn connect() -> Self {
+            Client
+        }
+
+        fn retry(&self) {}
+    }
+
+    impl std::fmt::Display for Client {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn helper() {}
+
+    fn private_helper() {}
+}
+
+mod internal {
+    pub struct Hidden;
+
+    pub struct Exported;
+
+    pub mod deep {
+        pub fn tool() {}
+    }
+
+    pub trait Shape {
+        fn area(&self) -> f64;
+    }
+}
+
+pub use internal::deep as tools;
+pub use internal::{Exported, Shape};
+
+mod constants {
+    pub const VERSION: u32 = 1;
+}
+
+pub use constants::*;
+
+mod scoped {
+    pub mod inner {
+        pub(super) fn to_parent() {}
+
+        pub(in crate::scoped) fn to_scoped() {}
+
+        pub(self) fn to_self() {}
+
+        fn private() {}
+    }
+}
+
+#[macro_export]
+macro_rules! shout {
+    () => {};
+}
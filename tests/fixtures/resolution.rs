@@ -0,0 +1,57 @@
+//! This file contains types named through scopes and imports, for testing name resolution
+
+use std::collections::HashMap;
+
+use self::shapes::Circle as Round;
+
+pub struct Config {
+    pub name: String,
+    pub values: HashMap<String, u32>,
+}
+
+pub mod shapes {
+    pub struct Circle {
+        pub radius: f64,
+    }
+
+    pub struct Config {
+        pub sides: u8,
+    }
+
+    pub mod nested {
+        use super::super::Config as RootConfig;
+        use crate::shapes::*;
+
+        pub struct Drawing {
+            pub outline: Circle,
+            pub local: Config,
+            pub root: RootConfig,
+            pub parent: super::Circle,
+            pub missing: Missing,
+        }
+    }
+}
+
+pub struct Wrapper<T> {
+    pub inner: T,
+    pub config: Config,
+    pub round: Round,
+    pub shape: shapes::Circle,
+    pub absolute: crate::shapes::Circle,
+    pub external: serde::Value,
+}
+
+pub struct Buffers {
+    pub short: Vec<u8>,
+    pub long: std::vec::Vec<u8>,
+    pub words: Vec<u16>,
+}
+
+pub struct Projections<T: Iterator, I: IntoIterator> {
+    pub item: T::Item,
+    pub iter: I::IntoIter,
+}
+
+pub trait Outline {}
+
+pub trait Framed: Outline + self::Outline {}
@@ -0,0 +1,15 @@
+//! Crate root for testing whole-crate analysis
+
+pub mod net;
+mod util;
+
+#[path = "platform_impl.rs"]
+mod platform;
+
+pub mod inline {
+    pub mod nested;
+}
+
+pub fn root_function() -> &'static str {
+    platform::platform_name()
+}
@@ -0,0 +1,7 @@
+//! Client module backed by a non-`mod.rs` file
+
+pub mod retry;
+
+pub struct Client {
+    pub connection: super::Connection,
+}
@@ -0,0 +1,3 @@
+pub fn retry_count() -> u32 {
+    3
+}
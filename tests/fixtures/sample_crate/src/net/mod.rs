@@ -0,0 +1,8 @@
+//! Networking module backed by a `mod.rs` file
+
+pub mod client;
+
+// An open connection to a peer
+pub struct Connection {
+    pub address: String,
+}
@@ -0,0 +1,3 @@
+pub fn platform_name() -> &'static str {
+    "test"
+}
@@ -0,0 +1,17 @@
+use core_lib::shared;
+use core_lib::Reexported;
+
+pub fn build() -> core_lib::model::Record {
+    shared();
+    core_lib::model::Record { id: 1 }
+}
+
+pub fn wrap(value: Reexported) -> Reexported {
+    value
+}
+
+pub struct Config;
+
+fn main() {
+    build();
+}
@@ -0,0 +1,15 @@
+pub mod model {
+    pub struct Record {
+        pub id: u64,
+    }
+}
+
+mod inner {
+    pub struct Reexported;
+}
+
+pub use inner::Reexported;
+
+pub struct Config;
+
+pub fn shared() {}
@@ -0,0 +1,59 @@
+// Public API after changes of every semver level
+pub fn connect(addr: &str) -> bool {
+    !addr.is_empty()
+}
+
+pub fn unchanged(x: u32) -> u32 {
+    x + 0
+}
+
+pub fn bounded<T: Clone + Send>(value: T) {}
+
+pub fn loosened<T: Clone>(value: T) {}
+
+pub fn added() {}
+
+pub struct Config {
+    pub name: String,
+    pub retries: u32,
+    pub timeout: u64,
+}
+
+pub struct Handle {
+    pub id: u64,
+    secret: u64,
+    pub label: String,
+}
+
+pub enum Mode {
+    Fast,
+    Slow,
+    Auto,
+}
+
+#[non_exhaustive]
+pub enum Level {
+    Low,
+    High,
+    Max,
+}
+
+pub trait Store {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn flush(&self) {}
+
+    fn delete(&self, key: &str);
+
+    fn len(&self) -> usize {
+        0
+    }
+}
+
+pub trait Fresh {
+    fn run(&self);
+}
+
+mod internal {
+    pub fn helper(x: u8) {}
+}
@@ -0,0 +1,45 @@
+// Public API before the changes in `new.rs`
+pub fn connect(addr: String) -> bool {
+    !addr.is_empty()
+}
+
+pub fn removed() {}
+
+pub fn unchanged(x: u32) -> u32 {
+    x
+}
+
+pub fn bounded<T: Clone>(value: T) {}
+
+pub fn loosened<T: Clone + Send>(value: T) {}
+
+pub struct Config {
+    pub name: String,
+    pub retries: u32,
+}
+
+pub struct Handle {
+    pub id: u64,
+    secret: u64,
+}
+
+pub enum Mode {
+    Fast,
+    Slow,
+}
+
+#[non_exhaustive]
+pub enum Level {
+    Low,
+    High,
+}
+
+pub trait Store {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn flush(&self) {}
+}
+
+mod internal {
+    pub fn helper() {}
+}
@@ -0,0 +1,19 @@
+// Sample with test-only items
+
+pub fn production_function() -> i32 {
+    42
+}
+
+#[test]
+fn top_level_test() {
+    assert_eq!(production_function(), 42);
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn module_test() {}
+}
+
+#[cfg(all(test, feature = "slow"))]
+pub fn slow_test_helper() {}
@@ -0,0 +1,29 @@
+// Items of every visibility, for testing the visibility filter
+
+pub struct PublicStruct;
+
+pub(crate) struct CrateStruct;
+
+struct PrivateStruct;
+
+pub fn public_function() {}
+
+pub(crate) fn crate_function() {}
+
+fn private_function() {}
+
+pub struct Widget;
+
+impl Widget {
+    pub fn public_method(&self) {}
+
+    pub(crate) fn crate_method(&self) {}
+
+    fn private_method(&self) {}
+}
+
+impl Clone for Widget {
+    fn clone(&self) -> Self {
+        Widget
+    }
+}
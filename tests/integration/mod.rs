@@ -1,2 +1,3 @@
 // This module will include all integration tests
 pub mod full_graph_tests;
+pub mod workspace_tests;
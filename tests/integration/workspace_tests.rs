@@ -0,0 +1,116 @@
+use std::path::Path;
+use syn_parser::analyze_workspace;
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::TypeKind;
+
+use crate::common::{find_function_by_name, find_struct_by_name};
+
+#[test]
+fn test_workspace_members() {
+    let workspace = analyze_workspace(Path::new("tests/fixtures/sample_workspace"))
+        .expect("Failed to analyze workspace");
+
+    let mut names: Vec<&str> = workspace.crates.iter().map(|c| c.name.as_str()).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["app", "core_lib"],
+        "excluded crate should be skipped"
+    );
+
+    let core_lib = workspace.find_crate("core_lib").unwrap();
+    assert!(find_struct_by_name(&core_lib.graph, "Record").is_some());
+    assert!(core_lib
+        .graph
+        .modules
+        .iter()
+        .any(|m| m.id == core_lib.root_module && m.name == "crate"));
+}
+
+#[test]
+fn test_workspace_cross_crate_relations() {
+    let workspace = analyze_workspace(Path::new("tests/fixtures/sample_workspace"))
+        .expect("Failed to analyze workspace");
+
+    let app = workspace.find_crate("app").unwrap();
+    let core_lib = workspace.find_crate("core_lib").unwrap();
+    let record = find_struct_by_name(&core_lib.graph, "Record").unwrap();
+    let shared = find_function_by_name(&core_lib.graph, "shared").unwrap();
+
+    // `use core_lib::shared` links to the function
+    let shared_relation = workspace
+        .relations
+        .iter()
+        .find(|r| {
+            r.source_crate == "app"
+                && r.target_crate == "core_lib"
+                && r.target == shared.id
+                && r.kind == RelationKind::Uses
+        })
+        .expect("No relation to shared");
+    let import = app
+        .graph
+        .modules
+        .iter()
+        .flat_map(|m| &m.imports)
+        .find(|import| import.id == shared_relation.source)
+        .expect("Relation source is not an import");
+    assert_eq!(import.path, vec!["core_lib", "shared"]);
+
+    // The return type `core_lib::model::Record` links to the struct
+    let record_relation = workspace
+        .relations
+        .iter()
+        .find(|r| r.target == record.id)
+        .expect("No relation to Record");
+    let source_type = app
+        .graph
+        .type_graph
+        .iter()
+        .find(|t| t.id == record_relation.source)
+        .unwrap();
+    assert!(matches!(
+        &source_type.kind,
+        TypeKind::Named { path, .. } if path.last().unwrap() == "Record"
+    ));
+}
+
+#[test]
+fn test_workspace_relations_follow_reexports() {
+    let workspace = analyze_workspace(Path::new("tests/fixtures/sample_workspace"))
+        .expect("Failed to analyze workspace");
+
+    let app = workspace.find_crate("app").unwrap();
+    let core_lib = workspace.find_crate("core_lib").unwrap();
+    let reexported = find_struct_by_name(&core_lib.graph, "Reexported").unwrap();
+
+    // `use core_lib::Reexported` names the struct through `pub use inner::Reexported`
+    let relation = workspace
+        .relations
+        .iter()
+        .find(|r| r.target_crate == "core_lib" && r.target == reexported.id)
+        .expect("No relation to Reexported");
+    let import = app
+        .graph
+        .modules
+        .iter()
+        .flat_map(|m| &m.imports)
+        .find(|import| import.id == relation.source)
+        .expect("Relation source is not an import");
+    assert_eq!(import.path, vec!["core_lib", "Reexported"]);
+}
+
+#[test]
+fn test_workspace_member_ids_are_distinct() {
+    let workspace = analyze_workspace(Path::new("tests/fixtures/sample_workspace"))
+        .expect("Failed to analyze workspace");
+
+    let app = workspace.find_crate("app").unwrap();
+    let core_lib = workspace.find_crate("core_lib").unwrap();
+
+    // Both members define `crate::Config`
+    let app_config = find_struct_by_name(&app.graph, "Config").unwrap();
+    let core_config = find_struct_by_name(&core_lib.graph, "Config").unwrap();
+    assert_ne!(app_config.id, core_config.id);
+    assert_ne!(app.root_module, core_lib.root_module);
+}
@@ -1,5 +1,6 @@
 // This module will include all tests
 pub mod common;
+pub mod config;
 pub mod parser;
 pub mod serialization;
 pub mod integration;
@@ -1,5 +1,55 @@
+use std::path::Path;
+use syn_parser::{analyze_code, analyze_crate, save_to_ron, SynParserError};
+
 // Placeholder for basic parsing tests
 #[test]
 fn test_basic_parsing() {
     // Add basic parsing tests here
 }
+
+#[test]
+fn test_parse_error_reports_file_and_position() {
+    let path = Path::new("tests/fixtures/invalid_syntax.rs");
+    let err = analyze_code(path).expect_err("invalid syntax should fail to parse");
+
+    match &err {
+        SynParserError::Parse {
+            path: err_path,
+            line,
+            ..
+        } => {
+            assert_eq!(err_path, path);
+            assert_eq!(*line, 4);
+        }
+        other => panic!("Expected parse error, found {:?}", other),
+    }
+    assert!(err
+        .to_string()
+        .starts_with("tests/fixtures/invalid_syntax.rs:4:"));
+}
+
+#[test]
+fn test_missing_file_is_io_error() {
+    let path = Path::new("tests/fixtures/does_not_exist.rs");
+    let err = analyze_code(path).expect_err("missing file should fail");
+
+    assert!(matches!(err, SynParserError::Io { .. }));
+    assert_eq!(err.path(), Some(path));
+}
+
+#[test]
+fn test_unwritable_output_is_write_error() {
+    let graph = analyze_code(Path::new("tests/fixtures/functions.rs")).unwrap();
+    let path = Path::new("tests/fixtures/no_such_dir/graph.ron");
+    let err = save_to_ron(&graph, path).expect_err("missing directory should fail");
+
+    assert!(matches!(err, SynParserError::Write { .. }));
+    assert!(err.to_string().starts_with("failed to write"));
+}
+
+#[test]
+fn test_missing_crate_root_is_resolution_error() {
+    let err = analyze_crate(Path::new("tests/fixtures")).expect_err("no crate root");
+
+    assert!(matches!(err, SynParserError::Resolution { .. }));
+}
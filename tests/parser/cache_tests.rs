@@ -0,0 +1,138 @@
+use crate::common::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn_parser::{
+    analyze_crate, analyze_crate_incremental, analyze_crate_incremental_with_options,
+    AnalysisCache, CodeGraph, ParseOptions,
+};
+
+// Copy the `sample_crate` fixture somewhere its files can be edited
+fn copy_sample_crate(test_name: &str) -> PathBuf {
+    let dest = std::env::temp_dir().join(format!(
+        "syn_parser_cache_{}_{}",
+        test_name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dest);
+    copy_dir(Path::new("tests/fixtures/sample_crate"), &dest);
+    dest
+}
+
+fn copy_dir(src: &Path, dest: &Path) {
+    fs::create_dir_all(dest).unwrap();
+    for entry in fs::read_dir(src).unwrap() {
+        let path = entry.unwrap().path();
+        let target = dest.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+fn to_ron(graph: &CodeGraph) -> String {
+    ron::to_string(graph).expect("Failed to serialize graph")
+}
+
+#[test]
+fn test_cold_cache_matches_analyze_crate() {
+    let root = copy_sample_crate("cold");
+    let mut cache = AnalysisCache::new();
+
+    let graph = analyze_crate_incremental(&root, &mut cache).unwrap();
+    let cold = analyze_crate(&root).unwrap();
+
+    assert_eq!(to_ron(&graph), to_ron(&cold));
+    assert!(cache.last_run().reused.is_empty());
+    assert_eq!(cache.last_run().visited.len(), 7);
+    assert_eq!(cache.len(), 7);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_unchanged_files_are_reused() {
+    let root = copy_sample_crate("unchanged");
+    let mut cache = AnalysisCache::new();
+
+    let first = analyze_crate_incremental(&root, &mut cache).unwrap();
+    let second = analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    assert_eq!(to_ron(&first), to_ron(&second));
+    assert!(cache.last_run().visited.is_empty());
+    assert_eq!(cache.last_run().reused.len(), 7);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_changed_file_is_visited_again() {
+    let root = copy_sample_crate("changed");
+    let mut cache = AnalysisCache::new();
+    analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    let util = root.join("src/util.rs");
+    fs::write(
+        &util,
+        "pub fn helper() {}\n\npub fn added() -> u8 {\n    1\n}\n",
+    )
+    .unwrap();
+    let graph = analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    assert_eq!(cache.last_run().visited, [util]);
+    assert_eq!(cache.last_run().reused.len(), 6);
+    assert!(find_function_by_name(&graph, "added").is_some());
+    // Names are resolved again over the whole crate, so the graph is the same as a cold run
+    assert_eq!(to_ron(&graph), to_ron(&analyze_crate(&root).unwrap()));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_removed_module_is_dropped_from_cache() {
+    let root = copy_sample_crate("removed");
+    let mut cache = AnalysisCache::new();
+    analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    let lib = root.join("src/lib.rs");
+    let source = fs::read_to_string(&lib).unwrap();
+    fs::write(&lib, source.replace("mod util;\n", "")).unwrap();
+    let graph = analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    assert_eq!(cache.last_run().visited, [lib]);
+    assert_eq!(cache.len(), 6);
+    assert!(find_module_by_name(&graph, "util").is_none());
+    assert_eq!(to_ron(&graph), to_ron(&analyze_crate(&root).unwrap()));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_cache_saved_to_disk() {
+    let root = copy_sample_crate("saved");
+    let cache_file = root.join("analysis_cache.ron");
+    let mut cache = AnalysisCache::load(&cache_file).unwrap();
+    assert!(cache.is_empty());
+
+    let first = analyze_crate_incremental(&root, &mut cache).unwrap();
+    cache.save(&cache_file).unwrap();
+
+    let mut loaded = AnalysisCache::load(&cache_file).unwrap();
+    let second = analyze_crate_incremental(&root, &mut loaded).unwrap();
+    assert!(loaded.last_run().visited.is_empty());
+    assert_eq!(to_ron(&first), to_ron(&second));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_changed_options_empty_the_cache() {
+    let root = copy_sample_crate("options");
+    let mut cache = AnalysisCache::new();
+    analyze_crate_incremental(&root, &mut cache).unwrap();
+
+    let options = ParseOptions {
+        include_bodies: false,
+        ..ParseOptions::default()
+    };
+    analyze_crate_incremental_with_options(&root, &options, &mut cache).unwrap();
+    assert!(cache.last_run().reused.is_empty());
+    assert_eq!(cache.last_run().visited.len(), 7);
+    fs::remove_dir_all(&root).unwrap();
+}
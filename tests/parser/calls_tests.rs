@@ -0,0 +1,412 @@
+use crate::common::*;
+use syn_parser::parser::graph::CodeGraph;
+use syn_parser::parser::nodes::{CallKind, CallNode, FunctionNode, NodeId, Receiver};
+use syn_parser::parser::relations::{CallConfidence, RelationKind};
+use syn_parser::parser::types::Resolution;
+
+// Calls in the body of the function with the given ID, in source order
+fn calls_from(graph: &CodeGraph, caller: NodeId) -> Vec<&CallNode> {
+    graph.calls.iter().filter(|c| c.caller == caller).collect()
+}
+
+// Call with the given path as written
+fn find_call<'a>(calls: &[&'a CallNode], path: &str) -> &'a CallNode {
+    calls
+        .iter()
+        .find(|c| c.path.join("::") == path)
+        .copied()
+        .unwrap_or_else(|| panic!("call {} not found", path))
+}
+
+// Targets of the `Calls` relations of a function
+fn callees(graph: &CodeGraph, caller: NodeId) -> Vec<NodeId> {
+    graph
+        .relations
+        .iter()
+        .filter(|r| r.source == caller && matches!(r.kind, RelationKind::Calls { .. }))
+        .map(|r| r.target)
+        .collect()
+}
+
+// Kind of the `Calls` relation from a caller to a callee
+fn calls_kind(graph: &CodeGraph, caller: NodeId, callee: NodeId) -> Option<RelationKind> {
+    graph
+        .relations
+        .iter()
+        .find(|r| r.source == caller && r.target == callee)
+        .map(|r| r.kind)
+}
+
+// Method with the given name in the impl of the given trait, or the inherent impl, for a type
+fn impl_method<'a>(
+    graph: &'a CodeGraph,
+    type_name: &str,
+    trait_name: Option<&str>,
+    name: &str,
+) -> &'a FunctionNode {
+    graph
+        .impls
+        .iter()
+        .filter(|imp| {
+            let spelling = |id| {
+                graph
+                    .type_graph
+                    .iter()
+                    .find(|t| t.id == id)
+                    .map(|t| t.spellings[0].as_str())
+            };
+            spelling(imp.self_type) == Some(type_name)
+                && imp.trait_type.and_then(spelling) == trait_name
+        })
+        .flat_map(|imp| &imp.methods)
+        .find(|m| m.name == name)
+        .unwrap_or_else(|| panic!("method {} not found", name))
+}
+
+// Method with the given name declared by a trait
+fn trait_method<'a>(graph: &'a CodeGraph, trait_name: &str, name: &str) -> &'a FunctionNode {
+    let trait_node = find_trait_by_name(graph, trait_name).unwrap();
+    trait_node.methods.iter().find(|m| m.name == name).unwrap()
+}
+
+#[test]
+fn test_calls_resolve_to_local_functions() {
+    let graph = parse_fixture("calls.rs");
+    let run = find_function_by_name(&graph, "run").unwrap();
+    let helper = find_function_by_name(&graph, "helper").unwrap();
+    let parse = find_function_by_name(&graph, "parse").unwrap();
+    let counter_impl = find_impl_for_type(&graph, "Counter").unwrap();
+    let new = counter_impl
+        .methods
+        .iter()
+        .find(|m| m.name == "new")
+        .unwrap();
+
+    let calls = calls_from(&graph, run.id);
+    // `Type::assoc`
+    let counter_new = find_call(&calls, "Counter::new");
+    assert_eq!(counter_new.kind, CallKind::Function);
+    assert_eq!(counter_new.resolution, Some(Resolution::Local));
+    assert_eq!(counter_new.callee, Some(new.id));
+    assert_eq!(
+        counter_new.callee_path.as_deref(),
+        Some("root::Counter::new")
+    );
+    // Path through a module
+    assert_eq!(find_call(&calls, "util::helper").callee, Some(helper.id));
+    // Function named without being called, through an import
+    let reference = find_call(&calls, "parse");
+    assert_eq!(reference.kind, CallKind::Reference);
+    assert_eq!(reference.callee, Some(parse.id));
+
+    let targets = callees(&graph, run.id);
+    assert!(targets.contains(&new.id));
+    assert!(targets.contains(&helper.id));
+    assert!(targets.contains(&parse.id));
+}
+
+#[test]
+fn test_calls_in_methods() {
+    let graph = parse_fixture("calls.rs");
+    let counter_impl = find_impl_for_type(&graph, "Counter").unwrap();
+    let method = |name: &str| {
+        counter_impl
+            .methods
+            .iter()
+            .find(|m| m.name == name)
+            .unwrap()
+    };
+    let parse = find_function_by_name(&graph, "parse").unwrap();
+
+    // `Self::` stands for the impl type
+    let reset = calls_from(&graph, method("reset").id);
+    let self_new: Vec<_> = reset.iter().filter(|c| c.path == ["Self", "new"]).collect();
+    assert_eq!(self_new.len(), 2);
+    assert!(self_new.iter().all(|c| c.callee == Some(method("new").id)));
+    // Both calls are linked by one relation
+    assert_eq!(callees(&graph, method("reset").id).len(), 2);
+
+    // Imported function called from a closure
+    let add = calls_from(&graph, method("add").id);
+    assert_eq!(find_call(&add, "parse").callee, Some(parse.id));
+    assert_eq!(find_call(&add, "parse").kind, CallKind::Function);
+
+    // Default methods of traits
+    let describe = find_trait_by_name(&graph, "Describe").unwrap();
+    let default = describe
+        .methods
+        .iter()
+        .find(|m| m.name == "describe")
+        .unwrap();
+    let calls = calls_from(&graph, default.id);
+    let helper = find_function_by_name(&graph, "helper").unwrap();
+    assert_eq!(find_call(&calls, "util::helper").callee, Some(helper.id));
+}
+
+#[test]
+fn test_method_calls_without_receiver_type() {
+    let graph = parse_fixture("calls.rs");
+    let run = find_function_by_name(&graph, "run").unwrap();
+    let counter_impl = find_impl_for_type(&graph, "Counter").unwrap();
+    let add = counter_impl
+        .methods
+        .iter()
+        .find(|m| m.name == "add")
+        .unwrap();
+
+    // `counter` has no type annotation, but `add` is the only method of the name
+    let call = find_call(&calls_from(&graph, run.id), "add");
+    assert_eq!(call.kind, CallKind::Method);
+    assert_eq!(call.receiver, Some(Receiver::Unknown));
+    assert_eq!(call.callee, Some(add.id));
+    assert_eq!(call.confidence, Some(CallConfidence::Heuristic));
+    assert_eq!(
+        calls_kind(&graph, run.id, add.id),
+        Some(RelationKind::Calls {
+            confidence: CallConfidence::Heuristic
+        })
+    );
+
+    // Methods of other crates are not guessed at
+    let lengths = find_call(&calls_from(&graph, run.id), "collect");
+    assert_eq!(lengths.resolution, Some(Resolution::Unresolved));
+    assert_eq!(lengths.confidence, None);
+    assert_eq!(
+        calls_kind(&graph, run.id, lengths.id),
+        Some(RelationKind::Calls {
+            confidence: CallConfidence::Unresolved
+        })
+    );
+}
+
+#[test]
+fn test_external_and_unresolved_calls() {
+    let graph = parse_fixture("calls.rs");
+    let run = find_function_by_name(&graph, "run").unwrap();
+    let calls = calls_from(&graph, run.id);
+
+    let vec_new = find_call(&calls, "Vec::new");
+    assert_eq!(
+        vec_new.resolution,
+        Some(Resolution::External {
+            crate_name: "std".to_string()
+        })
+    );
+    assert_eq!(vec_new.callee_path.as_deref(), Some("std::vec::Vec::new"));
+    assert_eq!(
+        calls_kind(&graph, run.id, vec_new.id),
+        Some(RelationKind::Calls {
+            confidence: CallConfidence::External
+        })
+    );
+    assert_eq!(
+        find_call(&calls, "Some").callee_path.as_deref(),
+        Some("std::option::Option::Some")
+    );
+
+    let missing = find_call(&calls, "missing");
+    assert_eq!(missing.resolution, Some(Resolution::Unresolved));
+    assert_eq!(
+        calls_kind(&graph, run.id, missing.id),
+        Some(RelationKind::Calls {
+            confidence: CallConfidence::Unresolved
+        })
+    );
+
+    let counter_impl = find_impl_for_type(&graph, "Counter").unwrap();
+    let reset = counter_impl
+        .methods
+        .iter()
+        .find(|m| m.name == "reset")
+        .unwrap();
+    let drop = find_call(&calls_from(&graph, reset.id), "std::mem::drop");
+    assert_eq!(drop.callee_path.as_deref(), Some("std::mem::drop"));
+
+    let caller = find_function_by_name(&graph, "missing_caller").unwrap();
+    let to_string = find_call(&calls_from(&graph, caller.id), "serde_json::to_string");
+    assert_eq!(
+        to_string.resolution,
+        Some(Resolution::External {
+            crate_name: "serde_json".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_only_function_references_are_kept() {
+    let graph = parse_fixture("calls.rs");
+    // Local variables, `None` and constants are not calls
+    assert!(graph
+        .calls
+        .iter()
+        .all(|c| c.kind != CallKind::Reference || c.callee.is_some()));
+    assert!(!graph
+        .calls
+        .iter()
+        .any(|c| c.path == ["counter"] || c.path == ["None"]));
+    // Calls outside of function bodies are not recorded
+    assert!(!graph
+        .calls
+        .iter()
+        .any(|c| c.path == ["util", "helper_value"]));
+}
+
+#[test]
+fn test_calls_in_macro_arguments() {
+    let graph = parse_fixture("calls.rs");
+    let in_macros = find_function_by_name(&graph, "in_macros").unwrap();
+    let helper = find_function_by_name(&graph, "helper").unwrap();
+    let parse = find_function_by_name(&graph, "parse").unwrap();
+
+    let calls = calls_from(&graph, in_macros.id);
+    let paths: Vec<_> = calls.iter().map(|c| c.path.join("::")).collect();
+    assert_eq!(paths, ["util::helper", "util::helper", "parse", "parse"]);
+
+    let targets = callees(&graph, in_macros.id);
+    assert!(targets.contains(&helper.id));
+    assert!(targets.contains(&parse.id));
+}
+
+#[test]
+fn test_local_bindings_shadow_functions() {
+    let graph = parse_fixture("calls.rs");
+    let shadowed = find_function_by_name(&graph, "shadowed").unwrap();
+    let parse = find_function_by_name(&graph, "parse").unwrap();
+
+    // `parse` is a local, and `helper` a closure, neither of which names an item
+    let calls = calls_from(&graph, shadowed.id);
+    assert!(calls.iter().all(|c| c.kind == CallKind::Method));
+    assert!(!callees(&graph, shadowed.id).contains(&parse.id));
+}
+
+#[test]
+fn test_call_spans() {
+    let graph = parse_fixture("calls.rs");
+    let run = find_function_by_name(&graph, "run").unwrap();
+    let calls = calls_from(&graph, run.id);
+    let counter_new = find_call(&calls, "Counter::new");
+
+    assert_eq!(
+        (counter_new.span.start_line, counter_new.span.start_col),
+        (43, 22)
+    );
+    assert_eq!(
+        (counter_new.span.end_line, counter_new.span.end_col),
+        (43, 36)
+    );
+    assert!(counter_new.span.file.ends_with("calls.rs"));
+}
+
+#[test]
+fn test_methods_resolved_through_receiver_type() {
+    let graph = parse_fixture("method_calls.rs");
+    let engine_process = impl_method(&graph, "Engine", None, "process");
+    let parser_process = impl_method(&graph, "Parser", None, "process");
+
+    // `self`
+    let start = impl_method(&graph, "Engine", None, "start");
+    let call = find_call(&calls_from(&graph, start.id), "process");
+    assert_eq!(call.receiver, Some(Receiver::SelfValue));
+    assert_eq!(call.callee, Some(engine_process.id));
+    assert_eq!(call.confidence, Some(CallConfidence::Exact));
+    assert_eq!(
+        calls_kind(&graph, start.id, engine_process.id),
+        Some(RelationKind::Calls {
+            confidence: CallConfidence::Exact
+        })
+    );
+
+    let run = impl_method(&graph, "Machine", None, "run");
+    let calls = calls_from(&graph, run.id);
+    let process: Vec<_> = calls.iter().filter(|c| c.path == ["process"]).collect();
+    // Field of a struct
+    assert_eq!(
+        process[0].receiver,
+        Some(Receiver::Field(
+            Box::new(Receiver::SelfValue),
+            "engine".to_string()
+        ))
+    );
+    assert_eq!(process[0].callee, Some(engine_process.id));
+    // Parameter
+    assert_eq!(
+        process[1].receiver,
+        Some(Receiver::Typed(vec!["Parser".to_string()]))
+    );
+    assert_eq!(process[1].callee, Some(parser_process.id));
+    assert!(process
+        .iter()
+        .all(|c| c.confidence == Some(CallConfidence::Exact)));
+
+    // Trait object parameter
+    let export_render = trait_method(&graph, "Export", "render");
+    let render: Vec<_> = calls.iter().filter(|c| c.path == ["render"]).collect();
+    assert_eq!(render[1].callee, Some(export_render.id));
+
+    // Method of a type of another crate
+    let len = find_call(&calls, "len");
+    assert_eq!(len.callee_path.as_deref(), Some("std::string::String::len"));
+    assert_eq!(
+        len.resolution,
+        Some(Resolution::External {
+            crate_name: "std".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_trait_methods_resolved_through_receiver_type() {
+    let graph = parse_fixture("method_calls.rs");
+
+    // `self` in a default method stands for the trait
+    let show = trait_method(&graph, "Render", "show");
+    let render = trait_method(&graph, "Render", "render");
+    let call = find_call(&calls_from(&graph, show.id), "render");
+    assert_eq!(call.callee, Some(render.id));
+    assert_eq!(call.confidence, Some(CallConfidence::Exact));
+
+    // Default method of a trait the parameter type implements, and struct literal
+    let tick = find_function_by_name(&graph, "tick").unwrap();
+    let calls = calls_from(&graph, tick.id);
+    assert_eq!(find_call(&calls, "show").callee, Some(show.id));
+    let start = impl_method(&graph, "Engine", None, "start");
+    assert_eq!(find_call(&calls, "start").callee, Some(start.id));
+}
+
+#[test]
+fn test_ambiguous_method_calls() {
+    let graph = parse_fixture("method_calls.rs");
+
+    // `Engine` implements two traits with a `render` method
+    let run = impl_method(&graph, "Machine", None, "run");
+    let call = find_call(&calls_from(&graph, run.id), "render");
+    let candidates = [
+        impl_method(&graph, "Engine", Some("Render"), "render").id,
+        impl_method(&graph, "Engine", Some("Export"), "render").id,
+    ];
+    assert_eq!(call.callee, None);
+    assert_eq!(call.candidates, candidates);
+    assert_eq!(call.confidence, Some(CallConfidence::Ambiguous));
+    for candidate in candidates {
+        assert_eq!(
+            calls_kind(&graph, run.id, candidate),
+            Some(RelationKind::Calls {
+                confidence: CallConfidence::Ambiguous
+            })
+        );
+    }
+
+    // `other` is shadowed by a binding without a type, so every `process` may be meant
+    let tick = find_function_by_name(&graph, "tick").unwrap();
+    let calls = calls_from(&graph, tick.id);
+    let process = find_call(&calls, "process");
+    assert_eq!(process.receiver, Some(Receiver::Unknown));
+    assert_eq!(process.candidates.len(), 2);
+    assert_eq!(process.confidence, Some(CallConfidence::Ambiguous));
+
+    // The only method of the name
+    let halt = impl_method(&graph, "Parser", None, "halt");
+    let call = find_call(&calls, "halt");
+    assert_eq!(call.callee, Some(halt.id));
+    assert_eq!(call.confidence, Some(CallConfidence::Heuristic));
+}
@@ -0,0 +1,106 @@
+use crate::common::*;
+use syn_parser::parser::nodes::{CommentKind, CommentNode, CommentPlacement};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::CodeGraph;
+
+fn find_comment<'a>(graph: &'a CodeGraph, text: &str) -> &'a CommentNode {
+    graph
+        .comments
+        .iter()
+        .find(|c| c.text.contains(text))
+        .unwrap_or_else(|| panic!("comment containing {:?} not found", text))
+}
+
+#[test]
+fn test_comments_are_found_outside_literals() {
+    let graph = parse_fixture("comments.rs");
+
+    assert_eq!(graph.comments.len(), 8);
+    assert!(graph
+        .comments
+        .iter()
+        .all(|c| !c.text.contains("not a comment")));
+    // Doc comments are stored as docstrings instead
+    assert!(graph.comments.iter().all(|c| !c.text.starts_with("//!")));
+}
+
+#[test]
+fn test_leading_and_trailing_comments() {
+    let graph = parse_fixture("comments.rs");
+    let config = find_struct_by_name(&graph, "Config").expect("Config not found");
+
+    let leading = find_comment(&graph, "Configuration for the reader");
+    assert_eq!(leading.target, config.id);
+    assert_eq!(leading.placement, CommentPlacement::Leading);
+    assert_eq!(leading.span.start_line, 3);
+
+    let field = find_comment(&graph, "Size of the read buffer");
+    assert_eq!(field.target, config.fields[0].id);
+    assert_eq!(field.placement, CommentPlacement::Leading);
+
+    let trailing = find_comment(&graph, "print progress");
+    assert_eq!(trailing.target, config.fields[1].id);
+    assert_eq!(trailing.placement, CommentPlacement::Trailing);
+
+    assert!(graph.relations.iter().any(|r| r.source == trailing.id
+        && r.target == config.fields[1].id
+        && r.kind == RelationKind::Annotates));
+}
+
+#[test]
+fn test_comment_markers() {
+    let graph = parse_fixture("comments.rs");
+    let read = find_function_by_name(&graph, "read").expect("read not found");
+
+    let todo = find_comment(&graph, "support async reads");
+    assert_eq!(todo.kind, CommentKind::Todo);
+    assert_eq!(todo.target, read.id);
+
+    // Comments inside a body belong to the function
+    let safety = find_comment(&graph, "is non-empty");
+    assert_eq!(safety.kind, CommentKind::Safety);
+    assert_eq!(safety.target, read.id);
+    assert_eq!(safety.placement, CommentPlacement::Inner);
+
+    let fixme = find_comment(&graph, "ignores the buffer size");
+    assert_eq!(fixme.kind, CommentKind::Fixme);
+
+    assert_eq!(
+        find_comment(&graph, "print progress").kind,
+        CommentKind::Regular
+    );
+}
+
+#[test]
+fn test_comments_record_their_module() {
+    let graph = parse_fixture("comments.rs");
+    let inner = find_module_by_name(&graph, "inner").expect("inner not found");
+    let legacy = find_function_by_name(&graph, "legacy").expect("legacy not found");
+
+    let hack = find_comment(&graph, "re-exported");
+    assert_eq!(hack.kind, CommentKind::Hack);
+    assert_eq!(hack.target, legacy.id);
+    assert_eq!(hack.module, inner.id);
+
+    let dangling = find_comment(&graph, "Nothing follows");
+    assert_eq!(dangling.target, inner.id);
+    assert_eq!(dangling.placement, CommentPlacement::Inner);
+
+    let todos: Vec<_> = graph
+        .comments
+        .iter()
+        .filter(|c| c.module == graph.modules[0].id && c.kind != CommentKind::Regular)
+        .collect();
+    assert_eq!(todos.len(), 3);
+}
+
+#[test]
+fn test_comments_in_module_files() {
+    let graph = parse_fixture_crate("sample_crate");
+    let net = find_module_by_name(&graph, "net").expect("net not found");
+
+    assert!(graph
+        .comments
+        .iter()
+        .any(|c| c.module == net.id && c.span.file.ends_with("net/mod.rs")));
+}
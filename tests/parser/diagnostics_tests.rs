@@ -0,0 +1,72 @@
+use crate::common::*;
+use std::path::Path;
+use syn_parser::{analyze_crate, analyze_crate_tolerant};
+
+#[test]
+fn test_tolerant_crate_keeps_parsable_items() {
+    let graph = analyze_crate_tolerant(Path::new("tests/fixtures/broken_crate"))
+        .expect("Tolerant analysis should produce a graph");
+
+    assert!(find_function_by_name(&graph, "intact").is_some());
+    assert!(find_struct_by_name(&graph, "Complete").is_some());
+    assert!(find_function_by_name(&graph, "after_broken").is_some());
+    assert!(graph.values.iter().any(|v| v.name == "WITH_BLOCK"));
+    assert!(find_function_by_name(&graph, "unfinished").is_none());
+}
+
+#[test]
+fn test_tolerant_crate_records_diagnostics() {
+    let graph = analyze_crate_tolerant(Path::new("tests/fixtures/broken_crate")).unwrap();
+
+    // The broken function is reported in the file it is in
+    let half_written = find_module_by_name(&graph, "half_written").unwrap();
+    assert_eq!(half_written.diagnostics.len(), 1);
+    let diagnostic = &half_written.diagnostics[0];
+    assert!(diagnostic.file.ends_with("half_written.rs"));
+    assert_eq!(diagnostic.line, 6);
+
+    // A file that cannot be tokenized is reported as a whole
+    let unbalanced = find_module_by_name(&graph, "unbalanced").unwrap();
+    assert_eq!(unbalanced.diagnostics.len(), 1);
+    assert!(unbalanced.items.is_empty());
+
+    // A missing module file is reported at its declaration
+    let missing = find_module_by_name(&graph, "missing").unwrap();
+    assert_eq!(missing.diagnostics.len(), 1);
+    assert!(missing.diagnostics[0].file.ends_with("lib.rs"));
+    assert_eq!(missing.diagnostics[0].line, 5);
+
+    let root = find_module_by_name(&graph, "crate").unwrap();
+    assert!(root.diagnostics.is_empty());
+}
+
+#[test]
+fn test_strict_crate_fails_on_broken_file() {
+    assert!(analyze_crate(Path::new("tests/fixtures/broken_crate")).is_err());
+}
+
+#[test]
+fn test_tolerant_single_file() {
+    let graph = syn_parser::analyze_code_tolerant(Path::new("tests/fixtures/invalid_syntax.rs"))
+        .expect("Tolerant analysis should produce a graph");
+
+    let root = find_module_by_name(&graph, "root").unwrap();
+    assert_eq!(root.diagnostics.len(), 1);
+    assert_eq!(root.diagnostics[0].line, 4);
+}
+
+#[test]
+fn test_tolerant_crate_reports_module_file_cycle() {
+    let graph = analyze_crate_tolerant(Path::new("tests/fixtures/cyclic_crate"))
+        .expect("Tolerant analysis should produce a graph");
+
+    assert!(find_function_by_name(&graph, "intact").is_some());
+    assert!(find_function_by_name(&graph, "in_looped").is_some());
+
+    // The module that leads back to its own file is reported at its declaration
+    let again = find_module_by_name(&graph, "again").unwrap();
+    assert!(again.items.is_empty());
+    assert_eq!(again.diagnostics.len(), 1);
+    assert!(again.diagnostics[0].file.ends_with("looped.rs"));
+    assert_eq!(again.diagnostics[0].line, 4);
+}
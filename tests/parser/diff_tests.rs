@@ -0,0 +1,114 @@
+use crate::common::*;
+use syn_parser::parser::diff::{GraphDiff, NodeKind};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::TypeKind;
+use syn_parser::{diff, CodeGraph};
+
+fn fixture_graphs() -> (CodeGraph, CodeGraph) {
+    (parse_fixture("diff/old.rs"), parse_fixture("diff/new.rs"))
+}
+
+fn paths(summaries: &[syn_parser::parser::diff::NodeSummary]) -> Vec<&str> {
+    summaries.iter().map(|s| s.path.as_str()).collect()
+}
+
+#[test]
+fn test_identical_graphs() {
+    let old = parse_fixture("diff/old.rs");
+    let same = parse_fixture("diff/old.rs");
+    assert!(diff(&old, &same).is_empty());
+}
+
+#[test]
+fn test_added_and_removed_nodes() {
+    let (old, new) = fixture_graphs();
+    let result = diff(&old, &new);
+
+    assert_eq!(paths(&result.added), ["root::added", "root::transport"]);
+    assert_eq!(paths(&result.removed), ["root::removed"]);
+    assert_eq!(result.added[0].kind, NodeKind::Function);
+    assert_eq!(result.added[1].kind, NodeKind::Module);
+}
+
+#[test]
+fn test_moved_and_renamed_nodes() {
+    let (old, new) = fixture_graphs();
+    let result = diff(&old, &new);
+
+    assert_eq!(result.moved.len(), 1);
+    assert_eq!(result.moved[0].old.path, "root::net::connect");
+    assert_eq!(result.moved[0].new.path, "root::transport::connect");
+    assert_eq!(result.moved[0].similarity, 1.0);
+
+    assert_eq!(result.renamed.len(), 1);
+    assert_eq!(result.renamed[0].old.path, "root::parse_config");
+    assert_eq!(result.renamed[0].new.path, "root::read_config");
+}
+
+#[test]
+fn test_modified_nodes() {
+    let (old, new) = fixture_graphs();
+    let result = diff(&old, &new);
+
+    let modified = result
+        .modified
+        .iter()
+        .find(|m| m.old.path == "root::modified")
+        .expect("modified function not reported");
+    assert!(modified.signature_changed);
+    assert!(modified.body_changed);
+    assert_eq!(modified.old.id, modified.new.id);
+
+    // `net` lost its only item
+    assert!(result.modified.iter().any(|m| m.old.path == "root::net"));
+    for path in ["root::unchanged", "root::Config"] {
+        assert!(
+            result.modified.iter().all(|m| m.old.path != path),
+            "{} reported as modified",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_relation_changes() {
+    let (old, new) = fixture_graphs();
+    let result = diff(&old, &new);
+
+    let transport = find_module_by_name(&new, "transport").unwrap();
+    let connect = find_function_by_name(&new, "connect").unwrap();
+    assert!(result
+        .added_relations
+        .iter()
+        .any(|r| r.source == transport.id
+            && r.target == connect.id
+            && r.kind == RelationKind::Contains));
+
+    let net = find_module_by_name(&old, "net").unwrap();
+    let old_connect = find_function_by_name(&old, "connect").unwrap();
+    assert!(result.removed_relations.iter().any(|r| r.source == net.id
+        && r.target == old_connect.id
+        && r.kind == RelationKind::Contains));
+
+    // `u64` is only written in the new graph
+    let u64_type = new
+        .type_graph
+        .iter()
+        .find(|t| matches!(&t.kind, TypeKind::Named { path, .. } if path == &["u64"]))
+        .unwrap();
+    assert_eq!(result.added_types, vec![u64_type.id]);
+}
+
+#[test]
+fn test_diff_serialization_round_trip() {
+    let (old, new) = fixture_graphs();
+    let result = diff(&old, &new);
+
+    let json = serde_json::to_string(&result).expect("Failed to serialize to JSON");
+    let from_json: GraphDiff = serde_json::from_str(&json).expect("Failed to read JSON");
+    assert_eq!(from_json, result);
+
+    let ron = ron::to_string(&result).expect("Failed to serialize to RON");
+    let from_ron: GraphDiff = ron::from_str(&ron).expect("Failed to read RON");
+    assert_eq!(from_ron, result);
+}
@@ -0,0 +1,112 @@
+use crate::common::*;
+use syn_parser::parser::nodes::{TypeDefNode, ValueKind};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::VisibilityKind;
+use std::path::Path;
+use syn_parser::analyze_code_with_options;
+use syn_parser::config::{ParseOptions, VisibilityFilter};
+
+#[test]
+fn test_foreign_mod_parsing() {
+    let graph = parse_fixture("foreign.rs");
+
+    assert_eq!(graph.foreign_mods.len(), 2);
+
+    let zlib = &graph.foreign_mods[0];
+    assert_eq!(zlib.abi.as_deref(), Some("C"));
+    assert!(!zlib.is_unsafe);
+    assert_eq!(zlib.items.len(), 5);
+    assert!(zlib
+        .docstring
+        .as_ref()
+        .unwrap()
+        .contains("compression library"));
+
+    let links: Vec<_> = zlib.links().collect();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].args, vec!["name = \"z\"".to_string()]);
+
+    // A bare `extern` block has no ABI string
+    assert_eq!(graph.foreign_mods[1].abi, None);
+}
+
+#[test]
+fn test_foreign_function_parsing() {
+    let graph = parse_fixture("foreign.rs");
+
+    let compress = find_function_by_name(&graph, "compress").expect("compress not found");
+    assert!(compress.is_foreign);
+    assert_eq!(compress.visibility, VisibilityKind::Public);
+    assert_eq!(compress.parameters.len(), 4);
+    assert!(compress.return_type.is_some());
+    assert_eq!(compress.body, None);
+
+    let version = find_function_by_name(&graph, "zlibVersion").expect("zlibVersion not found");
+    assert!(version
+        .docstring
+        .as_ref()
+        .unwrap()
+        .contains("library version"));
+
+    let regular =
+        find_function_by_name(&graph, "regular_function").expect("regular_function not found");
+    assert!(!regular.is_foreign);
+}
+
+#[test]
+fn test_foreign_statics_and_types() {
+    let graph = parse_fixture("foreign.rs");
+
+    let errmsg = graph
+        .values
+        .iter()
+        .find(|v| v.name == "z_errmsg")
+        .expect("z_errmsg not found");
+    assert_eq!(errmsg.kind, ValueKind::ForeignStatic { is_mutable: false });
+    assert_eq!(errmsg.value, None);
+
+    let verbose = graph
+        .values
+        .iter()
+        .find(|v| v.name == "z_verbose")
+        .expect("z_verbose not found");
+    assert_eq!(verbose.kind, ValueKind::ForeignStatic { is_mutable: true });
+
+    assert!(graph.defined_types.iter().any(|def| matches!(
+        def,
+        TypeDefNode::ForeignType(ty) if ty.name == "z_stream"
+    )));
+}
+
+#[test]
+fn test_foreign_items_are_contained_in_module() {
+    let graph = parse_fixture("foreign.rs");
+    let root = &graph.modules[0];
+
+    let zlib = &graph.foreign_mods[0];
+    assert!(root.items.contains(&zlib.id));
+    for id in std::iter::once(&zlib.id).chain(&zlib.items) {
+        assert!(graph
+            .relations
+            .iter()
+            .any(|r| r.source == root.id && r.target == *id && r.kind == RelationKind::Contains));
+    }
+}
+
+#[test]
+fn test_private_foreign_items_are_filtered() {
+    let options = ParseOptions {
+        visibility: VisibilityFilter::Public,
+        ..ParseOptions::default()
+    };
+    let path = Path::new("tests/fixtures/foreign.rs");
+    let graph = analyze_code_with_options(path, &options).unwrap();
+
+    // `abort` and `z_verbose` are private
+    assert!(find_function_by_name(&graph, "compress").is_some());
+    assert!(find_function_by_name(&graph, "abort").is_none());
+    assert!(graph.values.iter().all(|v| v.name != "z_verbose"));
+
+    let abort_block = &graph.foreign_mods[1];
+    assert!(abort_block.items.is_empty());
+}
@@ -217,3 +217,25 @@ fn test_default_params_function_parsing() {
     assert_eq!(function.parameters[1].name, Some("optional".to_string()));
     assert!(function.return_type.is_some());
 }
+
+#[test]
+fn test_function_body_is_source_text() {
+    let graph = parse_fixture("bodies.rs");
+
+    let function = find_function_by_name(&graph, "commented").expect("commented not found");
+    let body = function.body.as_ref().unwrap();
+    assert!(body.starts_with("{\n    // Double the input\n    let doubled = x * 2;"));
+    assert!(body.contains("/* then add one */"));
+    assert!(body.ends_with("doubled + 1\n}"));
+    assert_eq!(function.body_normalized, None);
+
+    let table = graph.values.iter().find(|v| v.name == "TABLE").unwrap();
+    assert!(table.value.as_ref().unwrap().contains("3, 4, // large"));
+
+    let square = graph.macros.iter().find(|m| m.name == "square").unwrap();
+    assert!(square
+        .body
+        .as_ref()
+        .unwrap()
+        .starts_with("{\n    // Single expression\n    ($x:expr) => {"));
+}
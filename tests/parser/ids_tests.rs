@@ -0,0 +1,119 @@
+use crate::common::*;
+use std::collections::HashSet;
+use syn_parser::parser::graph::CodeGraph;
+use syn_parser::parser::nodes::{NodeId, TypeDefNode};
+
+// IDs of the nodes that appear in both fixtures, by a description of the node
+fn shared_ids(graph: &CodeGraph) -> Vec<(String, NodeId)> {
+    let mut ids = Vec::new();
+    let point = find_struct_by_name(graph, "Point").unwrap();
+    ids.push(("struct Point".to_string(), point.id));
+    for field in &point.fields {
+        ids.push((format!("field {:?}", field.name), field.id));
+    }
+    for imp in &graph.impls {
+        ids.push((format!("impl with {}", imp.methods[0].name), imp.id));
+        for method in &imp.methods {
+            ids.push((format!("method {}", method.name), method.id));
+            for param in &method.parameters {
+                ids.push((
+                    format!("param {:?} of {}", param.name, method.name),
+                    param.id,
+                ));
+            }
+        }
+    }
+    let shapes = find_module_by_name(graph, "shapes").unwrap();
+    ids.push(("mod shapes".to_string(), shapes.id));
+    let shape = find_enum_by_name(graph, "Shape").unwrap();
+    ids.push(("enum Shape".to_string(), shape.id));
+    for variant in &shape.variants {
+        ids.push((format!("variant {}", variant.name), variant.id));
+        for (i, field) in variant.fields.iter().enumerate() {
+            ids.push((format!("field {} of {}", i, variant.name), field.id));
+        }
+    }
+    ids
+}
+
+#[test]
+fn test_ids_are_stable_across_runs() {
+    let first = parse_fixture("ids/before.rs");
+    let second = parse_fixture("ids/before.rs");
+    assert_eq!(shared_ids(&first), shared_ids(&second));
+}
+
+#[test]
+fn test_ids_survive_unrelated_edits() {
+    let before = parse_fixture("ids/before.rs");
+    let after = parse_fixture("ids/after.rs");
+    assert_eq!(shared_ids(&before), shared_ids(&after));
+
+    // Types are identified by how they are written
+    let field_types = |graph: &CodeGraph| {
+        let point = find_struct_by_name(graph, "Point").unwrap();
+        point.fields.iter().map(|f| f.type_id).collect::<Vec<_>>()
+    };
+    assert_eq!(field_types(&before), field_types(&after));
+}
+
+#[test]
+fn test_ids_are_unique() {
+    let graph = parse_fixture("ids/before.rs");
+    let ids = shared_ids(&graph);
+    let unique: HashSet<NodeId> = ids.iter().map(|(_, id)| *id).collect();
+    assert_eq!(unique.len(), ids.len());
+
+    // Items live in the namespace of their module
+    let shapes = find_module_by_name(&graph, "shapes").unwrap();
+    let shape = find_enum_by_name(&graph, "Shape").unwrap();
+    let point = find_struct_by_name(&graph, "Point").unwrap();
+    assert_eq!(shape.id.namespace(), shapes.id.namespace());
+    assert_ne!(point.id.namespace(), shapes.id.namespace());
+    assert!(graph.defined_types.iter().all(|def| match def {
+        TypeDefNode::Struct(s) => s.id.namespace() == point.id.namespace(),
+        _ => true,
+    }));
+}
+
+#[test]
+fn test_compact_ids() {
+    let graph = parse_fixture("ids/before.rs");
+    for (_, id) in shared_ids(&graph) {
+        assert_eq!(NodeId::from_compact(id.to_compact()), id);
+    }
+}
+
+#[test]
+fn test_macro_invocation_ids_survive_earlier_invocations() {
+    let before = parse_fixture("ids/before.rs");
+    let after = parse_fixture("ids/after.rs");
+    let invocation_ids = |graph: &CodeGraph| -> Vec<NodeId> {
+        graph.macro_invocations.iter().map(|m| m.id).collect()
+    };
+
+    // `after.rs` adds a `println!` in `zeroth`, ahead of those of `first` and `second`
+    let before_ids = invocation_ids(&before);
+    let after_ids = invocation_ids(&after);
+    assert_eq!(before_ids.len(), 3);
+    assert_eq!(after_ids.len(), 4);
+    assert_eq!(before_ids, after_ids[1..]);
+
+    // Invocations of the same macro in one function are still told apart
+    let unique: HashSet<NodeId> = after_ids.iter().copied().collect();
+    assert_eq!(unique.len(), after_ids.len());
+}
+
+#[test]
+fn test_type_ids_are_unique() {
+    for fixture in ["ids/before.rs", "traits.rs", "resolution.rs"] {
+        let graph = parse_fixture(fixture);
+        let ids: HashSet<NodeId> = graph.type_graph.iter().map(|t| t.id).collect();
+        assert_eq!(ids.len(), graph.type_graph.len(), "{}", fixture);
+        // Supertraits are not wrapped in a type of the same spelling
+        assert!(graph
+            .type_graph
+            .iter()
+            .all(|t| !t.related_types.contains(&t.id)));
+    }
+}
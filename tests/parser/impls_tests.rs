@@ -1,5 +1,91 @@
-// Placeholder for implementation parsing tests
+use crate::common::*;
+use syn_parser::parser::graph::CodeGraph;
+use syn_parser::parser::nodes::{ImplNode, TraitRef};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::TypeKind;
+
+// Impl of the trait whose path ends with `trait_name`
+fn find_trait_impl<'a>(graph: &'a CodeGraph, trait_name: &str) -> &'a ImplNode {
+    graph
+        .impls
+        .iter()
+        .find(|imp| {
+            graph
+                .type_graph
+                .iter()
+                .find(|t| Some(t.id) == imp.trait_type)
+                .is_some_and(|t| match &t.kind {
+                    TypeKind::Named { path, .. } => path.last().unwrap() == trait_name,
+                    _ => false,
+                })
+        })
+        .unwrap_or_else(|| panic!("impl of {} not found", trait_name))
+}
+
+#[test]
+fn test_all_impls_are_recorded() {
+    let graph = parse_fixture("impls.rs");
+    assert_eq!(graph.impls.len(), 7);
+
+    let inherent = find_impl_for_type(&graph, "Meters").unwrap();
+    assert_eq!(inherent.trait_type, None);
+    assert_eq!(inherent.trait_ref, None);
+    assert_eq!(inherent.methods[0].name, "new");
+}
+
+#[test]
+fn test_impl_before_trait_definition() {
+    let graph = parse_fixture("impls.rs");
+    let measure = find_trait_by_name(&graph, "Measure").unwrap();
+    let impl_node = find_trait_impl(&graph, "Measure");
+
+    assert_eq!(impl_node.trait_ref, Some(TraitRef::Local(measure.id)));
+    assert_eq!(impl_node.methods[0].name, "value");
+
+    // Associated items are linked to the trait's declarations in the second pass
+    let links = [
+        (impl_node.assoc_types[0].id, measure.assoc_types[0].id),
+        (impl_node.assoc_consts[0].id, measure.assoc_consts[0].id),
+    ];
+    for (source, target) in links {
+        assert!(graph.relations.iter().any(|r| r.source == source
+            && r.target == target
+            && r.kind == RelationKind::ImplementsItem));
+    }
+}
+
+#[test]
+fn test_private_trait_impl() {
+    let graph = parse_fixture("impls.rs");
+    let hidden = find_trait_by_name(&graph, "Hidden").unwrap();
+    let impl_node = find_trait_impl(&graph, "Hidden");
+
+    assert_eq!(impl_node.trait_ref, Some(TraitRef::Local(hidden.id)));
+}
+
 #[test]
-fn test_impl_parsing() {
-    // Add implementation parsing tests here
+fn test_external_trait_impls() {
+    let graph = parse_fixture("impls.rs");
+    let external = |path: &[&str]| {
+        Some(TraitRef::External {
+            path: path.iter().map(|s| s.to_string()).collect(),
+        })
+    };
+
+    assert_eq!(
+        find_trait_impl(&graph, "Display").trait_ref,
+        external(&["std", "fmt", "Display"])
+    );
+    assert_eq!(
+        find_trait_impl(&graph, "Clone").trait_ref,
+        external(&["std", "clone", "Clone"])
+    );
+    assert_eq!(
+        find_trait_impl(&graph, "Serialize").trait_ref,
+        external(&["serde", "Serialize"])
+    );
+    assert_eq!(
+        find_trait_impl(&graph, "Missing").trait_ref,
+        Some(TraitRef::Unresolved)
+    );
 }
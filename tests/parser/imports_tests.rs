@@ -0,0 +1,100 @@
+use crate::common::*;
+use syn_parser::parser::nodes::{ImportKind, ImportNode};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::VisibilityKind;
+
+fn find_import<'a>(imports: &'a [ImportNode], path: &[&str]) -> &'a ImportNode {
+    imports
+        .iter()
+        .find(|import| import.path == path)
+        .unwrap_or_else(|| panic!("import {:?} not found", path))
+}
+
+#[test]
+fn test_use_tree_leaves() {
+    let graph = parse_fixture("imports.rs");
+    let imports = &graph.modules[0].imports;
+
+    assert_eq!(imports.len(), 9);
+
+    // Every leaf of a group becomes its own import
+    let entry = find_import(imports, &["std", "collections", "hash_map", "Entry"]);
+    assert_eq!(entry.alias.as_deref(), Some("MapEntry"));
+    assert_eq!(entry.name(), Some("MapEntry"));
+    find_import(imports, &["std", "collections", "HashMap"]);
+    find_import(imports, &["std", "collections", "HashSet"]);
+
+    // `self` in a group imports the module itself
+    let fmt = find_import(imports, &["std", "fmt"]);
+    assert_eq!(fmt.name(), Some("fmt"));
+    find_import(imports, &["std", "fmt", "Display"]);
+
+    let prelude = find_import(imports, &["std", "io", "prelude"]);
+    assert!(prelude.is_glob);
+    assert_eq!(prelude.name(), None);
+}
+
+#[test]
+fn test_import_visibility_and_kind() {
+    let graph = parse_fixture("imports.rs");
+    let imports = &graph.modules[0].imports;
+
+    let arc = find_import(imports, &["std", "sync", "Arc"]);
+    assert_eq!(arc.visibility, VisibilityKind::Public);
+    assert_eq!(arc.kind, ImportKind::UseStatement);
+
+    let rc = find_import(imports, &["std", "rc", "Rc"]);
+    assert_eq!(
+        rc.visibility,
+        VisibilityKind::Restricted(vec!["crate".to_string()])
+    );
+    assert_eq!(rc.alias.as_deref(), Some("Shared"));
+
+    let alloc = find_import(imports, &["alloc"]);
+    assert_eq!(alloc.kind, ImportKind::ExternCrate);
+    assert_eq!(alloc.alias.as_deref(), Some("allocator"));
+    assert_eq!(alloc.span.start_line, 8);
+}
+
+#[test]
+fn test_imports_belong_to_their_module() {
+    let graph = parse_fixture("imports.rs");
+    let inner = find_module_by_name(&graph, "inner").expect("inner not found");
+
+    assert_eq!(inner.imports.len(), 1);
+    assert_eq!(inner.imports[0].path, vec!["super", "Shared"]);
+
+    for module in &graph.modules {
+        for import in &module.imports {
+            assert!(graph.relations.iter().any(|r| r.source == module.id
+                && r.target == import.id
+                && r.kind == RelationKind::Uses));
+        }
+    }
+}
+
+#[test]
+fn test_imports_nested_in_functions() {
+    let graph = parse_fixture("imports.rs");
+    let nested = find_function_by_name(&graph, "nested").expect("nested not found");
+
+    let paths: Vec<_> = graph
+        .block_imports
+        .iter()
+        .map(|i| i.path.join("::"))
+        .collect();
+    assert_eq!(paths, ["std::fmt::Write", "core"]);
+    assert_eq!(graph.block_imports[1].kind, ImportKind::ExternCrate);
+
+    // They are not imports of the module
+    let root = &graph.modules[0];
+    assert!(root
+        .imports
+        .iter()
+        .all(|i| i.path != ["std", "fmt", "Write"]));
+    for import in &graph.block_imports {
+        assert!(graph.relations.iter().any(|r| r.source == nested.id
+            && r.target == import.id
+            && r.kind == RelationKind::Uses));
+    }
+}
@@ -8,3 +8,17 @@ pub mod impls_tests;
 pub mod modules_tests;
 pub mod macros_tests;
 pub mod visibility_tests;
+pub mod diagnostics_tests;
+pub mod foreign_tests;
+pub mod spans_tests;
+pub mod comments_tests;
+pub mod imports_tests;
+pub mod resolution_tests;
+pub mod ids_tests;
+pub mod paths_tests;
+pub mod semver_tests;
+pub mod diff_tests;
+pub mod cache_tests;
+pub mod parallel_tests;
+pub mod watch_tests;
+pub mod calls_tests;
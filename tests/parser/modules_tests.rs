@@ -1,5 +1,91 @@
+use crate::common::*;
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::SynParserError;
+
 // Placeholder for module parsing tests
 #[test]
 fn test_module_parsing() {
     // Add module parsing tests here
 }
+
+#[test]
+fn test_crate_follows_out_of_line_modules() {
+    let graph = parse_fixture_crate("sample_crate");
+
+    let root = find_module_by_name(&graph, "crate").expect("crate root module not found");
+    assert!(root
+        .docstring
+        .as_ref()
+        .unwrap()
+        .contains("whole-crate analysis"));
+
+    // `mod.rs`, non-`mod.rs` and `#[path]` files are all followed
+    assert!(find_struct_by_name(&graph, "Connection").is_some());
+    assert!(find_struct_by_name(&graph, "Client").is_some());
+    assert!(find_function_by_name(&graph, "retry_count").is_some());
+    assert!(find_function_by_name(&graph, "helper").is_some());
+    assert!(find_function_by_name(&graph, "platform_name").is_some());
+    assert!(find_struct_by_name(&graph, "Nested").is_some());
+
+    // Docs from the module file are attached to the module
+    let net = find_module_by_name(&graph, "net").expect("net module not found");
+    assert!(net.docstring.as_ref().unwrap().contains("mod.rs"));
+}
+
+#[test]
+fn test_crate_module_tree() {
+    let graph = parse_fixture_crate("sample_crate");
+
+    let root = find_module_by_name(&graph, "crate").unwrap();
+    let net = find_module_by_name(&graph, "net").unwrap();
+    let client = find_module_by_name(&graph, "client").unwrap();
+    let retry = find_module_by_name(&graph, "retry").unwrap();
+    let inline = find_module_by_name(&graph, "inline").unwrap();
+    let nested = find_module_by_name(&graph, "nested").unwrap();
+
+    assert_eq!(root.submodules.len(), 4, "net, util, platform and inline");
+    assert!(root.submodules.contains(&net.id));
+    assert!(root.submodules.contains(&inline.id));
+    assert_eq!(net.submodules, vec![client.id]);
+    assert_eq!(client.submodules, vec![retry.id]);
+    assert_eq!(inline.submodules, vec![nested.id]);
+
+    // Items are contained by the module whose file defines them
+    let retry_count = find_function_by_name(&graph, "retry_count").unwrap();
+    assert_eq!(retry.items, vec![retry_count.id]);
+    assert!(graph
+        .relations
+        .iter()
+        .any(|r| r.kind == RelationKind::Contains
+            && r.source == retry.id
+            && r.target == retry_count.id));
+
+    let root_function = find_function_by_name(&graph, "root_function").unwrap();
+    assert!(root.items.contains(&root_function.id));
+}
+
+#[test]
+fn test_crate_missing_module_file() {
+    let result =
+        syn_parser::analyze_crate(std::path::Path::new("tests/fixtures/missing_module.rs"));
+
+    let err = result.expect_err("missing module file should be reported");
+    assert!(matches!(err, SynParserError::Resolution { .. }));
+    assert_eq!(
+        err.path(),
+        Some(std::path::Path::new("tests/fixtures/missing_module.rs"))
+    );
+    assert!(err
+        .to_string()
+        .contains("file not found for module `missing`"));
+}
+
+#[test]
+fn test_crate_module_file_cycle() {
+    let result = syn_parser::analyze_crate(std::path::Path::new("tests/fixtures/cyclic_crate"));
+
+    let err = result.expect_err("a module including its own file should be reported");
+    assert!(matches!(err, SynParserError::Resolution { .. }));
+    assert!(err.path().unwrap().ends_with("looped.rs"));
+    assert!(err.to_string().contains("module `again` (line 4)"));
+}
@@ -0,0 +1,78 @@
+use crate::common::*;
+use std::collections::HashSet;
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::CodeGraph;
+
+fn to_ron(graph: &CodeGraph) -> String {
+    ron::to_string(graph).expect("Failed to serialize graph")
+}
+
+#[test]
+fn test_crate_analysis_is_deterministic() {
+    // Files are visited on several threads, but merged in declaration order
+    let first = parse_fixture_crate("sample_crate");
+    for _ in 0..4 {
+        assert_eq!(to_ron(&parse_fixture_crate("sample_crate")), to_ron(&first));
+    }
+}
+
+#[test]
+fn test_module_declared_twice_keeps_ids_unique() {
+    let graph = parse_fixture_crate("platform_crate");
+
+    let imps: Vec<_> = graph.modules.iter().filter(|m| m.name == "imp").collect();
+    assert_eq!(imps.len(), 2);
+    assert_ne!(imps[0].id, imps[1].id);
+    assert_eq!(imps[0].items.len(), 2);
+    assert_eq!(imps[1].items.len(), 2);
+
+    let spawns: Vec<_> = graph
+        .functions
+        .iter()
+        .filter(|f| f.name == "spawn")
+        .collect();
+    assert_eq!(spawns.len(), 2);
+    assert_ne!(spawns[0].id, spawns[1].id);
+    // Each module contains its own `spawn`
+    for (imp, spawn) in imps.iter().zip(&spawns) {
+        assert!(imp.items.contains(&spawn.id));
+        assert!(graph.relations.iter().any(|r| r.source == imp.id
+            && r.target == spawn.id
+            && r.kind == RelationKind::Contains));
+    }
+
+    let mut ids = HashSet::new();
+    for node in graph.path_nodes() {
+        assert!(ids.insert(node.id()), "duplicate ID for {}", node.path());
+    }
+}
+
+#[test]
+fn test_macro_invocation_linked_across_files() {
+    let graph = parse_fixture_crate("platform_crate");
+
+    let trace = graph.macros.iter().find(|m| m.name == "trace").unwrap();
+    let invocation = graph
+        .macro_invocations
+        .iter()
+        .find(|i| i.path.ends_with("trace"))
+        .unwrap();
+    assert_eq!(invocation.span.file.file_name().unwrap(), "worker.rs");
+    assert_eq!(invocation.macro_id, Some(trace.id));
+    assert!(graph.relations.iter().any(|r| r.source == invocation.id
+        && r.target == trace.id
+        && r.kind == RelationKind::MacroUse));
+}
+
+#[test]
+fn test_caller_spans_survive_crate_analysis() {
+    // Files are parsed on other threads too, whose spans are freed afterwards, but the
+    // spans of the calling thread are left alone
+    let file: syn::File = syn::parse_str("fn kept() {}").unwrap();
+    parse_fixture_crate("sample_crate");
+    let syn::Item::Fn(func) = &file.items[0] else {
+        panic!("expected a function");
+    };
+    let start = func.sig.ident.span().start();
+    assert_eq!((start.line, start.column), (1, 3));
+}
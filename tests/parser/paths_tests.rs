@@ -0,0 +1,108 @@
+use crate::common::*;
+use syn_parser::parser::graph::NodeRef;
+use syn_parser::parser::nodes::TypeDefNode;
+
+#[test]
+fn test_item_paths() {
+    let graph = parse_fixture("paths.rs");
+
+    let client = find_struct_by_name(&graph, "Client").unwrap();
+    assert_eq!(client.path, "root::net::client::Client");
+    let connect = find_trait_by_name(&graph, "Connect").unwrap();
+    assert_eq!(connect.path, "root::net::Connect");
+    assert_eq!(connect.methods[0].path, "root::net::Connect::connect");
+    assert_eq!(
+        find_function_by_name(&graph, "main").unwrap().path,
+        "root::main"
+    );
+    assert_eq!(
+        find_module_by_name(&graph, "server").unwrap().path,
+        "root::net::server"
+    );
+    assert_eq!(graph.modules[0].path, "root");
+
+    let port = graph
+        .values
+        .iter()
+        .find(|v| v.name == "DEFAULT_PORT")
+        .unwrap();
+    assert_eq!(port.path, "root::net::DEFAULT_PORT");
+
+    // Exported macros live at the crate root
+    let connect_macro = graph.macros.iter().find(|m| m.name == "connect").unwrap();
+    assert_eq!(connect_macro.path, "root::connect");
+}
+
+#[test]
+fn test_same_name_in_different_modules() {
+    let graph = parse_fixture("paths.rs");
+
+    let mut error_paths: Vec<&str> = graph
+        .defined_types
+        .iter()
+        .filter_map(|def| match def {
+            TypeDefNode::Enum(e) if e.name == "Error" => Some(e.path.as_str()),
+            _ => None,
+        })
+        .collect();
+    error_paths.sort();
+    assert_eq!(
+        error_paths,
+        ["root::net::client::Error", "root::net::server::Error"]
+    );
+}
+
+#[test]
+fn test_method_paths() {
+    let graph = parse_fixture("paths.rs");
+
+    let mut method_paths: Vec<&str> = graph
+        .impls
+        .iter()
+        .flat_map(|i| &i.methods)
+        .map(|m| m.path.as_str())
+        .collect();
+    method_paths.sort();
+    assert_eq!(
+        method_paths,
+        [
+            "<&[u8] as root::net::Connect>::connect",
+            "<root::net::client::Client as std::ops::Drop>::drop",
+            "<root::net::server::Server as root::net::Connect>::connect",
+            "root::net::client::Client::new",
+            "root::net::server::Server::new",
+        ]
+    );
+}
+
+#[test]
+fn test_find_by_path() {
+    let graph = parse_fixture("paths.rs");
+
+    let Some(NodeRef::Function(new)) = graph.find_by_path("root::net::server::Server::new") else {
+        panic!("Server::new not found");
+    };
+    assert_eq!(new.name, "new");
+
+    let error = graph.find_by_path("root::net::client::Error").unwrap();
+    assert!(matches!(error, NodeRef::TypeDef(TypeDefNode::Enum(_))));
+    assert_eq!(error.path(), "root::net::client::Error");
+
+    let module = graph.find_by_path("root::net").unwrap();
+    assert_eq!(module.id(), find_module_by_name(&graph, "net").unwrap().id);
+
+    assert!(graph.find_by_path("root::net::Client").is_none());
+}
+
+#[test]
+fn test_path_index() {
+    let graph = parse_fixture("paths.rs");
+    let index = graph.path_index();
+
+    // Every node has its own path
+    assert_eq!(index.len(), graph.path_nodes().count());
+    for node in graph.path_nodes() {
+        assert_eq!(index[node.path()].id(), node.id());
+    }
+    assert!(index.contains_key("<root::net::client::Client as std::ops::Drop>::drop"));
+}
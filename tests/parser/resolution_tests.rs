@@ -0,0 +1,214 @@
+use crate::common::*;
+use syn_parser::parser::graph::CodeGraph;
+use syn_parser::parser::nodes::{NodeId, StructNode, TypeDefNode};
+use syn_parser::parser::relations::RelationKind;
+use syn_parser::parser::types::{Resolution, TypeKind, TypeNode};
+
+// Type of the field with the given name
+fn field_type<'a>(graph: &'a CodeGraph, owner: &StructNode, field: &str) -> &'a TypeNode {
+    let field = owner
+        .fields
+        .iter()
+        .find(|f| f.name.as_deref() == Some(field))
+        .unwrap_or_else(|| panic!("field {} not found", field));
+    graph
+        .type_graph
+        .iter()
+        .find(|t| t.id == field.type_id)
+        .expect("field type not found")
+}
+
+// Items the type is linked to by `ResolvesTo` relations
+fn resolves_to(graph: &CodeGraph, ty: &TypeNode) -> Vec<NodeId> {
+    graph
+        .relations
+        .iter()
+        .filter(|r| r.source == ty.id && r.kind == RelationKind::ResolvesTo)
+        .map(|r| r.target)
+        .collect()
+}
+
+// Struct with the given name declared in the module with the given name
+fn struct_in_module<'a>(graph: &'a CodeGraph, module: &str, name: &str) -> &'a StructNode {
+    let module = find_module_by_name(graph, module).expect("module not found");
+    graph
+        .defined_types
+        .iter()
+        .find_map(|def| match def {
+            TypeDefNode::Struct(s) if s.name == name && module.items.contains(&s.id) => Some(s),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("struct {} not found", name))
+}
+
+#[test]
+fn test_resolves_through_scopes_and_imports() {
+    let graph = parse_fixture("resolution.rs");
+    let circle = struct_in_module(&graph, "shapes", "Circle");
+    let root_config = struct_in_module(&graph, "root", "Config");
+    let drawing = struct_in_module(&graph, "nested", "Drawing");
+    let wrapper = find_struct_by_name(&graph, "Wrapper").unwrap();
+
+    // Glob import
+    let outline = field_type(&graph, drawing, "outline");
+    assert_eq!(outline.resolution, Some(Resolution::Local));
+    assert_eq!(resolves_to(&graph, outline), vec![circle.id]);
+
+    // `super::super` import with an alias
+    let root = field_type(&graph, drawing, "root");
+    assert_eq!(resolves_to(&graph, root), vec![root_config.id]);
+
+    // `super::` path
+    let parent = field_type(&graph, drawing, "parent");
+    assert_eq!(resolves_to(&graph, parent), vec![circle.id]);
+
+    // Aliased `self::` import, relative path and `crate::` path
+    for field in ["round", "shape", "absolute"] {
+        let ty = field_type(&graph, wrapper, field);
+        assert_eq!(resolves_to(&graph, ty), vec![circle.id], "field {}", field);
+    }
+}
+
+#[test]
+fn test_same_name_resolves_per_module() {
+    let graph = parse_fixture("resolution.rs");
+    let root_config = struct_in_module(&graph, "root", "Config");
+    let shapes_config = struct_in_module(&graph, "shapes", "Config");
+    let drawing = struct_in_module(&graph, "nested", "Drawing");
+
+    // `Config` is written in the root module and, through a glob import, in `nested`.
+    // It names a different struct in each, so each use is its own type.
+    let local = field_type(&graph, drawing, "local");
+    assert_eq!(resolves_to(&graph, local), vec![shapes_config.id]);
+    let wrapper = find_struct_by_name(&graph, "Wrapper").unwrap();
+    let config = field_type(&graph, wrapper, "config");
+    assert_eq!(resolves_to(&graph, config), vec![root_config.id]);
+    assert_ne!(local.id, config.id);
+
+    // The aliased import in `nested` names the root struct, so it is the same type
+    let root = field_type(&graph, drawing, "root");
+    assert_eq!(root.id, config.id);
+    assert_eq!(root.spellings, ["RootConfig", "Config"]);
+}
+
+#[test]
+fn test_spellings_of_one_type_are_merged() {
+    let graph = parse_fixture("resolution.rs");
+    let buffers = find_struct_by_name(&graph, "Buffers").unwrap();
+
+    let short = field_type(&graph, buffers, "short");
+    let long = field_type(&graph, buffers, "long");
+    assert_eq!(short.id, long.id);
+    assert_eq!(short.spellings, ["Vec < u8 >", "std :: vec :: Vec < u8 >"]);
+
+    // Only one node is left for the type
+    let nodes = graph.type_graph.iter().filter(|t| t.id == short.id).count();
+    assert_eq!(nodes, 1);
+
+    // Different generic arguments are different types
+    let words = field_type(&graph, buffers, "words");
+    assert_ne!(words.id, short.id);
+}
+
+#[test]
+fn test_merging_keeps_other_relations() {
+    let graph = parse_fixture("resolution.rs");
+    let framed = find_trait_by_name(&graph, "Framed").unwrap();
+    let outline = find_trait_by_name(&graph, "Outline").unwrap();
+
+    // Both bounds name `Outline`, so they point to the same type once merged
+    let bounds: Vec<_> = graph
+        .relations
+        .iter()
+        .filter(|r| r.source == framed.id && r.kind == RelationKind::Inherits)
+        .map(|r| r.target)
+        .collect();
+    assert_eq!(bounds.len(), 2);
+    assert_eq!(bounds[0], bounds[1]);
+
+    let ty = graph.type_graph.iter().find(|t| t.id == bounds[0]).unwrap();
+    assert_eq!(resolves_to(&graph, ty), vec![outline.id]);
+}
+
+#[test]
+fn test_external_primitive_generic_and_unresolved() {
+    let graph = parse_fixture("resolution.rs");
+    let root_config = struct_in_module(&graph, "root", "Config");
+    let drawing = struct_in_module(&graph, "nested", "Drawing");
+    let wrapper = find_struct_by_name(&graph, "Wrapper").unwrap();
+    let std = Some(Resolution::External {
+        crate_name: "std".to_string(),
+    });
+
+    // Imported from `std`, and from the prelude
+    assert_eq!(field_type(&graph, root_config, "values").resolution, std);
+    assert_eq!(field_type(&graph, root_config, "name").resolution, std);
+    assert_eq!(
+        field_type(&graph, wrapper, "external").resolution,
+        Some(Resolution::External {
+            crate_name: "serde".to_string()
+        })
+    );
+
+    let circle = struct_in_module(&graph, "shapes", "Circle");
+    assert_eq!(
+        field_type(&graph, circle, "radius").resolution,
+        Some(Resolution::Primitive)
+    );
+    assert_eq!(
+        field_type(&graph, wrapper, "inner").resolution,
+        Some(Resolution::Generic)
+    );
+
+    let missing = field_type(&graph, drawing, "missing");
+    assert_eq!(missing.resolution, Some(Resolution::Unresolved));
+    assert!(resolves_to(&graph, missing).is_empty());
+}
+
+#[test]
+fn test_associated_types_of_generics() {
+    let graph = parse_fixture("resolution.rs");
+    let projections = find_struct_by_name(&graph, "Projections").unwrap();
+
+    // `T::Item` is not an item of a crate named `T`
+    for field in ["item", "iter"] {
+        let ty = field_type(&graph, projections, field);
+        assert_eq!(ty.resolution, Some(Resolution::Generic), "{}", field);
+        assert!(resolves_to(&graph, ty).is_empty());
+    }
+}
+
+#[test]
+fn test_only_named_types_are_resolved() {
+    let graph = parse_fixture("resolution.rs");
+    for ty in &graph.type_graph {
+        assert_eq!(
+            ty.resolution.is_some(),
+            matches!(ty.kind, TypeKind::Named { .. }),
+            "{:?}",
+            ty
+        );
+    }
+}
+
+#[test]
+fn test_impls_resolve_to_their_type() {
+    let graph = parse_fixture("assoc_items.rs");
+    let impl_node = find_impl_for_type(&graph, "Bytes").expect("impl not found");
+    let bytes = find_struct_by_name(&graph, "Bytes").unwrap();
+    let trait_node = find_trait_by_name(&graph, "Source").unwrap();
+
+    let self_type = graph
+        .type_graph
+        .iter()
+        .find(|t| t.id == impl_node.self_type);
+    assert_eq!(resolves_to(&graph, self_type.unwrap()), vec![bytes.id]);
+    let trait_type = graph
+        .type_graph
+        .iter()
+        .find(|t| Some(t.id) == impl_node.trait_type);
+    assert_eq!(
+        resolves_to(&graph, trait_type.unwrap()),
+        vec![trait_node.id]
+    );
+}
@@ -0,0 +1,176 @@
+use crate::common::*;
+use syn_parser::api_diff;
+use syn_parser::parser::semver::{ApiDiff, ChangeKind, SemverLevel};
+
+fn fixture_diff() -> ApiDiff {
+    let old = parse_fixture("semver/old.rs");
+    let new = parse_fixture("semver/new.rs");
+    api_diff(&old, &new)
+}
+
+// Changes to the item at `path`, as (kind, level, description)
+fn changes_of(diff: &ApiDiff, path: &str) -> Vec<(ChangeKind, SemverLevel, String)> {
+    diff.changes
+        .iter()
+        .filter(|change| change.path == path)
+        .map(|change| (change.kind, change.level, change.description.clone()))
+        .collect()
+}
+
+#[test]
+fn test_identical_graphs_have_no_changes() {
+    let old = parse_fixture("semver/old.rs");
+    let same = parse_fixture("semver/old.rs");
+    let diff = api_diff(&old, &same);
+    assert!(diff.changes.is_empty(), "{:?}", diff.changes);
+    assert_eq!(diff.level(), SemverLevel::Patch);
+}
+
+#[test]
+fn test_function_changes() {
+    let diff = fixture_diff();
+
+    assert_eq!(
+        changes_of(&diff, "root::removed"),
+        [(
+            ChangeKind::Removed,
+            SemverLevel::Major,
+            "removed function".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::added"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Minor,
+            "added function".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::connect"),
+        [(
+            ChangeKind::Changed,
+            SemverLevel::Major,
+            "parameter `addr` changed type from `String` to `& str`".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::unchanged"),
+        [(
+            ChangeKind::Changed,
+            SemverLevel::Patch,
+            "body changed".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_generic_bound_changes() {
+    let diff = fixture_diff();
+
+    assert_eq!(
+        changes_of(&diff, "root::bounded"),
+        [(
+            ChangeKind::Changed,
+            SemverLevel::Major,
+            "tightened bounds on `T`".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::loosened"),
+        [(
+            ChangeKind::Changed,
+            SemverLevel::Minor,
+            "loosened bounds on `T`".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_field_and_variant_changes() {
+    let diff = fixture_diff();
+
+    // A struct with only public fields could be built with a literal
+    assert_eq!(
+        changes_of(&diff, "root::Config"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Major,
+            "added public field `timeout`".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::Handle"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Minor,
+            "added public field `label`".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::Mode"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Major,
+            "added variant `Auto`".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::Level"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Minor,
+            "added variant `Max`".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_trait_changes() {
+    let diff = fixture_diff();
+
+    assert_eq!(
+        changes_of(&diff, "root::Store::delete"),
+        [(
+            ChangeKind::Added,
+            SemverLevel::Major,
+            "added required trait method".to_string()
+        )]
+    );
+    assert_eq!(
+        changes_of(&diff, "root::Store::len")[0].1,
+        SemverLevel::Minor
+    );
+
+    // Methods of a new trait break no implementors
+    assert_eq!(changes_of(&diff, "root::Fresh")[0].1, SemverLevel::Minor);
+    assert_eq!(
+        changes_of(&diff, "root::Fresh::run")[0].1,
+        SemverLevel::Minor
+    );
+}
+
+#[test]
+fn test_overall_level() {
+    let diff = fixture_diff();
+    assert_eq!(diff.level(), SemverLevel::Major);
+
+    // Private items are not part of the API
+    assert!(diff.changes.iter().all(|c| !c.path.contains("internal")));
+
+    let major: Vec<&str> = diff
+        .at_least(SemverLevel::Major)
+        .map(|c| c.path.as_str())
+        .collect();
+    assert_eq!(
+        major,
+        [
+            "root::Config",
+            "root::Mode",
+            "root::Store::delete",
+            "root::bounded",
+            "root::connect",
+            "root::removed",
+        ]
+    );
+}
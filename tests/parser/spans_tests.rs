@@ -0,0 +1,85 @@
+use crate::common::*;
+use std::path::Path;
+
+#[test]
+fn test_function_span() {
+    let graph = parse_fixture("functions.rs");
+    let source = std::fs::read_to_string("tests/fixtures/functions.rs").unwrap();
+
+    let function =
+        find_function_by_name(&graph, "regular_function").expect("regular_function not found");
+    let span = &function.span;
+
+    assert_eq!(span.file, Path::new("tests/fixtures/functions.rs"));
+    assert_eq!((span.start_line, span.start_col), (4, 0));
+    assert_eq!((span.end_line, span.end_col), (6, 1));
+    assert!(span
+        .slice(&source)
+        .unwrap()
+        .starts_with("pub fn regular_function()"));
+    assert!(span.slice(&source).unwrap().ends_with('}'));
+}
+
+#[test]
+fn test_struct_and_field_spans() {
+    let graph = parse_fixture("structs.rs");
+    let source = std::fs::read_to_string("tests/fixtures/structs.rs").unwrap();
+
+    let sample = find_struct_by_name(&graph, "SampleStruct").expect("SampleStruct not found");
+    assert_eq!((sample.span.start_line, sample.span.end_line), (4, 6));
+    assert_eq!(
+        sample.fields[0].span.slice(&source),
+        Some("pub field: String")
+    );
+
+    // Item spans include attributes and doc comments
+    let attributed =
+        find_struct_by_name(&graph, "AttributedStruct").expect("AttributedStruct not found");
+    assert!(attributed
+        .span
+        .slice(&source)
+        .unwrap()
+        .starts_with("#[derive(Debug)]"));
+}
+
+#[test]
+fn test_root_module_spans_whole_file() {
+    let graph = parse_fixture("structs.rs");
+    let source = std::fs::read_to_string("tests/fixtures/structs.rs").unwrap();
+
+    let root = &graph.modules[0];
+    assert_eq!(root.span.start_line, 1);
+    assert_eq!(root.span.byte_range, 0..source.len());
+}
+
+#[test]
+fn test_spans_point_into_module_files() {
+    let graph = parse_fixture_crate("sample_crate");
+
+    let client = find_struct_by_name(&graph, "Client").expect("Client not found");
+    assert_eq!(
+        client.span.file,
+        Path::new("tests/fixtures/sample_crate/src/net/client.rs")
+    );
+    assert_eq!(client.span.start_line, 5);
+
+    // Out-of-line modules point at their declaration in the parent file
+    let net = find_module_by_name(&graph, "net").expect("net not found");
+    assert_eq!(
+        net.span.file,
+        Path::new("tests/fixtures/sample_crate/src/lib.rs")
+    );
+}
+
+#[test]
+fn test_macro_invocation_spans() {
+    let graph = parse_fixture("functions.rs");
+
+    let println = graph
+        .macro_invocations
+        .iter()
+        .find(|m| m.path == "println")
+        .expect("println! invocation not found");
+    assert_eq!(println.span.start_line, 5);
+    assert_eq!(println.macro_id, None);
+}
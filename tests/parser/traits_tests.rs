@@ -5,6 +5,7 @@ fn test_trait_parsing() {
 }
 
 use crate::common::*;
+use syn_parser::parser::relations::RelationKind;
 use syn_parser::parser::types::*;
 
 #[test]
@@ -79,3 +80,82 @@ fn test_private_trait_parsing() {
         panic!("PrivateTrait not found in graph when using find_trait_by_name.")
     }
 }
+
+#[test]
+fn test_trait_associated_items() {
+    let graph = parse_fixture("assoc_items.rs");
+
+    let source = find_trait_by_name(&graph, "Source").expect("Source not found");
+    assert_eq!(source.methods.len(), 1);
+
+    let names: Vec<_> = source.assoc_types.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["Item", "View", "Error"]);
+
+    let item = &source.assoc_types[0];
+    assert_eq!(item.bounds.len(), 1);
+    assert_eq!(item.type_id, None);
+    assert!(item.docstring.as_ref().unwrap().contains("type of item"));
+
+    // Generic associated types keep their own parameters
+    let view = &source.assoc_types[1];
+    assert_eq!(view.generic_params.len(), 1);
+    assert!(matches!(
+        &view.generic_params[0].kind,
+        GenericParamKind::Lifetime { name, .. } if name == "a"
+    ));
+
+    // Defaults are recorded as the type
+    assert!(source.assoc_types[2].type_id.is_some());
+
+    assert_eq!(source.assoc_consts.len(), 2);
+    assert_eq!(source.assoc_consts[0].name, "CAPACITY");
+    assert_eq!(source.assoc_consts[0].value, None);
+    assert_eq!(source.assoc_consts[1].value.as_deref(), Some("\"source\""));
+}
+
+#[test]
+fn test_impl_associated_items_link_to_trait() {
+    let graph = parse_fixture("assoc_items.rs");
+
+    let source = find_trait_by_name(&graph, "Source").expect("Source not found");
+    let bytes_impl = graph
+        .impls
+        .iter()
+        .find(|imp| imp.trait_type.is_some())
+        .expect("impl Source for Bytes not found");
+
+    assert_eq!(bytes_impl.assoc_types.len(), 2);
+    assert_eq!(bytes_impl.assoc_consts.len(), 1);
+    assert_eq!(bytes_impl.assoc_consts[0].value.as_deref(), Some("1024"));
+
+    let item = &bytes_impl.assoc_types[0];
+    assert_eq!(item.name, "Item");
+    assert!(item.type_id.is_some());
+
+    let links: Vec<_> = graph
+        .relations
+        .iter()
+        .filter(|r| r.kind == RelationKind::ImplementsItem)
+        .map(|r| (r.source, r.target))
+        .collect();
+    assert!(links.contains(&(item.id, source.assoc_types[0].id)));
+    assert!(links.contains(&(bytes_impl.assoc_types[1].id, source.assoc_types[1].id)));
+    assert!(links.contains(&(bytes_impl.assoc_consts[0].id, source.assoc_consts[0].id)));
+}
+
+#[test]
+fn test_trait_alias_parsing() {
+    let graph = parse_fixture("assoc_items.rs");
+
+    assert_eq!(graph.trait_aliases.len(), 1);
+    let alias = &graph.trait_aliases[0];
+    assert_eq!(alias.name, "SharedSource");
+    assert_eq!(alias.visibility, VisibilityKind::Public);
+    assert_eq!(alias.bounds.len(), 3);
+    assert!(alias
+        .docstring
+        .as_ref()
+        .unwrap()
+        .contains("shared between threads"));
+    assert!(graph.modules[0].items.contains(&alias.id));
+}
@@ -1,5 +1,161 @@
-// Placeholder for visibility handling tests
+use crate::common::*;
+use syn_parser::parser::api::{EffectiveVisibility, PublicApi};
+use syn_parser::parser::graph::CodeGraph;
+use syn_parser::parser::types::VisibilityKind;
+use syn_parser::public_api;
+
+// Effective visibility and public paths of the item with the given canonical path
+fn item<'a>(api: &'a PublicApi, path: &str) -> (EffectiveVisibility, &'a [String]) {
+    let item = api
+        .items
+        .iter()
+        .find(|item| item.node.path() == path)
+        .unwrap_or_else(|| panic!("item {} not found", path));
+    (item.visibility, &item.public_paths)
+}
+
+fn api_fixture() -> CodeGraph {
+    parse_fixture("public_api.rs")
+}
+
+#[test]
+fn test_public_items_through_public_modules() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    assert_eq!(
+        item(&api, "root"),
+        (EffectiveVisibility::Public, &["root".to_string()][..])
+    );
+    assert_eq!(
+        item(&api, "root::net::Client"),
+        (
+            EffectiveVisibility::Public,
+            &["root::net::Client".to_string()][..]
+        )
+    );
+    assert_eq!(
+        item(&api, "root::net::helper").0,
+        EffectiveVisibility::Crate
+    );
+    assert_eq!(
+        item(&api, "root::net::private_helper").0,
+        EffectiveVisibility::Private
+    );
+}
+
+#[test]
+fn test_pub_items_in_private_modules() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    // Not re-exported, so only usable inside the crate
+    let (visibility, paths) = item(&api, "root::internal::Hidden");
+    assert_eq!(visibility, EffectiveVisibility::Crate);
+    assert!(paths.is_empty());
+    assert_eq!(item(&api, "root::internal").0, EffectiveVisibility::Private);
+}
+
+#[test]
+fn test_restricted_items_by_the_module_they_reach() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    let to_parent = find_function_by_name(&graph, "to_parent").unwrap();
+    assert_eq!(
+        to_parent.visibility,
+        VisibilityKind::Restricted(vec!["crate".to_string(), "scoped".to_string()])
+    );
+
+    // Seen by the parent module, unlike items without `pub`
+    let visibility = |name: &str| item(&api, &format!("root::scoped::inner::{}", name)).0;
+    assert_eq!(visibility("to_parent"), EffectiveVisibility::Crate);
+    assert_eq!(visibility("to_scoped"), EffectiveVisibility::Crate);
+    assert_eq!(visibility("to_self"), EffectiveVisibility::Private);
+    assert_eq!(visibility("private"), EffectiveVisibility::Private);
+}
+
+#[test]
+fn test_reexports() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    let public_paths = |path: &str| {
+        let (visibility, paths) = item(&api, path);
+        assert_eq!(visibility, EffectiveVisibility::Public, "{}", path);
+        paths.to_vec()
+    };
+    assert_eq!(public_paths("root::internal::Exported"), ["root::Exported"]);
+    assert_eq!(public_paths("root::internal::Shape"), ["root::Shape"]);
+    // Through a renamed module
+    assert_eq!(public_paths("root::internal::deep"), ["root::tools"]);
+    assert_eq!(
+        public_paths("root::internal::deep::tool"),
+        ["root::tools::tool"]
+    );
+    // Through a glob
+    assert_eq!(public_paths("root::constants::VERSION"), ["root::VERSION"]);
+}
+
+#[test]
+fn test_methods_and_macros() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    let by_name = |name: &str| {
+        let item = api
+            .items
+            .iter()
+            .find(|item| item.node.name() == name)
+            .unwrap_or_else(|| panic!("item {} not found", name));
+        (item.visibility, item.public_paths.clone())
+    };
+    assert_eq!(
+        by_name("connect"),
+        (
+            EffectiveVisibility::Public,
+            vec!["root::net::Client::connect".to_string()]
+        )
+    );
+    assert_eq!(by_name("retry").0, EffectiveVisibility::Private);
+    assert_eq!(
+        by_name("fmt").1,
+        ["<root::net::Client as std::fmt::Display>::fmt"]
+    );
+    assert_eq!(by_name("area").1, ["root::Shape::area"]);
+
+    // `#[macro_export]` macros are public at the root
+    assert_eq!(by_name("shout").1, ["root::shout"]);
+}
+
 #[test]
-fn test_visibility_handling() {
-    // Add visibility handling tests here
+fn test_public_items_view() {
+    let graph = api_fixture();
+    let api = public_api(&graph);
+
+    let mut paths: Vec<&str> = api
+        .public_items()
+        .flat_map(|item| item.public_paths.iter().map(String::as_str))
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            "<root::net::Client as std::fmt::Display>::fmt",
+            "root",
+            "root::Exported",
+            "root::Shape",
+            "root::Shape::area",
+            "root::VERSION",
+            "root::net",
+            "root::net::Client",
+            "root::net::Client::connect",
+            "root::shout",
+            "root::tools",
+            "root::tools::tool",
+        ]
+    );
+
+    let client = find_struct_by_name(&graph, "Client").unwrap();
+    assert_eq!(api.get(client.id).unwrap().node.path(), "root::net::Client");
 }
@@ -0,0 +1,121 @@
+use crate::common::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+use syn_parser::parser::diff::NodeKind;
+use syn_parser::{CrateWatcher, GraphEvent, ParseOptions};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Copy the `sample_crate` fixture somewhere its files can be edited
+fn copy_sample_crate(test_name: &str) -> PathBuf {
+    let dest = std::env::temp_dir().join(format!(
+        "syn_parser_watch_{}_{}",
+        test_name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dest);
+    copy_dir(Path::new("tests/fixtures/sample_crate"), &dest);
+    dest
+}
+
+fn copy_dir(src: &Path, dest: &Path) {
+    fs::create_dir_all(dest).unwrap();
+    for entry in fs::read_dir(src).unwrap() {
+        let path = entry.unwrap().path();
+        let target = dest.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+// Collect events until one matches `done`, failing after `TIMEOUT`
+fn wait_for(events: &Receiver<GraphEvent>, done: impl Fn(&GraphEvent) -> bool) -> Vec<GraphEvent> {
+    let deadline = Instant::now() + TIMEOUT;
+    let mut received = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let event = events
+            .recv_timeout(remaining)
+            .unwrap_or_else(|_| panic!("no matching event, got {:?}", received));
+        let matched = done(&event);
+        received.push(event);
+        if matched {
+            return received;
+        }
+    }
+}
+
+#[test]
+fn test_watcher_publishes_added_and_removed_nodes() {
+    let root = copy_sample_crate("added");
+    let watcher = CrateWatcher::start(&root, ParseOptions::default()).unwrap();
+    let events = watcher.subscribe();
+    assert!(find_function_by_name(&watcher.graph(), "helper").is_some());
+
+    fs::write(
+        root.join("src/util.rs"),
+        "pub fn added() -> u8 {\n    1\n}\n",
+    )
+    .unwrap();
+    let received = wait_for(
+        &events,
+        |event| matches!(event, GraphEvent::NodeRemoved(node) if node.path == "crate::util::helper"),
+    );
+    assert!(received.iter().any(|event| matches!(
+        event,
+        GraphEvent::NodeAdded(node)
+            if node.path == "crate::util::added" && node.kind == NodeKind::Function
+    )));
+
+    let graph = watcher.graph();
+    assert!(find_function_by_name(&graph, "added").is_some());
+    assert!(find_function_by_name(&graph, "helper").is_none());
+    drop(watcher);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_watcher_publishes_modified_nodes() {
+    let root = copy_sample_crate("modified");
+    let watcher = CrateWatcher::start(&root, ParseOptions::default()).unwrap();
+    let events = watcher.subscribe();
+
+    fs::write(
+        root.join("src/util.rs"),
+        "pub fn helper() {\n    let _ = 1;\n}\n",
+    )
+    .unwrap();
+    wait_for(&events, |event| {
+        matches!(
+            event,
+            GraphEvent::NodeModified(change)
+                if change.new.path == "crate::util::helper"
+                    && change.body_changed
+                    && !change.signature_changed
+        )
+    });
+    drop(watcher);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_watcher_reports_failed_analysis() {
+    let root = copy_sample_crate("failed");
+    let watcher = CrateWatcher::start(&root, ParseOptions::default()).unwrap();
+    let events = watcher.subscribe();
+
+    fs::write(root.join("src/util.rs"), "pub fn helper( {}\n").unwrap();
+    wait_for(
+        &events,
+        |event| matches!(event, GraphEvent::AnalysisFailed { message } if message.contains("util.rs")),
+    );
+    // The last good graph is kept
+    assert!(find_function_by_name(&watcher.graph(), "helper").is_some());
+    drop(watcher);
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -1,5 +1,18 @@
+use std::path::Path;
+use syn_parser::{analyze_code, save_to_json, SynParserError};
+
 // Placeholder for JSON serialization tests
 #[test]
 fn test_json_serialization() {
     // Add JSON serialization tests here
 }
+
+#[test]
+fn test_unwritable_output_is_write_error() {
+    let graph = analyze_code(Path::new("tests/fixtures/functions.rs")).unwrap();
+    let path = Path::new("tests/fixtures/no_such_dir/graph.json");
+    let err = save_to_json(&graph, path).expect_err("missing directory should fail");
+
+    assert!(matches!(err, SynParserError::Write { .. }));
+    assert_eq!(err.path(), Some(path));
+}